@@ -2,23 +2,123 @@
 
 use dirty::*;
 use math::*;
+use geom::*;
 use gfx::shapes;
 use input::Key;
 
 const INCRE: usize = 100;
 
+#[derive(Clone, Copy, PartialEq)]
+enum Scene {
+	Crowd,
+	Forest,
+}
+
 struct Game {
 	tex: gfx::Texture,
-	count: usize,
+	tree: gfx::Mesh,
+	rock: gfx::Mesh,
+	cam: gfx::PerspectiveCam,
+	scene: Scene,
+	instancing: bool,
+	sprites: Vec<gfx::Instance>,
+	trees: Vec<gfx::Instance>,
+	rocks: Vec<gfx::Instance>,
+}
+
+impl Game {
+
+	fn count(&self) -> usize {
+		return match self.scene {
+			Scene::Crowd => self.sprites.len(),
+			Scene::Forest => self.trees.len() + self.rocks.len(),
+		};
+	}
+
+	fn add(&mut self, d: &Ctx) {
+
+		let w = d.gfx.width() as f32 * 0.5;
+		let h = d.gfx.height() as f32 * 0.5;
+
+		match self.scene {
+
+			Scene::Crowd => {
+				for _ in 0..INCRE {
+					self.sprites.push(
+						gfx::Instance::new(
+							mat4!()
+								.t2(vec2!(rand(-w, w), rand(-h, h)))
+								.s2(vec2!(32.0 / self.tex.width() as f32))
+						)
+						.color(rgba!(rand(0.5, 1.0), rand(0.5, 1.0), rand(0.5, 1.0), 1))
+					);
+				}
+			},
+
+			Scene::Forest => {
+				let r = 8.0 + (self.trees.len() as f32).sqrt();
+				for _ in 0..INCRE {
+					let pos = vec3!(rand(-r, r), 0, rand(-r, -1.0));
+					if rand(0.0, 1.0) < 0.7 {
+						self.trees.push(
+							gfx::Instance::new(
+								mat4!()
+									.t3(pos)
+									.s3(vec3!(1, rand(1.0, 3.0), 1))
+							)
+							.color(rgba!(0.2, rand(0.5, 0.9), 0.3, 1))
+						);
+					} else {
+						self.rocks.push(
+							gfx::Instance::new(
+								mat4!()
+									.t3(pos)
+									.ry(rand(0.0, 3.14))
+									.s3(vec3!(rand(0.2, 0.6)))
+							)
+							.color(rgba!(0.5, 0.5, 0.5, 1))
+						);
+					}
+				}
+			},
+
+		}
+
+	}
+
 }
 
 impl State for Game {
 
 	fn init(d: &mut Ctx) -> Result<Self> {
-		return Ok(Self {
+
+		let tree = meshgen::cylinder(0.3, 2.0, 6)
+			.ok_or_else(|| format!("failed to create cylinder"))?;
+
+		let mut game = Self {
 			tex: gfx::Texture::from_bytes(d.gfx, include_bytes!("res/acid2.png"))?,
-			count: INCRE,
-		});
+			tree: gfx::Mesh::from_meshdata(d.gfx, &tree)?,
+			rock: gfx::Mesh::from_meshdata(d.gfx, &meshgen::cube())?,
+			cam: gfx::PerspectiveCam {
+				fov: f32::to_radians(60.0),
+				up: vec3!(0, 1, 0),
+				aspect: d.gfx.width() as f32 / d.gfx.height() as f32,
+				near: 0.1,
+				far: 1024.0,
+				pos: vec3!(0, 3, 6),
+				dir: vec3!(0, -0.2, -1).unit(),
+			},
+			scene: Scene::Crowd,
+			instancing: true,
+			sprites: vec![],
+			trees: vec![],
+			rocks: vec![],
+		};
+
+		game.add(d);
+
+		return Ok(game);
+
 	}
 
 	fn event(&mut self, d: &mut Ctx, e: &input::Event) -> Result<()> {
@@ -26,10 +126,23 @@ impl State for Game {
 		use input::Event::*;
 
 		match e {
+			Resize(w, h) => {
+				self.cam.aspect = *w as f32 / *h as f32;
+			},
 			KeyPress(k) => {
 				match *k {
 					Key::Esc => d.window.quit(),
-					Key::Space => self.count += INCRE,
+					Key::Space => self.add(d),
+					Key::Tab => self.instancing = !self.instancing,
+					Key::M => {
+						self.scene = match self.scene {
+							Scene::Crowd => Scene::Forest,
+							Scene::Forest => Scene::Crowd,
+						};
+						if self.count() == 0 {
+							self.add(d);
+						}
+					},
 					_ => {},
 				}
 			},
@@ -40,29 +153,47 @@ impl State for Game {
 
 	}
 
-	fn update(&mut self, d: &mut Ctx) -> Result<()> {
+	fn frame(&mut self, d: &mut Ctx) -> Result<()> {
 
-		d.window.set_title(&format!("FPS: {} DCS: {} OBJS: {}", d.app.fps(), d.gfx.draw_calls(), self.count));
+		d.window.set_title(&format!("FPS: {} DCS: {} OBJS: {}", d.app.fps(), d.gfx.draw_calls(), self.count()));
 
-		return Ok(());
+		match self.scene {
 
-	}
+			Scene::Crowd => {
+				if self.instancing {
+					d.gfx.draw(&shapes::instanced_sprite(&self.tex, &self.sprites))?;
+				} else {
+					for i in &self.sprites {
+						d.gfx.draw_t(i.transform, &shapes::sprite(&self.tex).color(i.color))?;
+					}
+				}
+			},
+
+			Scene::Forest => {
+
+				let instancing = self.instancing;
+				let tree = &self.tree;
+				let rock = &self.rock;
+				let trees = &self.trees;
+				let rocks = &self.rocks;
+
+				d.gfx.use_cam(&self.cam, |gfx| {
+					if instancing {
+						gfx.draw(&shapes::instanced_mesh(tree, trees))?;
+						gfx.draw(&shapes::instanced_mesh(rock, rocks))?;
+					} else {
+						for i in trees {
+							gfx.draw_t(i.transform, &shapes::mesh(tree).color(i.color))?;
+						}
+						for i in rocks {
+							gfx.draw_t(i.transform, &shapes::mesh(rock).color(i.color))?;
+						}
+					}
+					return Ok(());
+				})?;
+
+			},
 
-	fn draw(&mut self, d: &mut Ctx) -> Result<()> {
-
-		let w = d.gfx.width();
-		let h = d.gfx.height();
-
-		for _ in 0..self.count {
-			d.gfx.draw_t(
-				mat4!()
-					.t2(vec2!(rand(-w, w) as f32 * 0.5, rand(-h, h) as f32 * 0.5))
-					,
-				&shapes::sprite(&self.tex)
-					.width(32.0)
-					.height(32.0)
-					,
-			)?;
 		}
 
 		let c = if d.app.fps() >= 60 {
@@ -72,7 +203,7 @@ impl State for Game {
 		};
 
 		d.gfx.draw(
-			&shapes::rect(-vec2!(144, 96), vec2!(144, 84))
+			&shapes::rect(-vec2!(144, 112), vec2!(144, 84))
 				.fill(rgba!(0, 0, 0, 1))
 		)?;
 
@@ -88,10 +219,26 @@ impl State for Game {
 			mat4!()
 				.ty(-54.0)
 				,
-			&shapes::text(&format!("{} faces", self.count))
+			&shapes::text(&format!("{} objs", self.count()))
 				.size(16.0)
 		)?;
 
+		d.gfx.draw_t(
+			mat4!()
+				.ty(-80.0)
+				,
+			&shapes::text(if self.instancing { "instanced (tab)" } else { "individual (tab)" })
+				.size(12.0)
+		)?;
+
+		d.gfx.draw_t(
+			mat4!()
+				.ty(-98.0)
+				,
+			&shapes::text("space: more, m: switch scene")
+				.size(12.0)
+		)?;
+
 		return Ok(());
 
 	}
@@ -102,9 +249,9 @@ fn main() {
 
 	if let Err(err) = launcher()
 		.vsync(false)
+		.resizable(true)
 		.run::<Game>() {
 		elog!("{}", err);
 	}
 
 }
-
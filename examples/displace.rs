@@ -31,7 +31,8 @@ impl State for Game {
 			tex: Texture::from_bytes(d.gfx, include_bytes!("res/acid2.png"))?,
 			effect_tex: Texture::from_bytes_with_conf(d.gfx, include_bytes!("res/displace.png"), TextureConf {
 				filter: FilterMode::Linear,
-				wrap: WrapMode::Repeat,
+				wrap_x: WrapMode::Repeat,
+				wrap_y: WrapMode::Repeat,
 				..TextureConf::default()
 			})?,
 			shader: Shader::from_frag(d.gfx, include_str!("res/displace.frag"))?,
@@ -21,8 +21,14 @@ struct Viewer {
 	loader: Task<Result<gfx::ModelData>>,
 	draw_wireframe: bool,
 	run_anim: bool,
+	clip: usize,
 	draw_bound: bool,
+	shading: Option<gfx::Shading>,
 	helping: bool,
+	picker: gfx::Picker,
+	dragged: bool,
+	pick_pos: Option<Vec2>,
+	selected: Option<usize>,
 }
 
 fn load_file(path: impl AsRef<Path>) -> Result<Task<Result<gfx::ModelData>>> {
@@ -40,6 +46,8 @@ impl Viewer {
 	fn update_model(&mut self, model: gfx::Model) {
 
 		self.resetting = true;
+		self.selected = None;
+		self.clip = 0;
 		self.model = Some(model);
 
 	}
@@ -55,6 +63,52 @@ impl Viewer {
 
 	}
 
+	fn model_transform(&self, model: &gfx::Model) -> Mat4 {
+		return mat4!()
+			.t2(self.pos)
+			.s3(vec3!(self.scale))
+			.ry(self.rot.x.to_radians())
+			.rx(self.rot.y.to_radians())
+			.t3(-model.center());
+	}
+
+	fn anim_time(&self, model: &gfx::Model, time: f32) -> f32 {
+		let anim_len = model.clip(self.clip).map(|c| c.len()).unwrap_or(0.0);
+		if self.run_anim && anim_len > 0.0 {
+			return time - f32::floor(time / anim_len) * anim_len;
+		} else {
+			return 0.0;
+		}
+	}
+
+	fn pick(&mut self, d: &mut Ctx, pos: Vec2) -> Result<()> {
+
+		let model = match &self.model {
+			Some(model) => model,
+			None => return Ok(()),
+		};
+
+		let t = self.anim_time(model, d.app.time().as_secs_f32());
+		let transform = self.model_transform(model);
+		let clip = self.clip;
+
+		self.picker.render(d.gfx, |gfx| {
+			return gfx.push_t(transform, |gfx| {
+				return gfx.draw(
+					&shapes::model(&model)
+						.clip(clip)
+						.time(t)
+						.pick_nodes(0)
+				);
+			});
+		})?;
+
+		self.selected = self.picker.at(pos).map(|id| id as usize);
+
+		return Ok(());
+
+	}
+
 }
 
 impl State for Viewer {
@@ -70,9 +124,15 @@ impl State for Viewer {
 			loader: load_file("examples/res/truck.obj")?,
 			draw_wireframe: false,
 			draw_bound: false,
+			shading: Some(gfx::Shading::PBR),
 			run_anim: true,
+			clip: 0,
 			helping: false,
 			scale: 0.0,
+			picker: gfx::Picker::new(d.gfx)?,
+			dragged: false,
+			pick_pos: None,
+			selected: None,
 		});
 
 	}
@@ -81,6 +141,8 @@ impl State for Viewer {
 
 		use input::Event::*;
 
+		self.picker.event(d.gfx, e)?;
+
 		match e {
 
 			KeyPress(k) => {
@@ -96,6 +158,20 @@ impl State for Viewer {
 					Key::B => self.draw_bound = !self.draw_bound,
 					Key::H => self.helping = !self.helping,
 					Key::T => self.run_anim = !self.run_anim,
+					Key::C => {
+						if let Some(model) = &self.model {
+							if !model.clips().is_empty() {
+								self.clip = (self.clip + 1) % model.clips().len();
+							}
+						}
+					},
+					Key::M => {
+						self.shading = match self.shading {
+							Some(gfx::Shading::PBR) => Some(gfx::Shading::BlinnPhong),
+							Some(gfx::Shading::BlinnPhong) => None,
+							None => Some(gfx::Shading::PBR),
+						};
+					},
 					_ => {},
 				}
 
@@ -129,6 +205,7 @@ impl State for Viewer {
 				if d.window.mouse_down(Mouse::Left) {
 
 					self.resetting = false;
+					self.dragged = true;
 					self.rot += *delta;
 
 					if self.rot.x >= 360.0 {
@@ -151,6 +228,17 @@ impl State for Viewer {
 
 			},
 
+			MousePress(Mouse::Left) => {
+				self.dragged = false;
+			},
+
+			// click without dragging to select a node
+			MouseRelease(Mouse::Left) => {
+				if !self.dragged {
+					self.pick_pos = Some(d.window.mouse_pos());
+				}
+			},
+
 			FileDrop(path) => {
 				self.load_file(&path)?;
 			},
@@ -163,7 +251,7 @@ impl State for Viewer {
 
 	}
 
-	fn update(&mut self, d: &mut Ctx) -> Result<()> {
+	fn frame(&mut self, d: &mut Ctx) -> Result<()> {
 
 		let dt = d.app.dt().as_secs_f32();
 
@@ -219,41 +307,43 @@ impl State for Viewer {
 
 		}
 
-		return Ok(());
-
-	}
-
-	fn draw(&mut self, d: &mut Ctx) -> Result<()> {
+		if let Some(pos) = self.pick_pos.take() {
+			self.pick(d, pos)?;
+		}
 
 		let time = d.app.time().as_secs_f32();
 
 		if let Some(model) = &self.model {
 
-			let center = model.center();
+			d.gfx.push_t(self.model_transform(model), |gfx| {
+
+				let t = self.anim_time(model, time);
+
+				let shape = shapes::model(&model)
+// 					.wireframe(self.draw_wireframe)
+					.clip(self.clip)
+					.time(t)
+					;
+
+				if let Some(shading) = self.shading {
 
-			d.gfx.push_t(mat4!()
-				.t2(self.pos)
-				.s3(vec3!(self.scale))
-				.ry(self.rot.x.to_radians())
-				.rx(self.rot.y.to_radians())
-				.t3(-center)
-			, |gfx| {
+					let lighting = gfx::Lighting::new(shading)
+						.ambient(rgba!(0.2, 0.2, 0.2, 1))
+						.light(gfx::Light::directional(vec3!(-1, -1, -1)).intensity(2.0))
+						.light(gfx::Light::directional(vec3!(1, 0.5, 0.5)).intensity(0.5))
+						;
+
+					gfx.use_lighting(&lighting, |gfx| {
+						return gfx.draw(&shape);
+					})?;
 
-				let t = if self.run_anim {
-					let anim_len = model.anim_len();
-					time - f32::floor(time / anim_len) * anim_len
 				} else {
-					0.0
-				};
 
-				gfx.draw_with(&self.shader, &(), |gfx| {
-					gfx.draw(
-						&shapes::model(&model)
-// 							.wireframe(self.draw_wireframe)
-							.time(t)
-					)?;
-					return Ok(());
-				})?;
+					gfx.draw_with(&self.shader, &(), |gfx| {
+						return gfx.draw(&shape);
+					})?;
+
+				}
 
 				if self.draw_bound {
 					let bbox = model.bbox();
@@ -300,6 +390,29 @@ impl State for Viewer {
 
 				})?;
 
+				if let (Some(id), Some(model)) = (self.selected, &self.model) {
+
+					let name = model
+						.node_name(id)
+						.map(|n| n.to_owned())
+						.unwrap_or(format!("node {}", id));
+
+					gfx.push_t(mat4!()
+						.ty(-40.0)
+					, |gfx| {
+
+						gfx.draw(
+							&shapes::text(&format!("selected: {}", name))
+								.size(9.0)
+								.align(gfx::Origin::TopLeft)
+						)?;
+
+						return Ok(());
+
+					})?;
+
+				}
+
 			}
 
 			return Ok(());
@@ -317,12 +430,15 @@ impl State for Viewer {
 				let msg = [
 					"W/A/S/D:  move",
 					"<drag>:   rotate",
+					"<click>:  select",
 					"<scroll>: scale",
 					"<space>:  reset",
 					"L:        wireframe",
+					"M:        shading",
 					"B:        bound",
 					"F:        fullscreen",
 					"T:        anim",
+					"C:        next clip",
 					"<esc>:    quit",
 				];
 
@@ -148,8 +148,8 @@ impl State for Game {
 			MIDI(msg) => {
 
 				match msg {
-					midi::Msg::NoteOn(n, _) => self.press_note(Note::new(*n)),
-					midi::Msg::NoteOff(n, _) => self.release_note(Note::new(*n)),
+					midi::Msg::NoteOn(_, n, _) => self.press_note(Note::new(*n)),
+					midi::Msg::NoteOff(_, n, _) => self.release_note(Note::new(*n)),
 					_ => {},
 				}
 
@@ -0,0 +1,676 @@
+// wengwengweng
+
+use std::collections::HashSet;
+
+use super::*;
+
+pub type NodeID = usize;
+
+/// A Node in an Audio [`Graph`](struct.Graph.html)
+///
+/// each input receives the sum of everything connected to it, scaled by the connection gain
+pub trait Node: Send {
+	/// number of input ports, 0 for sources
+	fn inputs(&self) -> usize {
+		return 1;
+	}
+	/// number of output ports
+	fn outputs(&self) -> usize {
+		return 1;
+	}
+	/// process a frame, `inputs` and `outputs` have the length of [`inputs()`](#method.inputs) and [`outputs()`](#method.outputs)
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Edge {
+	from: (NodeID, usize),
+	to: (NodeID, usize),
+	gain: f32,
+}
+
+// a node in processing order, with where to read its inputs
+struct Step {
+	node: Arc<Mutex<dyn Node>>,
+	// for each input port, the output slots and gains summed into it
+	inputs: Vec<Vec<(usize, f32)>>,
+	out_offset: usize,
+	out_count: usize,
+}
+
+/// Audio Node Graph
+///
+/// a graph is a [`Stream`](trait.Stream.html), play it with [`Audio::stream`](struct.Audio.html#method.stream), connections can be changed at any time through the mutex
+/// ```no_run
+/// # use dirty::*;
+/// # use audio::*;
+/// # use std::sync::{Arc, Mutex};
+/// # fn f(music: impl Stream + 'static, voice: impl Stream + 'static) -> Result<()> {
+/// let mut g = Graph::new();
+/// let out = g.output();
+///
+/// let music = g.add(Arc::new(Mutex::new(StreamNode::new(music))));
+/// let voice = g.add(Arc::new(Mutex::new(StreamNode::new(voice))));
+/// let duck = g.add(Arc::new(Mutex::new(Duck::new(0.8))));
+/// let reverb = g.add(Arc::new(Mutex::new(EffectNode::new(Delay::default()))));
+///
+/// // duck music under voice
+/// g.connect(music, duck)?;
+/// g.connect_port((voice, 0), (duck, Duck::SIDECHAIN), 1.0)?;
+/// g.connect(duck, out)?;
+/// g.connect(voice, out)?;
+///
+/// // send some voice to a shared reverb
+/// g.connect_port((voice, 0), (reverb, 0), 0.3)?;
+/// g.connect(reverb, out)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Graph {
+	nodes: HashMap<NodeID, Arc<Mutex<dyn Node>>>,
+	edges: Vec<Edge>,
+	output: NodeID,
+	last_id: NodeID,
+	steps: Vec<Step>,
+	frames: Vec<Frame>,
+	input_buf: Vec<Frame>,
+	out_slot: Option<usize>,
+	dirty: bool,
+}
+
+impl Graph {
+
+	pub fn new() -> Self {
+
+		let mut g = Self {
+			nodes: hmap![],
+			edges: vec![],
+			output: 0,
+			last_id: 0,
+			steps: vec![],
+			frames: vec![],
+			input_buf: vec![],
+			out_slot: None,
+			dirty: true,
+		};
+
+		g.output = g.add(Arc::new(Mutex::new(Gain::new(1.0))));
+
+		return g;
+
+	}
+
+	/// the final output node, a [`Gain`](struct.Gain.html)
+	pub fn output(&self) -> NodeID {
+		return self.output;
+	}
+
+	/// add a node, keep a clone of the `Arc` to change its parameters later
+	pub fn add<N: Node + 'static>(&mut self, n: Arc<Mutex<N>>) -> NodeID {
+
+		let id = self.last_id;
+
+		self.nodes.insert(id, n);
+		self.last_id += 1;
+		self.dirty = true;
+
+		return id;
+
+	}
+
+	/// remove a node and all its connections, the output node can't be removed
+	pub fn remove(&mut self, id: NodeID) {
+
+		if id == self.output {
+			return;
+		}
+
+		self.nodes.remove(&id);
+		self.edges.retain(|e| e.from.0 != id && e.to.0 != id);
+		self.dirty = true;
+
+	}
+
+	/// connect the first output of a node to the first input of another
+	pub fn connect(&mut self, from: NodeID, to: NodeID) -> Result<()> {
+		return self.connect_port((from, 0), (to, 0), 1.0);
+	}
+
+	/// connect an output port to an input port with a gain, replacing the gain if already connected
+	pub fn connect_port(&mut self, from: (NodeID, usize), to: (NodeID, usize), gain: f32) -> Result<()> {
+
+		let out_count = self.port_count(from.0, |n| n.outputs())?;
+		let in_count = self.port_count(to.0, |n| n.inputs())?;
+
+		if from.1 >= out_count {
+			return Err(format!("node {} doesn't have output {}", from.0, from.1));
+		}
+
+		if to.1 >= in_count {
+			return Err(format!("node {} doesn't have input {}", to.0, to.1));
+		}
+
+		if let Some(e) = self.edges.iter_mut().find(|e| e.from == from && e.to == to) {
+			e.gain = gain;
+			return Ok(());
+		}
+
+		if self.reaches(to.0, from.0) {
+			return Err(format!("connecting node {} to {} creates a cycle", from.0, to.0));
+		}
+
+		self.edges.push(Edge {
+			from: from,
+			to: to,
+			gain: gain,
+		});
+
+		self.dirty = true;
+
+		return Ok(());
+
+	}
+
+	/// remove all connections from a node to another
+	pub fn disconnect(&mut self, from: NodeID, to: NodeID) {
+		self.edges.retain(|e| e.from.0 != from || e.to.0 != to);
+		self.dirty = true;
+	}
+
+	/// remove a connection between ports
+	pub fn disconnect_port(&mut self, from: (NodeID, usize), to: (NodeID, usize)) {
+		self.edges.retain(|e| e.from != from || e.to != to);
+		self.dirty = true;
+	}
+
+	/// set the gain of all connections from a node to another
+	pub fn set_gain(&mut self, from: NodeID, to: NodeID, gain: f32) {
+		for e in &mut self.edges {
+			if e.from.0 == from && e.to.0 == to {
+				e.gain = gain;
+			}
+		}
+		self.dirty = true;
+	}
+
+	fn port_count(&self, id: NodeID, f: impl FnOnce(&dyn Node) -> usize) -> Result<usize> {
+		let node = self.nodes
+			.get(&id)
+			.ok_or_else(|| format!("node {} not found", id))?;
+		let node = node
+			.lock()
+			.map_err(|_| format!("failed to lock node {}", id))?;
+		return Ok(f(&*node));
+	}
+
+	// if there's a path from a to b
+	fn reaches(&self, a: NodeID, b: NodeID) -> bool {
+
+		let mut stack = vec![a];
+		let mut visited = hset![];
+
+		while let Some(id) = stack.pop() {
+			if id == b {
+				return true;
+			}
+			if visited.insert(id) {
+				for e in &self.edges {
+					if e.from.0 == id {
+						stack.push(e.to.0);
+					}
+				}
+			}
+		}
+
+		return false;
+
+	}
+
+	// sort nodes that contribute to the output and flatten them into steps
+	fn build(&mut self) {
+
+		let mut order = vec![];
+		let mut visited = hset![];
+
+		fn visit(g: &Graph, id: NodeID, visited: &mut HashSet<NodeID>, order: &mut Vec<NodeID>) {
+			if !visited.insert(id) {
+				return;
+			}
+			for e in &g.edges {
+				if e.to.0 == id {
+					visit(g, e.from.0, visited, order);
+				}
+			}
+			order.push(id);
+		}
+
+		visit(self, self.output, &mut visited, &mut order);
+
+		let mut offsets = hmap![];
+		let mut slots = 0;
+		let mut steps = vec![];
+
+		for id in order {
+
+			let node = match self.nodes.get(&id) {
+				Some(n) => n.clone(),
+				None => continue,
+			};
+
+			let (in_count, out_count) = match node.lock() {
+				Ok(n) => (n.inputs(), n.outputs()),
+				Err(_) => continue,
+			};
+
+			let mut inputs = vec![vec![]; in_count];
+
+			for e in &self.edges {
+				if e.to.0 == id && e.to.1 < in_count {
+					if let Some(offset) = offsets.get(&e.from.0) {
+						inputs[e.to.1].push((offset + e.from.1, e.gain));
+					}
+				}
+			}
+
+			offsets.insert(id, slots);
+
+			steps.push(Step {
+				node: node,
+				inputs: inputs,
+				out_offset: slots,
+				out_count: out_count,
+			});
+
+			slots += out_count;
+
+		}
+
+		self.out_slot = offsets.get(&self.output).copied();
+		self.frames = vec![Frame::zero(); slots];
+		self.steps = steps;
+		self.dirty = false;
+
+	}
+
+}
+
+impl Default for Graph {
+	fn default() -> Self {
+		return Self::new();
+	}
+}
+
+impl Stream for Graph {
+
+	fn next(&mut self) -> Frame {
+
+		if self.dirty {
+			self.build();
+		}
+
+		for step in &self.steps {
+
+			self.input_buf.clear();
+
+			for port in &step.inputs {
+				self.input_buf.push(port
+					.iter()
+					.fold(Frame::zero(), |acc, (slot, gain)| acc + self.frames[*slot] * *gain));
+			}
+
+			let (_, rest) = self.frames.split_at_mut(step.out_offset);
+			let outputs = &mut rest[..step.out_count];
+
+			if let Ok(mut node) = step.node.lock() {
+				node.process(&self.input_buf, outputs);
+			}
+
+		}
+
+		return self.out_slot
+			.map(|s| self.frames[s])
+			.unwrap_or(Frame::zero());
+
+	}
+
+}
+
+/// Volume & Pan
+#[derive(Clone, Debug)]
+pub struct Gain {
+	pub volume: f32,
+	pub pan: Pan,
+}
+
+impl Gain {
+	pub fn new(v: f32) -> Self {
+		return Self {
+			volume: v,
+			pan: Pan::default(),
+		};
+	}
+}
+
+impl Node for Gain {
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]) {
+		outputs[0] = inputs[0] * self.pan * self.volume;
+	}
+}
+
+/// Sum Inputs with Levels
+#[derive(Clone, Debug)]
+pub struct Mix {
+	levels: Vec<f32>,
+}
+
+impl Mix {
+
+	pub fn new(inputs: usize) -> Self {
+		return Self {
+			levels: vec![1.0; inputs],
+		};
+	}
+
+	pub fn set_level(&mut self, input: usize, v: f32) {
+		if let Some(l) = self.levels.get_mut(input) {
+			*l = v;
+		}
+	}
+
+}
+
+impl Node for Mix {
+	fn inputs(&self) -> usize {
+		return self.levels.len();
+	}
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]) {
+		outputs[0] = inputs
+			.iter()
+			.zip(&self.levels)
+			.fold(Frame::zero(), |acc, (f, l)| acc + *f * *l);
+	}
+}
+
+/// Copy Input to Several Outputs
+#[derive(Clone, Debug)]
+pub struct Split {
+	count: usize,
+}
+
+impl Split {
+	pub fn new(outputs: usize) -> Self {
+		return Self {
+			count: outputs,
+		};
+	}
+}
+
+impl Node for Split {
+	fn outputs(&self) -> usize {
+		return self.count;
+	}
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]) {
+		for o in outputs {
+			*o = inputs[0];
+		}
+	}
+}
+
+/// Lower the Volume of the Main Input when the Sidechain Input is Loud
+#[derive(Clone, Debug)]
+pub struct Duck {
+	/// how much to reduce the volume when the sidechain is at threshold (0.0 - 1.0)
+	pub amount: f32,
+	/// sidechain level that causes full reduction
+	pub threshold: f32,
+	attack: f32,
+	release: f32,
+	env: f32,
+}
+
+impl Duck {
+
+	pub const MAIN: usize = 0;
+	pub const SIDECHAIN: usize = 1;
+
+	pub fn new(amount: f32) -> Self {
+		return Self {
+			amount: amount,
+			threshold: 0.1,
+			attack: time_coef(Duration::from_millis(10)),
+			release: time_coef(Duration::from_millis(300)),
+			env: 0.0,
+		};
+	}
+
+	pub fn threshold(mut self, t: f32) -> Self {
+		self.threshold = t;
+		return self;
+	}
+
+	/// how fast the ducking kicks in / recovers
+	pub fn times(mut self, attack: Duration, release: Duration) -> Self {
+		self.attack = time_coef(attack);
+		self.release = time_coef(release);
+		return self;
+	}
+
+}
+
+// one pole smoothing coefficient for reaching ~63% in the given time
+fn time_coef(t: Duration) -> f32 {
+	let samples = t.as_secs_f32() * SPEC.sample_rate as f32;
+	if samples <= 0.0 {
+		return 0.0;
+	}
+	return f32::exp(-1.0 / samples);
+}
+
+impl Node for Duck {
+
+	fn inputs(&self) -> usize {
+		return 2;
+	}
+
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]) {
+
+		let side = inputs[Duck::SIDECHAIN];
+		let level = side.left.abs().max(side.right.abs());
+		let coef = if level > self.env { self.attack } else { self.release };
+
+		self.env = level + (self.env - level) * coef;
+
+		let t = if self.threshold > 0.0 {
+			(self.env / self.threshold).min(1.0)
+		} else {
+			1.0
+		};
+
+		outputs[0] = inputs[Duck::MAIN] * (1.0 - t * self.amount.max(0.0).min(1.0));
+
+	}
+
+}
+
+/// Use a [`Stream`](trait.Stream.html) as a Source Node
+pub struct StreamNode<S: Stream> {
+	stream: S,
+}
+
+impl<S: Stream> StreamNode<S> {
+
+	pub fn new(s: S) -> Self {
+		return Self {
+			stream: s,
+		};
+	}
+
+	pub fn inner(&self) -> &S {
+		return &self.stream;
+	}
+
+	pub fn inner_mut(&mut self) -> &mut S {
+		return &mut self.stream;
+	}
+
+}
+
+impl<S: Stream> Node for StreamNode<S> {
+	fn inputs(&self) -> usize {
+		return 0;
+	}
+	fn process(&mut self, _: &[Frame], outputs: &mut [Frame]) {
+		outputs[0] = self.stream.next();
+	}
+}
+
+/// Use an [`Effect`](trait.Effect.html) as a Node
+pub struct EffectNode<E: Effect + Send> {
+	effect: E,
+}
+
+impl<E: Effect + Send> EffectNode<E> {
+
+	pub fn new(e: E) -> Self {
+		return Self {
+			effect: e,
+		};
+	}
+
+	pub fn inner(&self) -> &E {
+		return &self.effect;
+	}
+
+	pub fn inner_mut(&mut self) -> &mut E {
+		return &mut self.effect;
+	}
+
+}
+
+impl<E: Effect + Send> Node for EffectNode<E> {
+	fn process(&mut self, inputs: &[Frame], outputs: &mut [Frame]) {
+		outputs[0] = self.effect.process(inputs[0]);
+	}
+}
+
+/// Play an [`AudioBuffer`](struct.AudioBuffer.html) as a Source Node
+pub struct BufferNode {
+	buffer: Arc<AudioBuffer>,
+	pos: usize,
+	playing: bool,
+	looping: bool,
+}
+
+impl BufferNode {
+
+	pub fn new(buffer: Arc<AudioBuffer>) -> Self {
+		return Self {
+			buffer: buffer,
+			pos: 0,
+			playing: false,
+			looping: false,
+		};
+	}
+
+	/// play from start
+	pub fn play(&mut self) {
+		self.pos = 0;
+		self.playing = true;
+	}
+
+	pub fn stop(&mut self) {
+		self.playing = false;
+	}
+
+	pub fn playing(&self) -> bool {
+		return self.playing;
+	}
+
+	pub fn set_looping(&mut self, b: bool) {
+		self.looping = b;
+	}
+
+}
+
+impl Node for BufferNode {
+
+	fn inputs(&self) -> usize {
+		return 0;
+	}
+
+	fn process(&mut self, _: &[Frame], outputs: &mut [Frame]) {
+
+		if !self.playing {
+			outputs[0] = Frame::zero();
+			return;
+		}
+
+		let frames = self.buffer.frames();
+
+		if self.pos >= frames.len() {
+			if self.looping && !frames.is_empty() {
+				self.pos = 0;
+			} else {
+				self.playing = false;
+				outputs[0] = Frame::zero();
+				return;
+			}
+		}
+
+		outputs[0] = frames[self.pos];
+		self.pos += 1;
+
+	}
+
+}
+
+#[cfg(test)]
+struct Const(f32);
+
+#[cfg(test)]
+impl Node for Const {
+	fn inputs(&self) -> usize {
+		return 0;
+	}
+	fn process(&mut self, _: &[Frame], outputs: &mut [Frame]) {
+		outputs[0] = Frame::mono(self.0);
+	}
+}
+
+#[test]
+fn graph_routing() {
+
+	let mut g = Graph::new();
+	let out = g.output();
+	let a = g.add(Arc::new(Mutex::new(Const(0.5))));
+	let b = g.add(Arc::new(Mutex::new(Const(0.25))));
+	let split = g.add(Arc::new(Mutex::new(Split::new(2))));
+	let bus = g.add(Arc::new(Mutex::new(Gain::new(2.0))));
+
+	g.connect(a, split).unwrap();
+	g.connect_port((split, 0), (out, 0), 1.0).unwrap();
+	g.connect_port((split, 1), (bus, 0), 0.5).unwrap();
+	g.connect(b, bus).unwrap();
+	g.connect(bus, out).unwrap();
+
+	// 0.5 + (0.5 * 0.5 + 0.25) * 2.0
+	assert_eq!(g.next(), Frame::mono(1.5));
+	assert!(g.connect(bus, split).is_err());
+	assert!(g.connect_port((split, 2), (out, 0), 1.0).is_err());
+
+	g.disconnect(b, bus);
+	assert_eq!(g.next(), Frame::mono(1.0));
+
+	let duck = g.add(Arc::new(Mutex::new(Duck::new(1.0).times(Duration::from_secs(0), Duration::from_secs(0)))));
+
+	g.remove(bus);
+	g.disconnect_port((split, 0), (out, 0));
+	g.connect(b, duck).unwrap();
+	g.connect_port((split, 0), (duck, Duck::SIDECHAIN), 1.0).unwrap();
+	g.connect(duck, out).unwrap();
+	assert_eq!(g.next(), Frame::zero());
+
+	g.disconnect(split, duck);
+	assert_eq!(g.next(), Frame::mono(0.25));
+
+}
@@ -5,6 +5,10 @@
 //! This module provides 2 types of high-level types:
 //!  - [`Sound`](struct.Sound.html), buffered audio mainly for sound effects
 //!  - [`Track`](struct.Track.html), streamed audio mainly for music
+//!
+//! [`Sfx`](struct.Sfx.html) generates sound effects procedurally
+//!
+//! [`Graph`](struct.Graph.html) routes sources through custom nodes, sends and sidechains
 
 // TODO: sample rate conversion
 
@@ -26,12 +30,14 @@ import!(vorbis);
 import!(wav);
 import!(mp3);
 import!(decoder);
-import!(buffer);
 import!(resample);
 export!(source);
 export!(types);
 export!(effect);
 export!(spatial);
+export!(buffer);
+export!(sfx);
+export!(graph);
 #[cfg(not(web))]
 export!(track);
 #[cfg(not(web))]
@@ -1,6 +1,380 @@
 // wengwengweng
 
+use super::*;
+use crate::Result;
+
 pub const MAJ3: [i32; 3] = [0, 4, 7];
 pub const MIN3: [i32; 3] = [0, 3, 7];
+pub const DIM3: [i32; 3] = [0, 3, 6];
+pub const AUG3: [i32; 3] = [0, 4, 8];
+pub const SUS2: [i32; 3] = [0, 2, 7];
+pub const SUS4: [i32; 3] = [0, 5, 7];
 pub const MAJ7: [i32; 4] = [0, 4, 7, 11];
+pub const MIN7: [i32; 4] = [0, 3, 7, 10];
+pub const DOM7: [i32; 4] = [0, 4, 7, 10];
+pub const DIM7: [i32; 4] = [0, 3, 6, 9];
+pub const HDIM7: [i32; 4] = [0, 3, 6, 10];
+pub const MINMAJ7: [i32; 4] = [0, 3, 7, 11];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Quality {
+	Major,
+	Minor,
+	Dim,
+	HalfDim,
+	Aug,
+}
+
+/// A Chord as a Root and Intervals
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+	root: Key,
+	intervals: Vec<i32>,
+	bass: Option<Key>,
+}
+
+impl Chord {
+
+	pub fn new(root: Key, intervals: &[i32]) -> Self {
+
+		let mut intervals = intervals.to_vec();
+
+		intervals.sort();
+		intervals.dedup();
+
+		return Self {
+			root: root,
+			intervals: intervals,
+			bass: None,
+		};
+
+	}
+
+	/// parse a chord symbol like "C", "F#m", "Bbmaj7", "Cm7b5", "G7#9", "Dsus4", "Am/E"
+	pub fn parse(sym: &str) -> Result<Self> {
+
+		let (root, rest) = Key::parse(sym.trim())
+			.ok_or_else(|| format!("failed to parse chord root: {}", sym))?;
+
+		return parse_quality(root, rest, false)
+			.ok_or_else(|| format!("failed to parse chord: {}", sym));
+
+	}
+
+	pub fn root(&self) -> Key {
+		return self.root;
+	}
+
+	/// semitones from the root, in ascending order
+	pub fn intervals(&self) -> &[i32] {
+		return &self.intervals;
+	}
+
+	/// bass note of a slash chord
+	pub fn bass(&self) -> Option<Key> {
+		return self.bass;
+	}
+
+	/// set the bass note, making it a slash chord
+	pub fn over(mut self, bass: Key) -> Self {
+		self.bass = Some(bass);
+		return self;
+	}
+
+	/// transpose by semitones
+	pub fn transpose(&self, i: i32) -> Self {
+		return Self {
+			root: Key::from_n(self.root.to_n() + i),
+			intervals: self.intervals.clone(),
+			bass: self.bass.map(|b| Key::from_n(b.to_n() + i)),
+		};
+	}
+
+	/// notes in root position with the root in the given octave, the bass note of a slash chord goes below
+	pub fn notes(&self, octave: i32) -> Vec<Note> {
+
+		let root = Note::from_key(self.root, octave);
+		let mut notes = self.intervals
+			.iter()
+			.map(|i| root + *i)
+			.collect::<Vec<Note>>();
+
+		if let Some(bass) = self.bass {
+			let pc = bass.to_n();
+			notes.retain(|n| n.pitch_class() != pc);
+			let mut b = Note::from_key(bass, octave);
+			if b.n() >= root.n() {
+				b = b - 12;
+			}
+			notes.insert(0, b);
+		}
+
+		return notes;
+
+	}
+
+	/// notes of the nth inversion
+	pub fn inversion(&self, octave: i32, n: i32) -> Vec<Note> {
+		return invert(&self.notes(octave), n);
+	}
+
+	/// the voicing of this chord with the least total movement from the previous notes
+	pub fn voice_lead(&self, prev: &[Note]) -> Vec<Note> {
+
+		if prev.is_empty() {
+			return self.notes(4);
+		}
+
+		let center = prev.iter().map(|n| n.n()).sum::<i32>() / prev.len() as i32;
+		let octave = Note::new(center).octave();
+		let len = self.intervals.len() as i32;
+		let mut best = self.notes(octave);
+		let mut best_cost = i32::MAX;
+
+		let dist = |a: &[Note], b: &[Note]| {
+			return a
+				.iter()
+				.map(|n| b.iter().map(|m| (n.n() - m.n()).abs()).min().unwrap_or(0))
+				.sum::<i32>();
+		};
+
+		for o in (octave - 1)..=(octave + 1) {
+			for inv in 0..len.max(1) {
+				let v = if self.bass.is_some() {
+					self.notes(o)
+				} else {
+					self.inversion(o, inv)
+				};
+				let cost = dist(&v, prev) + dist(prev, &v);
+				if cost < best_cost {
+					best_cost = cost;
+					best = v;
+				}
+			}
+		}
+
+		return best;
+
+	}
+
+}
+
+/// invert a voicing n times, moving the lowest note up an octave (or the highest note down if n < 0)
+pub fn invert(notes: &[Note], n: i32) -> Vec<Note> {
+
+	let mut notes = notes.to_vec();
+
+	notes.sort_by_key(|n| n.n());
+
+	if notes.is_empty() {
+		return notes;
+	}
+
+	for _ in 0..n.abs() {
+		if n > 0 {
+			let low = notes.remove(0);
+			notes.push(low + 12);
+		} else {
+			let high = notes.pop().unwrap_or(Note::new(0));
+			notes.insert(0, high - 12);
+		}
+	}
+
+	return notes;
+
+}
+
+fn eat(s: &mut &str, tok: &str) -> bool {
+	if let Some(rest) = s.strip_prefix(tok) {
+		*s = rest;
+		return true;
+	}
+	return false;
+}
+
+fn eat_any(s: &mut &str, toks: &[&str]) -> bool {
+	return toks.iter().any(|t| eat(s, t));
+}
+
+// parse everything after the root, minor can be forced by roman numeral case
+pub(super) fn parse_quality(root: Key, mut s: &str, minor: bool) -> Option<Chord> {
+
+	let s = &mut s;
+	let mut quality = if minor { Quality::Minor } else { Quality::Major };
+	let mut major7 = false;
+	// "Δ" alone means maj7, while "M" / "maj" needs a number
+	let mut delta = false;
+
+	if eat(s, "Δ") {
+		major7 = true;
+		delta = true;
+	} else if eat_any(s, &["maj", "Maj", "M"]) {
+		major7 = true;
+	} else if eat_any(s, &["min", "m", "-"]) {
+		quality = Quality::Minor;
+		if eat(s, "Δ") {
+			major7 = true;
+			delta = true;
+		} else if eat_any(s, &["maj", "Maj", "M"]) {
+			major7 = true;
+		}
+	} else if eat_any(s, &["dim", "°", "o"]) {
+		quality = Quality::Dim;
+	} else if eat(s, "ø") {
+		quality = Quality::HalfDim;
+	} else if eat_any(s, &["aug", "+"]) {
+		quality = Quality::Aug;
+	}
+
+	let ext = if eat(s, "69") {
+		69
+	} else if eat(s, "13") {
+		13
+	} else if eat(s, "11") {
+		11
+	} else if eat(s, "9") {
+		9
+	} else if eat(s, "7") {
+		7
+	} else if eat(s, "6") {
+		6
+	} else {
+		0
+	};
+
+	let mut third = match quality {
+		Quality::Minor | Quality::Dim | Quality::HalfDim => 3,
+		_ => 4,
+	};
+
+	let mut fifth = match quality {
+		Quality::Dim | Quality::HalfDim => 6,
+		Quality::Aug => 8,
+		_ => 7,
+	};
+
+	let has_7th = match ext {
+		7 | 9 | 11 | 13 => true,
+		_ => quality == Quality::HalfDim || delta,
+	};
+
+	let mut extra = vec![];
+
+	if has_7th {
+		extra.push(if major7 {
+			11
+		} else if quality == Quality::Dim && ext == 7 {
+			9
+		} else {
+			10
+		});
+	}
+
+	match ext {
+		6 => extra.push(9),
+		69 => extra.extend(&[9, 14]),
+		9 => extra.push(14),
+		11 => extra.extend(&[14, 17]),
+		13 => {
+			extra.extend(&[14, 21]);
+			if third == 3 {
+				extra.push(17);
+			}
+		},
+		_ => {},
+	}
+
+	let mut bass = None;
+
+	fn replace(v: &mut Vec<i32>, from: i32, to: i32) {
+		v.retain(|i| *i != from);
+		v.push(to);
+	}
+
+	while !s.is_empty() {
+		if eat_any(s, &["(", ")", ",", " "]) {
+			continue;
+		} else if eat(s, "sus2") {
+			third = 2;
+		} else if eat(s, "sus4") || eat(s, "sus") {
+			third = 5;
+		} else if eat(s, "add9") || eat(s, "add2") {
+			extra.push(14);
+		} else if eat(s, "add11") || eat(s, "add4") {
+			extra.push(17);
+		} else if eat(s, "add13") || eat(s, "add6") {
+			extra.push(21);
+		} else if eat_any(s, &["b5", "-5", "♭5"]) {
+			fifth = 6;
+		} else if eat_any(s, &["#5", "+5", "♯5"]) {
+			fifth = 8;
+		} else if eat_any(s, &["b9", "-9", "♭9"]) {
+			replace(&mut extra, 14, 13);
+		} else if eat_any(s, &["#9", "+9", "♯9"]) {
+			replace(&mut extra, 14, 15);
+		} else if eat_any(s, &["#11", "+11", "♯11"]) {
+			replace(&mut extra, 17, 18);
+		} else if eat_any(s, &["b13", "-13", "♭13"]) {
+			replace(&mut extra, 21, 20);
+		} else if eat(s, "/") {
+			let (b, rest) = Key::parse(s)?;
+			bass = Some(b);
+			*s = rest;
+			if !s.is_empty() {
+				return None;
+			}
+		} else {
+			return None;
+		}
+	}
+
+	let mut intervals = vec![0, third, fifth];
+
+	intervals.extend(extra);
+
+	let mut chord = Chord::new(root, &intervals);
+
+	chord.bass = bass;
+
+	return Some(chord);
+
+}
+
+#[test]
+fn chord_symbols() {
+
+	let check = |sym: &str, intervals: &[i32]| {
+		let c = Chord::parse(sym).unwrap();
+		assert_eq!(c.intervals(), intervals, "{}", sym);
+	};
+
+	check("C", &MAJ3);
+	check("Cm", &MIN3);
+	check("C7", &DOM7);
+	check("Cmaj7", &MAJ7);
+	check("CΔ", &MAJ7);
+	check("CmM7", &MINMAJ7);
+	check("Cm7b5", &HDIM7);
+	check("Cø", &HDIM7);
+	check("Cdim7", &DIM7);
+	check("Caug", &AUG3);
+	check("Csus4", &SUS4);
+	check("C7sus4", &[0, 5, 7, 10]);
+	check("C9", &[0, 4, 7, 10, 14]);
+	check("C7#9", &[0, 4, 7, 10, 15]);
+	check("Cadd9", &[0, 4, 7, 14]);
+
+	let c = Chord::parse("Am/E").unwrap();
+
+	assert_eq!(c.root(), Key::A);
+	assert_eq!(c.bass(), Some(Key::E));
+	assert_eq!(c.notes(4), vec![Note::new(64), Note::new(69), Note::new(72)]);
+	assert_eq!(Chord::parse("Bbm7").unwrap().root(), Key::Bb);
+	assert_eq!(invert(&Chord::parse("C").unwrap().notes(4), 1), vec![Note::new(64), Note::new(67), Note::new(72)]);
+	assert!(Chord::parse("Cxyz").is_err());
+
+	let g = Chord::parse("G").unwrap().voice_lead(&Chord::parse("C").unwrap().notes(4));
+
+	assert_eq!(g, vec![Note::new(59), Note::new(62), Note::new(67)]);
 
+}
@@ -1,6 +1,9 @@
 // wengwengweng
 
+//! Music Theory
+
 export!(note);
 export!(chord);
 export!(scale);
-
+export!(progression);
+export!(tuning);
@@ -1,7 +1,12 @@
 // wengwengweng
 
-const A4_FREQ: f32 = 440.0;
-const A4_NOTE: i32 = 69;
+use std::ops::Add;
+use std::ops::Sub;
+
+use super::*;
+
+pub(super) const A4_FREQ: f32 = 440.0;
+pub(super) const A4_NOTE: i32 = 69;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Key {
@@ -54,6 +59,62 @@ impl Key {
 		return Note::from_key(self, o);
 	}
 
+	/// get key from a pitch class, black keys are spelled with sharps
+	pub fn from_n(n: i32) -> Self {
+		return match n.rem_euclid(12) {
+			0 => Key::C,
+			1 => Key::Cs,
+			2 => Key::D,
+			3 => Key::Ds,
+			4 => Key::E,
+			5 => Key::F,
+			6 => Key::Fs,
+			7 => Key::G,
+			8 => Key::Gs,
+			9 => Key::A,
+			10 => Key::As,
+			_ => Key::B,
+		};
+	}
+
+	/// parse a key name like "C", "F#" or "Bb" from the start of a string, returns the key and the rest of the string
+	pub fn parse(s: &str) -> Option<(Self, &str)> {
+
+		let mut chars = s.chars();
+
+		let n = match chars.next()? {
+			'C' => 0,
+			'D' => 2,
+			'E' => 4,
+			'F' => 5,
+			'G' => 7,
+			'A' => 9,
+			'B' => 11,
+			_ => return None,
+		};
+
+		let rest = chars.as_str();
+
+		if let Some(rest) = rest.strip_prefix('#').or_else(|| rest.strip_prefix('♯')) {
+			return Some((Key::from_n(n + 1), rest));
+		}
+
+		if let Some(rest) = rest.strip_prefix('b').or_else(|| rest.strip_prefix('♭')) {
+			let k = match n {
+				2 => Key::Db,
+				4 => Key::Eb,
+				7 => Key::Gb,
+				9 => Key::Ab,
+				11 => Key::Bb,
+				_ => Key::from_n(n - 1),
+			};
+			return Some((k, rest));
+		}
+
+		return Some((Key::from_n(n), rest));
+
+	}
+
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -81,6 +142,43 @@ impl Note {
 		return A4_FREQ * f32::powi(f32::powf(2.0, 1.0 / 12.0), self.n - A4_NOTE);
 	}
 
+	/// get frequency in a [`Tuning`](struct.Tuning.html) other than 12-TET
+	pub fn freq_in(&self, t: &Tuning) -> f32 {
+		return t.freq(*self);
+	}
+
+	/// MIDI note number
+	pub fn n(&self) -> i32 {
+		return self.n;
+	}
+
+	/// pitch class (0 - 11)
+	pub fn pitch_class(&self) -> i32 {
+		return self.n.rem_euclid(12);
+	}
+
+	pub fn octave(&self) -> i32 {
+		return self.n.div_euclid(12) - 1;
+	}
+
+	pub fn key(&self) -> Key {
+		return Key::from_n(self.n);
+	}
+
+}
+
+impl Add<i32> for Note {
+	type Output = Note;
+	fn add(self, i: i32) -> Note {
+		return Note::new(self.n + i);
+	}
+}
+
+impl Sub<i32> for Note {
+	type Output = Note;
+	fn sub(self, i: i32) -> Note {
+		return Note::new(self.n - i);
+	}
 }
 
 impl From<i32> for Note {
@@ -0,0 +1,116 @@
+// wengwengweng
+
+use super::*;
+use crate::Result;
+
+const NUMERALS: [(&str, i32); 7] = [
+	("VII", 6),
+	("III", 2),
+	("VI", 5),
+	("IV", 3),
+	("II", 1),
+	("V", 4),
+	("I", 0),
+];
+
+/// parse a roman numeral chord like "ii7", "V7", "bVII" or "vii°" relative to a key and scale
+///
+/// uppercase numerals are major and lowercase are minor, accidentals shift the scale degree, the rest follows the chord symbol syntax of [`Chord::parse`](struct.Chord.html#method.parse)
+pub fn numeral(key: Key, scale: &[i32], s: &str) -> Result<Chord> {
+
+	let err = || format!("failed to parse roman numeral: {}", s);
+	let mut rest = s.trim();
+	let mut offset = 0;
+
+	loop {
+		if let Some(r) = rest.strip_prefix('b').or_else(|| rest.strip_prefix('♭')) {
+			offset -= 1;
+			rest = r;
+		} else if let Some(r) = rest.strip_prefix('#').or_else(|| rest.strip_prefix('♯')) {
+			offset += 1;
+			rest = r;
+		} else {
+			break;
+		}
+	}
+
+	let (num, degree) = NUMERALS
+		.iter()
+		.find(|(n, _)| {
+			return rest.starts_with(n) || rest.starts_with(&n.to_lowercase());
+		})
+		.ok_or_else(err)?;
+
+	let minor = rest.starts_with(&num.to_lowercase());
+	let rest = &rest[num.len()..];
+
+	if scale.is_empty() {
+		return Err(err());
+	}
+
+	let d = *degree as usize;
+	let i = scale[d % scale.len()] + 12 * (d / scale.len()) as i32 + offset;
+	let root = Key::from_n(key.to_n() + i);
+
+	return chord::parse_quality(root, rest, minor).ok_or_else(err);
+
+}
+
+/// parse a progression of roman numerals separated by spaces or "-", e.g. "I-vi-IV-V" or "ii7 V7 Imaj7"
+pub fn progression(key: Key, scale: &[i32], s: &str) -> Result<Vec<Chord>> {
+	return s
+		.split(|c: char| c == '-' || c.is_whitespace())
+		.filter(|s| !s.is_empty())
+		.map(|s| numeral(key, scale, s))
+		.collect();
+}
+
+/// voice a progression with voice leading, starting from root position in the given octave
+pub fn voice_progression(chords: &[Chord], octave: i32) -> Vec<Vec<Note>> {
+
+	let mut voicings: Vec<Vec<Note>> = vec![];
+
+	for c in chords {
+		let v = match voicings.last() {
+			Some(prev) => c.voice_lead(prev),
+			None => c.notes(octave),
+		};
+		voicings.push(v);
+	}
+
+	return voicings;
+
+}
+
+/// the diatonic triad on a scale degree (0 based), None if the scale is empty
+pub fn triad(key: Key, scale: &[i32], d: i32) -> Option<Chord> {
+
+	let root = degree(key, 4, scale, d)?;
+	let third = degree(key, 4, scale, d + 2)?;
+	let fifth = degree(key, 4, scale, d + 4)?;
+
+	return Some(Chord::new(root.key(), &[0, third.n() - root.n(), fifth.n() - root.n()]));
+
+}
+
+#[test]
+fn roman_numerals() {
+
+	let chords = progression(Key::C, &MAJOR, "I-vi-IV-V7").unwrap();
+
+	assert_eq!(chords, vec![
+		Chord::parse("C").unwrap(),
+		Chord::parse("Am").unwrap(),
+		Chord::parse("F").unwrap(),
+		Chord::parse("G7").unwrap(),
+	]);
+
+	assert_eq!(numeral(Key::A, &MINOR, "VII").unwrap(), Chord::parse("G").unwrap());
+	assert_eq!(numeral(Key::C, &MAJOR, "bVII").unwrap().root().to_n(), 10);
+	assert_eq!(numeral(Key::C, &MAJOR, "viiø7").unwrap(), Chord::parse("Bm7b5").unwrap());
+	assert_eq!(numeral(Key::D, &MAJOR, "ii7").unwrap(), Chord::parse("Em7").unwrap());
+	assert_eq!(triad(Key::C, &MAJOR, 6), Chord::parse("Bdim").ok());
+	assert_eq!(triad(Key::C, &[], 0), None);
+	assert!(numeral(Key::C, &MAJOR, "X").is_err());
+
+}
@@ -1,5 +1,93 @@
 // wengwengweng
 
+use super::*;
+
 pub const MAJOR: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
 pub const MINOR: [i32; 7] = [0, 2, 3, 5, 7, 8, 10];
 
+// modes
+pub const IONIAN: [i32; 7] = MAJOR;
+pub const DORIAN: [i32; 7] = [0, 2, 3, 5, 7, 9, 10];
+pub const PHRYGIAN: [i32; 7] = [0, 1, 3, 5, 7, 8, 10];
+pub const LYDIAN: [i32; 7] = [0, 2, 4, 6, 7, 9, 11];
+pub const MIXOLYDIAN: [i32; 7] = [0, 2, 4, 5, 7, 9, 10];
+pub const AEOLIAN: [i32; 7] = MINOR;
+pub const LOCRIAN: [i32; 7] = [0, 1, 3, 5, 6, 8, 10];
+
+pub const HARMONIC_MINOR: [i32; 7] = [0, 2, 3, 5, 7, 8, 11];
+pub const MELODIC_MINOR: [i32; 7] = [0, 2, 3, 5, 7, 9, 11];
+pub const MAJOR_PENTATONIC: [i32; 5] = [0, 2, 4, 7, 9];
+pub const MINOR_PENTATONIC: [i32; 5] = [0, 3, 5, 7, 10];
+pub const BLUES: [i32; 6] = [0, 3, 5, 6, 7, 10];
+pub const WHOLE_TONE: [i32; 6] = [0, 2, 4, 6, 8, 10];
+pub const DIMINISHED: [i32; 8] = [0, 2, 3, 5, 6, 8, 9, 11];
+pub const CHROMATIC: [i32; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+// exotic
+pub const HUNGARIAN_MINOR: [i32; 7] = [0, 2, 3, 6, 7, 8, 11];
+pub const DOUBLE_HARMONIC: [i32; 7] = [0, 1, 4, 5, 7, 8, 11];
+pub const PHRYGIAN_DOMINANT: [i32; 7] = [0, 1, 4, 5, 7, 8, 10];
+pub const NEAPOLITAN_MAJOR: [i32; 7] = [0, 1, 3, 5, 7, 9, 11];
+pub const NEAPOLITAN_MINOR: [i32; 7] = [0, 1, 3, 5, 7, 8, 11];
+pub const ENIGMATIC: [i32; 7] = [0, 1, 4, 6, 8, 10, 11];
+pub const HIRAJOSHI: [i32; 5] = [0, 2, 3, 7, 8];
+pub const IN_SEN: [i32; 5] = [0, 1, 5, 7, 10];
+pub const IWATO: [i32; 5] = [0, 1, 5, 6, 10];
+pub const PELOG: [i32; 5] = [0, 1, 3, 7, 8];
+
+/// get the nth mode of a scale, e.g. `mode(&MAJOR, 1)` is dorian
+pub fn mode(scale: &[i32], n: usize) -> Vec<i32> {
+
+	if scale.is_empty() {
+		return vec![];
+	}
+
+	let n = n % scale.len();
+	let root = scale[n];
+
+	return scale[n..]
+		.iter()
+		.chain(scale[..n].iter())
+		.map(|i| (i - root).rem_euclid(12))
+		.collect();
+
+}
+
+/// get the note at a scale degree (0 based, can go beyond one octave or below 0), None if the scale is empty
+pub fn degree(key: Key, octave: i32, scale: &[i32], d: i32) -> Option<Note> {
+
+	if scale.is_empty() {
+		return None;
+	}
+
+	let len = scale.len() as i32;
+	let oct = d.div_euclid(len);
+	let i = d.rem_euclid(len) as usize;
+
+	return Some(Note::from_key(key, octave + oct) + scale[i]);
+
+}
+
+/// get the notes of a scale in an octave
+pub fn scale_notes(key: Key, octave: i32, scale: &[i32]) -> Vec<Note> {
+	return scale
+		.iter()
+		.map(|i| Note::from_key(key, octave) + *i)
+		.collect();
+}
+
+/// move a note to the closest note in a scale
+pub fn quantize(n: Note, key: Key, scale: &[i32]) -> Note {
+
+	let pc = (n.n() - key.to_n()).rem_euclid(12);
+
+	let offset = scale
+		.iter()
+		.flat_map(|i| vec![i - 12, *i, i + 12])
+		.map(|i| i - pc)
+		.min_by_key(|d| (d.abs(), *d))
+		.unwrap_or(0);
+
+	return n + offset;
+
+}
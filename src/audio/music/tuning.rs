@@ -0,0 +1,230 @@
+// wengwengweng
+
+// http://www.huygens-fokker.org/scala/scl_format.html
+
+use super::*;
+use crate::Result;
+use super::note::A4_FREQ;
+use super::note::A4_NOTE;
+
+// C4 in 12-TET, the default reference pitch of scala
+const MIDDLE_C_FREQ: f32 = 261.625_58;
+const MIDDLE_C_NOTE: i32 = 60;
+
+/// A Tuning System
+///
+/// maps each note to a frequency by repeating a scale of ratios from a reference note, one note per scale step
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tuning {
+	// ratios of each step from the root, the last one is the period (usually 2/1)
+	ratios: Vec<f32>,
+	root_note: i32,
+	root_freq: f32,
+}
+
+impl Tuning {
+
+	/// create from the ratios of each step from the root, the last one being the period (usually 2.0)
+	pub fn from_ratios(ratios: &[f32]) -> Result<Self> {
+
+		if ratios.is_empty() {
+			return Err(format!("tuning has no steps"));
+		}
+
+		if ratios.iter().any(|r| *r <= 0.0) {
+			return Err(format!("tuning ratios have to be positive"));
+		}
+
+		return Ok(Self {
+			ratios: ratios.to_vec(),
+			root_note: MIDDLE_C_NOTE,
+			root_freq: MIDDLE_C_FREQ,
+		});
+
+	}
+
+	/// create from cents of each step from the root, the last one being the period (usually 1200.0)
+	pub fn from_cents(cents: &[f32]) -> Result<Self> {
+		return Self::from_ratios(&cents
+			.iter()
+			.map(|c| f32::powf(2.0, c / 1200.0))
+			.collect::<Vec<f32>>());
+	}
+
+	/// equal temperament with n divisions of the octave, with A4 at 440hz
+	pub fn equal(n: usize) -> Self {
+
+		let n = n.max(1);
+
+		return Self {
+			ratios: (1..=n)
+				.map(|i| f32::powf(2.0, i as f32 / n as f32))
+				.collect(),
+			root_note: A4_NOTE,
+			root_freq: A4_FREQ,
+		};
+
+	}
+
+	/// 5-limit just intonation on C
+	pub fn just() -> Self {
+		return Self {
+			ratios: vec![
+				16.0 / 15.0,
+				9.0 / 8.0,
+				6.0 / 5.0,
+				5.0 / 4.0,
+				4.0 / 3.0,
+				45.0 / 32.0,
+				3.0 / 2.0,
+				8.0 / 5.0,
+				5.0 / 3.0,
+				9.0 / 5.0,
+				15.0 / 8.0,
+				2.0,
+			],
+			root_note: MIDDLE_C_NOTE,
+			root_freq: MIDDLE_C_FREQ,
+		};
+	}
+
+	/// pythagorean tuning on C
+	pub fn pythagorean() -> Self {
+		return Self {
+			ratios: vec![
+				256.0 / 243.0,
+				9.0 / 8.0,
+				32.0 / 27.0,
+				81.0 / 64.0,
+				4.0 / 3.0,
+				729.0 / 512.0,
+				3.0 / 2.0,
+				128.0 / 81.0,
+				27.0 / 16.0,
+				16.0 / 9.0,
+				243.0 / 128.0,
+				2.0,
+			],
+			root_note: MIDDLE_C_NOTE,
+			root_freq: MIDDLE_C_FREQ,
+		};
+	}
+
+	/// parse a scala (.scl) file, the scale starts on middle C (261.63hz), use [`root`](#method.root) to change
+	pub fn from_scl(src: &str) -> Result<Self> {
+
+		let mut lines = src
+			.lines()
+			.filter(|l| !l.starts_with('!'));
+
+		// description
+		lines.next()
+			.ok_or_else(|| format!("failed to parse scl: missing description"))?;
+
+		let count = lines.next()
+			.and_then(|l| l.split_whitespace().next())
+			.and_then(|n| n.parse::<usize>().ok())
+			.ok_or_else(|| format!("failed to parse scl: missing note count"))?;
+
+		let ratios = lines
+			.filter_map(|l| l.split_whitespace().next())
+			.take(count)
+			.map(|p| parse_pitch(p).ok_or_else(|| format!("failed to parse scl pitch: {}", p)))
+			.collect::<Result<Vec<f32>>>()?;
+
+		if ratios.len() != count {
+			return Err(format!("failed to parse scl: expected {} notes, found {}", count, ratios.len()));
+		}
+
+		return Self::from_ratios(&ratios);
+
+	}
+
+	/// set the reference note and its frequency, the scale starts from this note
+	pub fn root(mut self, n: Note, freq: f32) -> Self {
+		self.root_note = n.n();
+		self.root_freq = freq;
+		return self;
+	}
+
+	/// number of steps before the scale repeats
+	pub fn len(&self) -> usize {
+		return self.ratios.len();
+	}
+
+	pub fn freq(&self, n: Note) -> f32 {
+
+		let len = self.ratios.len() as i32;
+		let offset = n.n() - self.root_note;
+		let period = self.ratios[self.ratios.len() - 1];
+		let step = offset.rem_euclid(len) as usize;
+		let ratio = if step == 0 { 1.0 } else { self.ratios[step - 1] };
+
+		return self.root_freq * period.powi(offset.div_euclid(len)) * ratio;
+
+	}
+
+}
+
+impl Default for Tuning {
+	fn default() -> Self {
+		return Self::equal(12);
+	}
+}
+
+// a pitch is in cents if it contains a '.', otherwise it's a ratio
+fn parse_pitch(p: &str) -> Option<f32> {
+
+	if p.contains('.') {
+		let cents = p.parse::<f32>().ok()?;
+		return Some(f32::powf(2.0, cents / 1200.0));
+	}
+
+	let mut parts = p.splitn(2, '/');
+	let num = parts.next()?.parse::<f32>().ok()?;
+	let den = match parts.next() {
+		Some(d) => d.parse::<f32>().ok()?,
+		None => 1.0,
+	};
+
+	if den == 0.0 {
+		return None;
+	}
+
+	return Some(num / den);
+
+}
+
+#[test]
+fn tunings() {
+
+	let eq = |a: f32, b: f32| (a - b).abs() < 0.01;
+	let a4 = Note::new(69);
+	let c4 = Note::new(60);
+
+	assert!(eq(Tuning::default().freq(a4), a4.freq()));
+	assert!(eq(Tuning::default().freq(Note::new(30)), Note::new(30).freq()));
+	assert!(eq(Tuning::just().freq(Note::new(67)), MIDDLE_C_FREQ * 1.5));
+	assert!(eq(Tuning::just().freq(Note::new(48)), MIDDLE_C_FREQ / 2.0));
+
+	let scl = "! test.scl
+!
+quarter-comma meantone fragment
+ 4
+!
+ 193.157
+ 5/4
+ 3/2 fifth
+ 2/1
+";
+
+	let t = Tuning::from_scl(scl).unwrap().root(c4, 256.0);
+
+	assert_eq!(t.len(), 4);
+	assert!(eq(t.freq(Note::new(62)), 320.0));
+	assert!(eq(t.freq(Note::new(64)), 512.0));
+	assert!(eq(t.freq(Note::new(59)), 192.0));
+	assert!(eq(c4.freq_in(&t), 256.0));
+	assert!(Tuning::from_scl("bad\n3\n1/1\n").is_err());
+
+}
@@ -0,0 +1,440 @@
+// wengwengweng
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use super::*;
+
+// sfxr always generates at 44100hz
+const SFXR_SAMPLE_RATE: u32 = 44100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SfxWave {
+	Square,
+	Triangle,
+	Sine,
+	Noise,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SfxPreset {
+	Coin,
+	Jump,
+	Explosion,
+	Laser,
+	Hit,
+	Powerup,
+	Blip,
+}
+
+/// Procedural Sound Effect Parameters (sfxr)
+///
+/// parameters are in 0.0 - 1.0, except for the ones that describe a change (ramps, punch, phaser offset and arpeggio amount) which are in -1.0 - 1.0
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sfx {
+	pub wave: SfxWave,
+	pub base_freq: f32,
+	pub freq_limit: f32,
+	pub freq_ramp: f32,
+	pub freq_dramp: f32,
+	pub duty: f32,
+	pub duty_ramp: f32,
+	pub vib_strength: f32,
+	pub vib_speed: f32,
+	pub vib_delay: f32,
+	pub env_attack: f32,
+	pub env_sustain: f32,
+	pub env_decay: f32,
+	pub env_punch: f32,
+	pub lpf_resonance: f32,
+	pub lpf_freq: f32,
+	pub lpf_ramp: f32,
+	pub hpf_freq: f32,
+	pub hpf_ramp: f32,
+	pub pha_offset: f32,
+	pub pha_ramp: f32,
+	pub repeat_speed: f32,
+	pub arp_speed: f32,
+	pub arp_mod: f32,
+	pub volume: f32,
+}
+
+impl Default for Sfx {
+	fn default() -> Self {
+		return Self::from_sample(&sfxr::Sample::new());
+	}
+}
+
+fn rand_seed() -> u64 {
+	return math::rand(0, u32::MAX) as u64;
+}
+
+impl Sfx {
+
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	/// generate a random sound of a kind
+	pub fn preset(p: SfxPreset) -> Self {
+		return Self::preset_seeded(p, rand_seed());
+	}
+
+	/// generate a sound of a kind, the same seed always gives the same sound
+	pub fn preset_seeded(p: SfxPreset, seed: u64) -> Self {
+		let seed = Some(seed);
+		return Self::from_sample(&match p {
+			SfxPreset::Coin => sfxr::Sample::pickup(seed),
+			SfxPreset::Jump => sfxr::Sample::jump(seed),
+			SfxPreset::Explosion => sfxr::Sample::explosion(seed),
+			SfxPreset::Laser => sfxr::Sample::laser(seed),
+			SfxPreset::Hit => sfxr::Sample::hit(seed),
+			SfxPreset::Powerup => sfxr::Sample::powerup(seed),
+			SfxPreset::Blip => sfxr::Sample::blip(seed),
+		});
+	}
+
+	/// generate a completely random sound
+	pub fn random() -> Self {
+
+		let r = |a: f32, b: f32| math::rand(a, b);
+		// random in -1.0 - 1.0 raised to a power, biased towards 0 while keeping the sign
+		let rp = |p: i32| r(-1.0, 1.0).powi(p);
+
+		let mut s = Self::default();
+
+		s.wave = *math::rand_from(&[
+			SfxWave::Square,
+			SfxWave::Triangle,
+			SfxWave::Sine,
+			SfxWave::Noise,
+		]).unwrap_or(&SfxWave::Square);
+
+		s.base_freq = if r(0.0, 1.0) < 0.5 {
+			rp(2)
+		} else {
+			rp(3) + 0.5
+		};
+
+		s.freq_limit = 0.0;
+		s.freq_ramp = rp(5);
+
+		if s.base_freq > 0.7 && s.freq_ramp > 0.2 {
+			s.freq_ramp = -s.freq_ramp;
+		}
+
+		if s.base_freq < 0.2 && s.freq_ramp < -0.05 {
+			s.freq_ramp = -s.freq_ramp;
+		}
+
+		s.freq_dramp = rp(3);
+		s.duty = r(-1.0, 1.0);
+		s.duty_ramp = rp(3);
+		s.vib_strength = rp(3);
+		s.vib_speed = r(-1.0, 1.0);
+		s.vib_delay = r(-1.0, 1.0);
+		s.env_attack = rp(3);
+		s.env_sustain = rp(2);
+		s.env_decay = r(-1.0, 1.0);
+		s.env_punch = r(0.0, 0.8).powi(2);
+
+		if s.env_attack + s.env_sustain + s.env_decay < 0.2 {
+			s.env_sustain += r(0.2, 0.5);
+			s.env_decay += r(0.2, 0.5);
+		}
+
+		s.lpf_resonance = r(-1.0, 1.0);
+		s.lpf_freq = 1.0 - r(0.0, 1.0).powi(3);
+		s.lpf_ramp = rp(3);
+
+		if s.lpf_freq < 0.1 && s.lpf_ramp < -0.05 {
+			s.lpf_ramp = -s.lpf_ramp;
+		}
+
+		s.hpf_freq = r(0.0, 1.0).powi(5);
+		s.hpf_ramp = rp(5);
+		s.pha_offset = rp(3);
+		s.pha_ramp = rp(3);
+		s.repeat_speed = r(-1.0, 1.0);
+		s.arp_speed = r(-1.0, 1.0);
+		s.arp_mod = r(-1.0, 1.0);
+
+		return s.clamped();
+
+	}
+
+	/// randomly change the parameters by a little
+	pub fn mutate(&mut self) {
+		let mut sample = self.to_sample();
+		sample.mutate(Some(rand_seed()));
+		*self = Self {
+			volume: self.volume,
+			..Self::from_sample(&sample)
+		};
+	}
+
+	/// get a randomly changed copy
+	pub fn mutated(&self) -> Self {
+		let mut s = self.clone();
+		s.mutate();
+		return s;
+	}
+
+	/// clamp all parameters to their valid range
+	pub fn clamped(&self) -> Self {
+
+		let u = |v: f32| v.max(0.0).min(1.0);
+		let s = |v: f32| v.max(-1.0).min(1.0);
+
+		return Self {
+			wave: self.wave,
+			base_freq: u(self.base_freq),
+			freq_limit: u(self.freq_limit),
+			freq_ramp: s(self.freq_ramp),
+			// sfxr only accepts positive values here
+			freq_dramp: u(self.freq_dramp),
+			duty: u(self.duty),
+			duty_ramp: s(self.duty_ramp),
+			vib_strength: u(self.vib_strength),
+			vib_speed: u(self.vib_speed),
+			vib_delay: u(self.vib_delay),
+			env_attack: u(self.env_attack),
+			env_sustain: u(self.env_sustain),
+			env_decay: u(self.env_decay),
+			env_punch: s(self.env_punch),
+			lpf_resonance: u(self.lpf_resonance),
+			lpf_freq: u(self.lpf_freq),
+			lpf_ramp: s(self.lpf_ramp),
+			hpf_freq: u(self.hpf_freq),
+			hpf_ramp: s(self.hpf_ramp),
+			pha_offset: s(self.pha_offset),
+			pha_ramp: s(self.pha_ramp),
+			repeat_speed: u(self.repeat_speed),
+			arp_speed: u(self.arp_speed),
+			arp_mod: s(self.arp_mod),
+			volume: u(self.volume),
+		};
+
+	}
+
+	/// length of the sound
+	pub fn duration(&self) -> Duration {
+		return Duration::from_secs_f32(self.len() as f32 / SFXR_SAMPLE_RATE as f32);
+	}
+
+	// length in samples, the envelope stages are each (v ^ 2 * 100000) samples long
+	fn len(&self) -> usize {
+		let s = self.clamped();
+		let stage = |v: f32| (v * v * 100_000.0) as usize;
+		return stage(s.env_attack) + stage(s.env_sustain) + stage(s.env_decay);
+	}
+
+	/// render to an [`AudioBuffer`](struct.AudioBuffer.html)
+	pub fn to_buffer(&self) -> AudioBuffer {
+
+		let mut buf = vec![0.0; self.len()];
+		let mut gen = sfxr::Generator::new(self.to_sample());
+
+		gen.volume = self.volume.max(0.0).min(1.0);
+		gen.generate(&mut buf);
+
+		let frames = buf
+			.into_iter()
+			.map(Frame::mono)
+			.collect();
+
+		return AudioBuffer::from_frames(frames, SFXR_SAMPLE_RATE);
+
+	}
+
+	/// render to a [`Sound`](struct.Sound.html)
+	#[cfg(not(web))]
+	pub fn to_sound(&self, ctx: &Audio) -> Sound {
+		return Sound::from_buffer(ctx, self.to_buffer());
+	}
+
+	/// edit parameters with sliders in a [`ui`](../ui/index.html) window
+	///
+	/// sliders keep their own state after the first call, so a changed `Sfx` won't show up in an existing window
+	pub fn ui(&mut self, p: &mut ui::WidgetManager) -> Result<()> {
+
+		let waves = ["square", "triangle", "sine", "noise"];
+		let wave = p.select("wave", &waves, match self.wave {
+			SfxWave::Square => 0,
+			SfxWave::Triangle => 1,
+			SfxWave::Sine => 2,
+			SfxWave::Noise => 3,
+		})?;
+
+		self.wave = match wave {
+			0 => SfxWave::Square,
+			1 => SfxWave::Triangle,
+			2 => SfxWave::Sine,
+			_ => SfxWave::Noise,
+		};
+
+		self.volume = p.slider("volume", self.volume, 0.0, 1.0)?;
+		self.env_attack = p.slider("attack", self.env_attack, 0.0, 1.0)?;
+		self.env_sustain = p.slider("sustain", self.env_sustain, 0.0, 1.0)?;
+		self.env_punch = p.slider("punch", self.env_punch, -1.0, 1.0)?;
+		self.env_decay = p.slider("decay", self.env_decay, 0.0, 1.0)?;
+		self.base_freq = p.slider("freq", self.base_freq, 0.0, 1.0)?;
+		self.freq_limit = p.slider("freq limit", self.freq_limit, 0.0, 1.0)?;
+		self.freq_ramp = p.slider("freq ramp", self.freq_ramp, -1.0, 1.0)?;
+		self.freq_dramp = p.slider("freq delta ramp", self.freq_dramp, 0.0, 1.0)?;
+		self.vib_strength = p.slider("vibrato strength", self.vib_strength, 0.0, 1.0)?;
+		self.vib_speed = p.slider("vibrato speed", self.vib_speed, 0.0, 1.0)?;
+		self.vib_delay = p.slider("vibrato delay", self.vib_delay, 0.0, 1.0)?;
+		self.arp_mod = p.slider("arp amount", self.arp_mod, -1.0, 1.0)?;
+		self.arp_speed = p.slider("arp speed", self.arp_speed, 0.0, 1.0)?;
+		self.duty = p.slider("duty", self.duty, 0.0, 1.0)?;
+		self.duty_ramp = p.slider("duty ramp", self.duty_ramp, -1.0, 1.0)?;
+		self.repeat_speed = p.slider("repeat speed", self.repeat_speed, 0.0, 1.0)?;
+		self.pha_offset = p.slider("phaser offset", self.pha_offset, -1.0, 1.0)?;
+		self.pha_ramp = p.slider("phaser ramp", self.pha_ramp, -1.0, 1.0)?;
+		self.lpf_freq = p.slider("lowpass freq", self.lpf_freq, 0.0, 1.0)?;
+		self.lpf_ramp = p.slider("lowpass ramp", self.lpf_ramp, -1.0, 1.0)?;
+		self.lpf_resonance = p.slider("lowpass resonance", self.lpf_resonance, 0.0, 1.0)?;
+		self.hpf_freq = p.slider("highpass freq", self.hpf_freq, 0.0, 1.0)?;
+		self.hpf_ramp = p.slider("highpass ramp", self.hpf_ramp, -1.0, 1.0)?;
+
+		return Ok(());
+
+	}
+
+	fn to_sample(&self) -> sfxr::Sample {
+
+		let s = self.clamped();
+
+		return sfxr::Sample {
+			wave_type: match s.wave {
+				SfxWave::Square => sfxr::WaveType::Square,
+				SfxWave::Triangle => sfxr::WaveType::Triangle,
+				SfxWave::Sine => sfxr::WaveType::Sine,
+				SfxWave::Noise => sfxr::WaveType::Noise,
+			},
+			base_freq: s.base_freq as f64,
+			freq_limit: s.freq_limit as f64,
+			freq_ramp: s.freq_ramp as f64,
+			freq_dramp: s.freq_dramp as f64,
+			duty: s.duty,
+			duty_ramp: s.duty_ramp,
+			vib_strength: s.vib_strength as f64,
+			vib_speed: s.vib_speed as f64,
+			vib_delay: s.vib_delay,
+			env_attack: s.env_attack,
+			env_sustain: s.env_sustain,
+			env_decay: s.env_decay,
+			env_punch: s.env_punch,
+			lpf_resonance: s.lpf_resonance,
+			lpf_freq: s.lpf_freq,
+			lpf_ramp: s.lpf_ramp,
+			hpf_freq: s.hpf_freq,
+			hpf_ramp: s.hpf_ramp,
+			pha_offset: s.pha_offset,
+			pha_ramp: s.pha_ramp,
+			repeat_speed: s.repeat_speed,
+			arp_speed: s.arp_speed,
+			arp_mod: s.arp_mod as f64,
+		};
+
+	}
+
+	fn from_sample(s: &sfxr::Sample) -> Self {
+		return Self {
+			wave: match s.wave_type {
+				sfxr::WaveType::Square => SfxWave::Square,
+				sfxr::WaveType::Triangle => SfxWave::Triangle,
+				sfxr::WaveType::Sine => SfxWave::Sine,
+				sfxr::WaveType::Noise => SfxWave::Noise,
+			},
+			base_freq: s.base_freq as f32,
+			freq_limit: s.freq_limit as f32,
+			freq_ramp: s.freq_ramp as f32,
+			freq_dramp: s.freq_dramp as f32,
+			duty: s.duty,
+			duty_ramp: s.duty_ramp,
+			vib_strength: s.vib_strength as f32,
+			vib_speed: s.vib_speed as f32,
+			vib_delay: s.vib_delay,
+			env_attack: s.env_attack,
+			env_sustain: s.env_sustain,
+			env_decay: s.env_decay,
+			env_punch: s.env_punch,
+			lpf_resonance: s.lpf_resonance,
+			lpf_freq: s.lpf_freq,
+			lpf_ramp: s.lpf_ramp,
+			hpf_freq: s.hpf_freq,
+			hpf_ramp: s.hpf_ramp,
+			pha_offset: s.pha_offset,
+			pha_ramp: s.pha_ramp,
+			repeat_speed: s.repeat_speed,
+			arp_speed: s.arp_speed,
+			arp_mod: s.arp_mod as f32,
+			volume: 0.2,
+		};
+	}
+
+}
+
+#[test]
+fn sfx_presets() {
+
+	for p in &[
+		SfxPreset::Coin,
+		SfxPreset::Jump,
+		SfxPreset::Explosion,
+		SfxPreset::Laser,
+		SfxPreset::Hit,
+		SfxPreset::Powerup,
+		SfxPreset::Blip,
+	] {
+		let sfx = Sfx::preset_seeded(*p, 7);
+		assert_eq!(sfx, Sfx::preset_seeded(*p, 7));
+		assert_eq!(sfx.to_buffer().frames(), Sfx::preset_seeded(*p, 7).to_buffer().frames());
+	}
+
+	// a plain sine tone, no filters or effects
+	let tone = Sfx {
+		wave: SfxWave::Sine,
+		base_freq: 0.3,
+		env_attack: 0.0,
+		env_sustain: 0.5,
+		env_decay: 0.5,
+		env_punch: 0.0,
+		..Sfx::new()
+	};
+
+	let buf = tone.to_buffer();
+	let samples = buf.frames().iter().map(|f| f.left).collect::<Vec<f32>>();
+	let peak = |s: &[f32]| s.iter().fold(0.0, |m: f32, v| m.max(v.abs()));
+
+	assert_eq!(samples.len(), 50000);
+
+	// holds full amplitude, then fades out linearly
+	let peaks = samples.chunks(2000).map(peak).collect::<Vec<f32>>();
+
+	let top = peaks[0];
+
+	assert!(top > 0.0);
+	assert!(peaks[..12].iter().all(|p| *p > top * 0.95));
+	assert!(peaks[14..].windows(2).all(|w| w[1] < w[0] - top * 0.05));
+	assert!(peaks[24] < top * 0.1);
+
+	// sfxr runs 8x supersampled with a period of 100 / (freq² + 0.001)
+	let expected = 44100.0 * 8.0 * (0.3 * 0.3 + 0.001) / 100.0;
+	let crossings = samples[..22050]
+		.windows(2)
+		.filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+		.count();
+	let hz = crossings as f32 * 2.0;
+
+	assert!((hz - expected).abs() < 4.0, "expected {}hz, got {}hz", expected, hz);
+
+	let sfx = Sfx::preset_seeded(SfxPreset::Explosion, 0);
+	let json = data::to_json(&sfx).unwrap();
+
+	assert_eq!(data::from_json::<Sfx>(&json).unwrap(), sfx);
+
+}
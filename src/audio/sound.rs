@@ -15,13 +15,17 @@ impl Sound {
 	pub fn from_bytes(ctx: &Audio, data: &[u8]) -> Result<Self> {
 
 		let buffer = AudioBuffer::from_bytes(data)?;
-		let playback = AudioBufferPlayback::new(Arc::new(buffer));
 
-		return Ok(Self {
-			playback: playback,
-			mixer: Arc::clone(ctx.mixer()),
-		});
+		return Ok(Self::from_buffer(ctx, buffer));
+
+	}
 
+	/// create sound from an [`AudioBuffer`](AudioBuffer)
+	pub fn from_buffer(ctx: &Audio, buffer: AudioBuffer) -> Self {
+		return Self {
+			playback: AudioBufferPlayback::new(Arc::new(buffer)),
+			mixer: Arc::clone(ctx.mixer()),
+		};
 	}
 
 	/// play sound
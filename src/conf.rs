@@ -29,6 +29,8 @@ pub struct Conf {
 	pub multi_sample: Option<u16>,
 	pub canvas_root: CanvasRoot,
 	pub default_font: Option<gfx::BitmapFontData>,
+	#[cfg(feature = "midi")]
+	pub midi_input: Option<String>,
 }
 
 impl Conf {
@@ -64,6 +66,8 @@ impl Default for Conf {
 			multi_sample: None,
 			canvas_root: CanvasRoot::Body,
 			default_font: None,
+			#[cfg(feature = "midi")]
+			midi_input: None,
 		};
 	}
 
@@ -152,5 +156,12 @@ impl Launcher {
 		return self;
 	}
 
+	/// only listen to MIDI input ports whose name contains this
+	#[cfg(feature = "midi")]
+	pub fn midi_input(mut self, name: &str) -> Self {
+		self.conf.midi_input = Some(name.to_owned());
+		return self;
+	}
+
 }
 
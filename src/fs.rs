@@ -112,6 +112,16 @@ pub fn read_str(path: impl AsRef<Path>) -> Result<String> {
 
 }
 
+/// write bytes to a file, relative to the working dir
+pub fn write(path: impl AsRef<Path>, data: impl AsRef<[u8]>) -> Result<()> {
+
+	let path = path.as_ref();
+
+	return fs::write(&path, data)
+		.map_err(|_| format!("failed to write file {}", path.display()));
+
+}
+
 /// get file basename
 pub fn basename(path: impl AsRef<Path>) -> Result<String> {
 
@@ -433,6 +433,28 @@ fn box_plane(b: BBox, p: Plane) -> bool {
 
 }
 
+/// if a box is inside or touching a frustum, conservative near the corners
+pub fn frustum_box(f: Frustum, b: BBox) -> bool {
+
+	for p in &f.planes {
+
+		// the box corner furthest along the plane normal
+		let pt = vec3!(
+			if p.normal.x > 0.0 { b.max.x } else { b.min.x },
+			if p.normal.y > 0.0 { b.max.y } else { b.min.y },
+			if p.normal.z > 0.0 { b.max.z } else { b.min.z },
+		);
+
+		if Vec3::dot(p.normal, pt) + p.dist < 0.0 {
+			return false;
+		}
+
+	}
+
+	return true;
+
+}
+
 pub fn sphere_plane(s: Sphere, p: Plane) -> bool {
 	return f32::abs( Vec3::dot(p.normal, s.center) + p.dist ) <= s.radius;
 }
@@ -251,6 +251,41 @@ impl Plane {
 
 }
 
+/// View Frustum, Planes Facing Inwards
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Frustum {
+	pub planes: [Plane; 6],
+}
+
+impl Frustum {
+
+	/// extract planes from a projection * view matrix
+	pub fn from_mat(m: Mat4) -> Self {
+
+		let m = m.as_arr();
+		let row = |i: usize| vec4!(m[i], m[4 + i], m[8 + i], m[12 + i]);
+		let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+		let plane = |v: Vec4| {
+			let len = v.xyz().len();
+			return Plane::new(v.xyz() / len, v.w / len);
+		};
+
+		return Self {
+			planes: [
+				plane(r3 + r0),
+				plane(r3 - r0),
+				plane(r3 + r1),
+				plane(r3 - r1),
+				plane(r3 + r2),
+				plane(r3 - r2),
+			],
+		};
+
+	}
+
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Circle {
 	pub center: Vec2,
@@ -0,0 +1,676 @@
+// wengwengweng
+
+use super::*;
+
+type NodeID = usize;
+
+/// Local Transform & Morph Target Weights of Each Animated Node
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pose {
+	pub transforms: HashMap<NodeID, Transform>,
+	pub weights: HashMap<NodeID, Vec<f32>>,
+}
+
+impl Pose {
+
+	pub fn is_empty(&self) -> bool {
+		return self.transforms.is_empty() && self.weights.is_empty();
+	}
+
+	/// only keep these nodes
+	pub fn retain(&mut self, nodes: &[NodeID]) {
+		self.transforms.retain(|id, _| nodes.contains(id));
+		self.weights.retain(|id, _| nodes.contains(id));
+	}
+
+	/// replace nodes with the ones from another pose
+	pub fn extend(&mut self, other: Pose) {
+		self.transforms.extend(other.transforms);
+		self.weights.extend(other.weights);
+	}
+
+}
+
+/// How Values Between Keyframes Are Computed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Interpolation {
+	Step,
+	Linear,
+	/// hermite spline, each keyframe has an in tangent, a value and an out tangent
+	CubicSpline,
+}
+
+pub(super) trait TrackValue: Lerp {
+	fn interp(self, to: Self, t: f32) -> Self {
+		return self.lerp(to, t);
+	}
+	fn fix(self) -> Self {
+		return self;
+	}
+}
+
+impl TrackValue for f32 {}
+impl TrackValue for Vec3 {}
+
+// rotation tracks are quaternions
+impl TrackValue for Vec4 {
+	fn interp(self, to: Self, t: f32) -> Self {
+		return slerp(self, to, t);
+	}
+	fn fix(self) -> Self {
+		return quat_unit(self);
+	}
+}
+
+fn quat_dot(q1: Vec4, q2: Vec4) -> f32 {
+	return q1.x * q2.x + q1.y * q2.y + q1.z * q2.z + q1.w * q2.w;
+}
+
+fn quat_unit(q: Vec4) -> Vec4 {
+	let len = quat_dot(q, q).sqrt();
+	if len == 0.0 {
+		return vec4!(0, 0, 0, 1);
+	}
+	return q * (1.0 / len);
+}
+
+/// spherical interpolation between 2 quaternions, through the shortest path
+pub fn slerp(q1: Vec4, q2: Vec4, t: f32) -> Vec4 {
+
+	let t = t.max(0.0).min(1.0);
+	let mut d = quat_dot(q1, q2);
+	let mut q2 = q2;
+
+	if d < 0.0 {
+		d = -d;
+		q2 = q2 * -1.0;
+	}
+
+	// too close, lerp is fine & avoids dividing by ~0
+	if d > 0.9995 {
+		return quat_unit(q1.lerp(q2, t));
+	}
+
+	let th0 = d.acos();
+	let th = th0 * t;
+	let s1 = th.sin() / th0.sin();
+	let s0 = th.cos() - d * s1;
+
+	return q1 * s0 + q2 * s1;
+
+}
+
+/// Keyframes of a Single Property
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Track<T> {
+	pub interpolation: Interpolation,
+	pub times: Vec<f32>,
+	/// 3 values per keyframe for [`CubicSpline`](enum.Interpolation.html#variant.CubicSpline)
+	pub values: Vec<T>,
+}
+
+impl<T: Copy> Track<T> {
+
+	/// linear track from (time, value) pairs
+	pub fn linear(keys: &[(f32, T)]) -> Self {
+		return Self {
+			interpolation: Interpolation::Linear,
+			times: keys.iter().map(|(t, _)| *t).collect(),
+			values: keys.iter().map(|(_, v)| *v).collect(),
+		};
+	}
+
+	pub fn len(&self) -> f32 {
+		return self.times.last().cloned().unwrap_or(0.0);
+	}
+
+	fn value(&self, i: usize) -> T {
+		return match self.interpolation {
+			Interpolation::CubicSpline => self.values[i * 3 + 1],
+			_ => self.values[i],
+		};
+	}
+
+}
+
+pub(super) fn get_track_val<T: TrackValue>(track: &Track<T>, t: f32) -> Option<T> {
+
+	let count = track.times.len();
+	let per_key = match track.interpolation {
+		Interpolation::CubicSpline => 3,
+		_ => 1,
+	};
+
+	if count == 0 || track.values.len() < count * per_key {
+		return None;
+	}
+
+	// clamp outside of the keyframes
+	if t <= track.times[0] {
+		return Some(track.value(0));
+	}
+
+	if t >= track.times[count - 1] {
+		return Some(track.value(count - 1));
+	}
+
+	let i = track.times.partition_point(|k| *k <= t) - 1;
+	let t1 = track.times[i];
+	let t2 = track.times[i + 1];
+	let dt = t2 - t1;
+	let u = if dt > 0.0 { (t - t1) / dt } else { 0.0 };
+
+	return Some(match track.interpolation {
+
+		Interpolation::Step => track.value(i),
+		Interpolation::Linear => track.value(i).interp(track.value(i + 1), u),
+
+		Interpolation::CubicSpline => {
+
+			let p1 = track.values[i * 3 + 1];
+			let m1 = track.values[i * 3 + 2] * dt;
+			let p2 = track.values[i * 3 + 4];
+			let m2 = track.values[i * 3 + 3] * dt;
+
+			let u2 = u * u;
+			let u3 = u2 * u;
+
+			let v = p1 * (2.0 * u3 - 3.0 * u2 + 1.0)
+				+ m1 * (u3 - 2.0 * u2 + u)
+				+ p2 * (-2.0 * u3 + 3.0 * u2)
+				+ m2 * (u3 - u2);
+
+			v.fix()
+
+		},
+
+	});
+
+}
+
+/// Animated Properties of a Node
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Anim {
+	pub pos: Option<Track<Vec3>>,
+	pub rot: Option<Track<Vec4>>,
+	pub scale: Option<Track<Vec3>>,
+	/// 1 track for each morph target
+	pub weights: Vec<Track<f32>>,
+}
+
+impl Anim {
+
+	pub fn len(&self) -> f32 {
+
+		let t1 = self.pos.as_ref().map(|t| t.len()).unwrap_or(0.0);
+		let t2 = self.rot.as_ref().map(|t| t.len()).unwrap_or(0.0);
+		let t3 = self.scale.as_ref().map(|t| t.len()).unwrap_or(0.0);
+		let t4 = self.weights.iter().map(|t| t.len()).fold(0.0, f32::max);
+
+		return t1
+			.max(t2)
+			.max(t3)
+			.max(t4)
+			;
+
+	}
+
+	/// apply the animated properties at time t to a transform
+	pub fn apply(&self, tr: Transform, t: f32) -> Transform {
+		return Transform {
+			pos: self.pos.as_ref().and_then(|k| get_track_val(k, t)).unwrap_or(tr.pos),
+			rot: self.rot.as_ref().and_then(|k| get_track_val(k, t)).unwrap_or(tr.rot),
+			scale: self.scale.as_ref().and_then(|k| get_track_val(k, t)).unwrap_or(tr.scale),
+		};
+	}
+
+	/// apply the animated morph target weights at time t
+	pub fn apply_weights(&self, weights: &[f32], t: f32) -> Vec<f32> {
+		let mut weights = weights.to_vec();
+		for (i, track) in self.weights.iter().enumerate() {
+			if let Some(w) = get_track_val(track, t) {
+				if i >= weights.len() {
+					weights.resize(i + 1, 0.0);
+				}
+				weights[i] = w;
+			}
+		}
+		return weights;
+	}
+
+}
+
+/// A Named Event at a Time in a Clip
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClipEvent {
+	pub time: f32,
+	pub name: String,
+}
+
+/// A Named Animation, Like "walk" or "jump"
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AnimationClip {
+	pub name: Option<String>,
+	pub anims: HashMap<NodeID, Anim>,
+	pub events: Vec<ClipEvent>,
+	len: f32,
+}
+
+impl AnimationClip {
+
+	pub fn new(name: Option<String>, anims: HashMap<NodeID, Anim>) -> Self {
+		let len = anims
+			.values()
+			.map(|a| a.len())
+			.fold(0.0, f32::max);
+		return Self {
+			name: name,
+			anims: anims,
+			events: vec![],
+			len: len,
+		};
+	}
+
+	/// duration in seconds
+	pub fn len(&self) -> f32 {
+		return self.len;
+	}
+
+	/// add an event that's reported by [`Animator::update`](struct.Animator.html#method.update) when passed
+	pub fn add_event(&mut self, time: f32, name: &str) {
+		self.events.push(ClipEvent {
+			time: time,
+			name: name.to_string(),
+		});
+	}
+
+}
+
+/// An Event Reported by [`Animator::update`](struct.Animator.html#method.update)
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimEvent {
+	pub layer: usize,
+	pub clip: usize,
+	pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Playback {
+	clip: usize,
+	time: f32,
+	looping: bool,
+	fresh: bool,
+}
+
+impl Playback {
+
+	fn new(clip: usize, looping: bool) -> Self {
+		return Self {
+			clip: clip,
+			time: 0.0,
+			looping: looping,
+			fresh: true,
+		};
+	}
+
+	fn advance(&mut self, clip: &AnimationClip, dt: f32, mut on_event: impl FnMut(&ClipEvent)) {
+
+		let len = clip.len();
+		let from = self.time;
+		let mut to = from + dt;
+		let fresh = self.fresh;
+
+		self.fresh = false;
+
+		let mut fire = |from: f32, to: f32, inclusive: bool| {
+			for e in &clip.events {
+				if (e.time > from || (inclusive && e.time == from)) && e.time <= to {
+					on_event(e);
+				}
+			}
+		};
+
+		if len <= 0.0 {
+			self.time = 0.0;
+			return;
+		}
+
+		if self.looping {
+			fire(from, to.min(len), fresh);
+			while to > len {
+				to -= len;
+				fire(0.0, to.min(len), true);
+			}
+			// playing backwards
+			while to < 0.0 {
+				to += len;
+			}
+		} else {
+			to = to.max(0.0).min(len);
+			fire(from, to, fresh);
+		}
+
+		self.time = to;
+
+	}
+
+}
+
+/// A Layer of [`Animator`](struct.Animator.html), Plays 1 Clip (2 When Crossfading)
+#[derive(Clone, Debug)]
+pub struct AnimLayer {
+	cur: Option<Playback>,
+	prev: Option<Playback>,
+	fade: f32,
+	fade_len: f32,
+	speed: f32,
+	weight: f32,
+	mask: Option<Vec<NodeID>>,
+}
+
+impl AnimLayer {
+
+	fn new() -> Self {
+		return Self {
+			cur: None,
+			prev: None,
+			fade: 0.0,
+			fade_len: 0.0,
+			speed: 1.0,
+			weight: 1.0,
+			mask: None,
+		};
+	}
+
+	/// play a clip from the start, looped
+	pub fn play(&mut self, clip: usize) -> &mut Self {
+		return self.crossfade(clip, 0.0);
+	}
+
+	/// play a clip from the start once, holding the last frame
+	pub fn play_once(&mut self, clip: usize) -> &mut Self {
+		self.play(clip);
+		if let Some(cur) = &mut self.cur {
+			cur.looping = false;
+		}
+		return self;
+	}
+
+	/// fade from the current clip to another in `dur` seconds
+	pub fn crossfade(&mut self, clip: usize, dur: f32) -> &mut Self {
+		self.prev = if dur > 0.0 { self.cur.take() } else { None };
+		self.cur = Some(Playback::new(clip, true));
+		self.fade = 0.0;
+		self.fade_len = dur;
+		return self;
+	}
+
+	pub fn stop(&mut self) -> &mut Self {
+		self.cur = None;
+		self.prev = None;
+		return self;
+	}
+
+	pub fn looping(&mut self, b: bool) -> &mut Self {
+		if let Some(cur) = &mut self.cur {
+			cur.looping = b;
+		}
+		return self;
+	}
+
+	pub fn speed(&mut self, s: f32) -> &mut Self {
+		self.speed = s;
+		return self;
+	}
+
+	/// how much this layer overrides layers below, 0 - 1
+	pub fn weight(&mut self, w: f32) -> &mut Self {
+		self.weight = w;
+		return self;
+	}
+
+	/// only affect these nodes, see [`Model::subtree`](struct.Model.html#method.subtree)
+	pub fn mask(&mut self, nodes: Vec<NodeID>) -> &mut Self {
+		self.mask = Some(nodes);
+		return self;
+	}
+
+	/// current clip
+	pub fn clip(&self) -> Option<usize> {
+		return self.cur.map(|p| p.clip);
+	}
+
+	/// current time in the current clip
+	pub fn time(&self) -> f32 {
+		return self.cur.map(|p| p.time).unwrap_or(0.0);
+	}
+
+	pub fn fading(&self) -> bool {
+		return self.prev.is_some();
+	}
+
+	fn pose(&self, model: &Model) -> Option<Pose> {
+
+		let cur = self.cur?;
+		let mut pose = model.sample_clip(cur.clip, cur.time);
+
+		if let Some(prev) = self.prev {
+			let from = model.sample_clip(prev.clip, prev.time);
+			pose = model.blend_pose(&from, &pose, self.fade / self.fade_len);
+		}
+
+		if let Some(mask) = &self.mask {
+			pose.retain(mask);
+		}
+
+		return Some(pose);
+
+	}
+
+}
+
+/// Plays, Crossfades & Layers [`AnimationClip`](struct.AnimationClip.html)s of a [`Model`](struct.Model.html)
+///
+/// layers are blended in order, each overriding the ones below by its weight
+#[derive(Clone, Debug)]
+pub struct Animator {
+	layers: Vec<AnimLayer>,
+}
+
+impl Default for Animator {
+	fn default() -> Self {
+		return Self::new();
+	}
+}
+
+impl Animator {
+
+	/// create an animator with 1 layer
+	pub fn new() -> Self {
+		return Self {
+			layers: vec![AnimLayer::new()],
+		};
+	}
+
+	/// add a layer on top, returns its index
+	pub fn add_layer(&mut self) -> usize {
+		self.layers.push(AnimLayer::new());
+		return self.layers.len() - 1;
+	}
+
+	pub fn layer(&self, i: usize) -> Option<&AnimLayer> {
+		return self.layers.get(i);
+	}
+
+	pub fn layer_mut(&mut self, i: usize) -> Option<&mut AnimLayer> {
+		return self.layers.get_mut(i);
+	}
+
+	/// play a clip on the base layer, looped
+	pub fn play(&mut self, clip: usize) -> &mut AnimLayer {
+		return self.layers[0].play(clip);
+	}
+
+	/// crossfade to a clip on the base layer
+	pub fn crossfade(&mut self, clip: usize, dur: f32) -> &mut AnimLayer {
+		return self.layers[0].crossfade(clip, dur);
+	}
+
+	/// advance time, returns the clip events passed
+	pub fn update(&mut self, model: &Model, dt: f32) -> Vec<AnimEvent> {
+
+		let mut events = vec![];
+
+		for (i, layer) in self.layers.iter_mut().enumerate() {
+
+			let dt = dt * layer.speed;
+
+			if let Some(cur) = &mut layer.cur {
+				if let Some(clip) = model.clip(cur.clip) {
+					let id = cur.clip;
+					cur.advance(clip, dt, |e| {
+						events.push(AnimEvent {
+							layer: i,
+							clip: id,
+							name: e.name.clone(),
+						});
+					});
+				}
+			}
+
+			// the clip fading out keeps moving but doesn't report events
+			if let Some(prev) = &mut layer.prev {
+				if let Some(clip) = model.clip(prev.clip) {
+					prev.advance(clip, dt, |_| {});
+				}
+			}
+
+			if layer.prev.is_some() {
+				layer.fade += dt.abs();
+				if layer.fade >= layer.fade_len {
+					layer.prev = None;
+				}
+			}
+
+		}
+
+		return events;
+
+	}
+
+	/// get the blended pose, pass to [`shapes::Model::pose`](shapes/struct.Model.html#method.pose)
+	pub fn pose(&self, model: &Model) -> Pose {
+
+		let mut pose = Pose::default();
+
+		for layer in &self.layers {
+			if let Some(lpose) = layer.pose(model) {
+				if pose.is_empty() && layer.weight >= 1.0 {
+					pose = lpose;
+				} else {
+					let mut over = pose.clone();
+					over.extend(lpose);
+					pose = model.blend_pose(&pose, &over, layer.weight);
+				}
+			}
+		}
+
+		return pose;
+
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn anim() {
+
+	let step = Track {
+		interpolation: Interpolation::Step,
+		..Track::linear(&[(0.0, vec3!(0)), (1.0, vec3!(10, 0, 0))])
+	};
+
+	let linear = Track::linear(&[(1.0, vec3!(0)), (3.0, vec3!(10, 0, 0))]);
+
+	// in tangent, value, out tangent
+	let cubic = Track {
+		interpolation: Interpolation::CubicSpline,
+		times: vec![0.0, 1.0],
+		values: vec![vec3!(0), vec3!(0), vec3!(0), vec3!(0), vec3!(10, 0, 0), vec3!(0)],
+	};
+
+	assert_eq!(get_track_val(&step, 0.9), Some(vec3!(0)));
+	assert_eq!(get_track_val(&step, 1.0), Some(vec3!(10, 0, 0)));
+	assert_eq!(get_track_val(&linear, 0.0), Some(vec3!(0)));
+	assert_eq!(get_track_val(&linear, 2.0), Some(vec3!(5, 0, 0)));
+	assert_eq!(get_track_val(&linear, 9.0), Some(vec3!(10, 0, 0)));
+	assert!((get_track_val(&cubic, 0.5).unwrap().x - 5.0).abs() < 0.001);
+	assert!(get_track_val(&cubic, 0.25).unwrap().x < 2.5);
+
+	let h = std::f32::consts::FRAC_1_SQRT_2;
+	let q = slerp(vec4!(0, 0, 0, 1), vec4!(0, 0, 1, 0), 0.5);
+
+	// 90 degrees about z, not the unnormalized lerp
+	assert!((q.z - h).abs() < 0.001 && (q.w - h).abs() < 0.001);
+
+	let mut h = test_headless(8, 8);
+
+	let gfx = h.gfx();
+	let mut model = Model::from_meshdata(gfx, geom::meshgen::cube()).unwrap();
+
+	let move_to = |x: f32| {
+		return AnimationClip::new(None, hmap![
+			0 => Anim {
+				pos: Some(Track::linear(&[(0.0, vec3!(x, 0, 0)), (1.0, vec3!(x, 1, 0))])),
+				..Anim::default()
+			},
+		]);
+	};
+
+	let mut walk = move_to(0.0);
+
+	walk.name = Some(String::from("walk"));
+	walk.add_event(0.5, "step");
+
+	let walk = model.add_clip(walk);
+	let run = model.add_clip(move_to(10.0));
+
+	assert_eq!(model.clip_id("walk"), Some(walk));
+
+	let mut animator = Animator::new();
+
+	animator.play(walk);
+
+	let events = animator.update(&model, 0.6);
+
+	assert_eq!(events.len(), 1);
+	assert_eq!(events[0].name, "step");
+	assert_eq!(animator.pose(&model).transforms[&0].pos.y, 0.6);
+
+	// loops & passes the event again
+	assert_eq!(animator.update(&model, 1.0).len(), 1);
+	assert!((animator.layer(0).unwrap().time() - 0.6).abs() < 0.001);
+
+	animator.crossfade(run, 1.0);
+	animator.update(&model, 0.5);
+
+	assert!((animator.pose(&model).transforms[&0].pos.x - 5.0).abs() < 0.001);
+
+	animator.update(&model, 0.5);
+
+	assert!(!animator.layer(0).unwrap().fading());
+	assert_eq!(animator.pose(&model).transforms[&0].pos.x, 10.0);
+
+	let top = animator.add_layer();
+
+	animator.layer_mut(top).unwrap().play_once(walk).weight(0.5);
+	animator.update(&model, 5.0);
+
+	assert_eq!(animator.pose(&model).transforms[&0].pos.x, 5.0);
+	assert_eq!(animator.pose(&model).transforms[&0].pos.y, 1.0);
+
+	animator.layer_mut(top).unwrap().mask(vec![]);
+
+	assert_eq!(animator.pose(&model).transforms[&0].pos.x, 10.0);
+
+}
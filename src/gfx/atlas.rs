@@ -0,0 +1,663 @@
+// wengwengweng
+
+use super::*;
+
+/// Packing Algorithms
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AtlasPacking {
+	/// fast, good for images with similar heights like glyphs or tiles
+	Skyline,
+	/// slower, packs tighter with mixed sizes
+	MaxRects,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasConf {
+	/// width of each page
+	pub width: i32,
+	/// height of each page
+	pub height: i32,
+	/// empty pixels between images
+	pub padding: i32,
+	/// repeat edge pixels of each image outwards, avoids bleeding with linear filtering
+	pub extrude: i32,
+	pub packing: AtlasPacking,
+	pub tex_conf: TextureConf,
+}
+
+impl Default for AtlasConf {
+	fn default() -> Self {
+		return Self {
+			width: 1024,
+			height: 1024,
+			padding: 1,
+			extrude: 1,
+			packing: AtlasPacking::MaxRects,
+			tex_conf: TextureConf::default(),
+		};
+	}
+}
+
+/// A Packed Image in a [`TextureAtlas`](struct.TextureAtlas.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+	/// index of the page texture
+	pub page: usize,
+	/// normalized region for [`Sprite::quad`](shapes/struct.Sprite.html#method.quad)
+	pub quad: Quad,
+	pub x: i32,
+	pub y: i32,
+	pub width: i32,
+	pub height: i32,
+}
+
+/// Runtime Texture Atlas
+///
+/// packs images into a few big textures so sprites from different images can be drawn in one batch, a new page is added when the current ones are full
+/// ```ignore
+/// let mut atlas = TextureAtlas::new(d.gfx)?;
+///
+/// atlas.add_glob(d.gfx, "res/sprites/*.png")?;
+///
+/// let r = atlas.get("car").unwrap();
+///
+/// d.gfx.draw(&shapes::sprite(atlas.texture(r.page).unwrap()).quad(r.quad))?;
+/// ```
+pub struct TextureAtlas {
+	conf: AtlasConf,
+	pages: Vec<Page>,
+	regions: HashMap<String, AtlasRegion>,
+	sprites: HashMap<String, ase::SpriteData>,
+}
+
+struct Page {
+	tex: Texture,
+	packer: Packer,
+}
+
+impl TextureAtlas {
+
+	/// create with default conf
+	pub fn new(ctx: &impl GLCtx) -> Result<Self> {
+		return Self::with_conf(ctx, AtlasConf::default());
+	}
+
+	pub fn with_conf(ctx: &impl GLCtx, conf: AtlasConf) -> Result<Self> {
+
+		if conf.width <= 0 || conf.height <= 0 {
+			return Err(format!("atlas size must be > 0"));
+		}
+
+		let mut atlas = Self {
+			conf: conf,
+			pages: vec![],
+			regions: hmap![],
+			sprites: hmap![],
+		};
+
+		atlas.add_page(ctx)?;
+
+		return Ok(atlas);
+
+	}
+
+	fn add_page(&mut self, ctx: &impl GLCtx) -> Result<()> {
+
+		let (w, h) = (self.conf.width, self.conf.height);
+		let data = vec![0; w as usize * h as usize * 4];
+
+		self.pages.push(Page {
+			tex: Texture::from_raw_with_conf(ctx, w, h, &data, self.conf.tex_conf)?,
+			packer: Packer::new(self.conf.packing, w, h),
+		});
+
+		return Ok(());
+
+	}
+
+	/// pack an image, replaces the region of an existing name (the old space is not reused)
+	pub fn add(&mut self, ctx: &impl GLCtx, name: &str, img: &img::Image) -> Result<AtlasRegion> {
+
+		let e = self.conf.extrude;
+		let p = self.conf.padding;
+		let (w, h) = (img.width() + e * 2 + p, img.height() + e * 2 + p);
+
+		if w > self.conf.width || h > self.conf.height {
+			return Err(format!(
+				"image {} ({}x{}) is too big for atlas page ({}x{})",
+				name,
+				img.width(),
+				img.height(),
+				self.conf.width,
+				self.conf.height,
+			));
+		}
+
+		let mut pos = None;
+
+		for (i, page) in self.pages.iter_mut().enumerate() {
+			if let Some(pt) = page.packer.pack(w, h) {
+				pos = Some((i, pt));
+				break;
+			}
+		}
+
+		let (page, (x, y)) = match pos {
+			Some(pos) => pos,
+			None => {
+				self.add_page(ctx)?;
+				let i = self.pages.len() - 1;
+				let pt = self.pages[i].packer.pack(w, h)
+					.ok_or_else(|| format!("failed to pack image {}", name))?;
+				(i, pt)
+			},
+		};
+
+		let extruded = extrude(img, e)?;
+
+		self.pages[page].tex.sub_data(x, y, extruded.width(), extruded.height(), extruded.as_raw());
+
+		let (x, y) = (x + e, y + e);
+		let (pw, ph) = (self.conf.width as f32, self.conf.height as f32);
+
+		let region = AtlasRegion {
+			page: page,
+			quad: quad!(
+				x as f32 / pw,
+				y as f32 / ph,
+				img.width() as f32 / pw,
+				img.height() as f32 / ph
+			),
+			x: x,
+			y: y,
+			width: img.width(),
+			height: img.height(),
+		};
+
+		self.regions.insert(name.to_owned(), region);
+
+		return Ok(region);
+
+	}
+
+	/// pack many images, larger ones first for tighter packing
+	pub fn add_all(&mut self, ctx: &impl GLCtx, imgs: Vec<(String, img::Image)>) -> Result<()> {
+
+		let mut imgs = imgs;
+
+		imgs.sort_by_key(|(_, img)| -(img.width().max(img.height()) * 4096 + img.width().min(img.height())));
+
+		for (name, img) in imgs {
+			self.add(ctx, &name, &img)?;
+		}
+
+		return Ok(());
+
+	}
+
+	/// pack an Aseprite spritesheet as a whole, frame quads are mapped into the atlas page
+	pub fn add_ase(&mut self, ctx: &impl GLCtx, name: &str, img: &img::Image, json: &str) -> Result<ase::SpriteData> {
+		let region = self.add(ctx, name, img)?;
+		return self.add_sprite_data(name, region, json);
+	}
+
+	fn add_sprite_data(&mut self, name: &str, region: AtlasRegion, json: &str) -> Result<ase::SpriteData> {
+
+		let mut data = ase::parse(json)?;
+		let q = region.quad;
+
+		for f in &mut data.frames {
+			*f = quad!(q.x + f.x * q.w, q.y + f.y * q.h, f.w * q.w, f.h * q.h);
+		}
+
+		self.sprites.insert(name.to_owned(), data.clone());
+
+		return Ok(data);
+
+	}
+
+	/// pack every image that matches a glob pattern, named by file basename
+	///
+	/// images with a `.json` file next to them with the same name are treated as Aseprite spritesheets, see [`sprite_data`](#method.sprite_data)
+	pub fn add_glob(&mut self, ctx: &impl GLCtx, pat: &str) -> Result<Vec<String>> {
+
+		let mut imgs = vec![];
+		let mut sheets = vec![];
+
+		for path in fs::glob(pat)? {
+
+			if fs::extname(&path).map(|e| e == "json").unwrap_or(false) {
+				continue;
+			}
+
+			let name = fs::basename(&path)?;
+			let img = img::Image::from_bytes(&fs::read(&path)?)?;
+			let json_path = path.with_extension("json");
+
+			if fs::exists(&json_path) {
+				sheets.push((name.clone(), fs::read_str(&json_path)?));
+			}
+
+			imgs.push((name, img));
+
+		}
+
+		let names = imgs
+			.iter()
+			.map(|(name, _)| name.clone())
+			.collect::<Vec<String>>();
+
+		self.add_all(ctx, imgs)?;
+
+		for (name, json) in sheets {
+			if let Some(region) = self.get(&name) {
+				self.add_sprite_data(&name, region, &json)?;
+			}
+		}
+
+		return Ok(names);
+
+	}
+
+	/// get a packed region by name
+	pub fn get(&self, name: &str) -> Option<AtlasRegion> {
+		return self.regions.get(name).copied();
+	}
+
+	/// get the normalized region by name
+	pub fn quad(&self, name: &str) -> Option<Quad> {
+		return self.get(name).map(|r| r.quad);
+	}
+
+	/// get spritesheet data added by [`add_ase`](#method.add_ase) or [`add_glob`](#method.add_glob)
+	pub fn sprite_data(&self, name: &str) -> Option<&ase::SpriteData> {
+		return self.sprites.get(name);
+	}
+
+	/// get the texture of a page
+	pub fn texture(&self, page: usize) -> Option<&Texture> {
+		return self.pages.get(page).map(|p| &p.tex);
+	}
+
+	/// get the texture and normalized region by name, for `shapes::sprite(tex).quad(quad)`
+	pub fn sprite(&self, name: &str) -> Option<(&Texture, Quad)> {
+		let r = self.get(name)?;
+		return Some((self.texture(r.page)?, r.quad));
+	}
+
+	/// number of pages
+	pub fn pages(&self) -> usize {
+		return self.pages.len();
+	}
+
+	pub fn names(&self) -> impl Iterator<Item = &String> {
+		return self.regions.keys();
+	}
+
+}
+
+// pad an image by repeating its edge pixels
+fn extrude(img: &img::Image, e: i32) -> Result<img::Image> {
+
+	if e <= 0 {
+		return Ok(img.clone());
+	}
+
+	let (w, h) = (img.width(), img.height());
+	let (ew, eh) = (w + e * 2, h + e * 2);
+	let src = img.as_raw();
+	let mut data = Vec::with_capacity(ew as usize * eh as usize * 4);
+
+	for y in 0..eh {
+		let sy = (y - e).max(0).min(h - 1);
+		for x in 0..ew {
+			let sx = (x - e).max(0).min(w - 1);
+			let i = ((sy * w + sx) * 4) as usize;
+			data.extend_from_slice(&src[i..i + 4]);
+		}
+	}
+
+	return img::Image::from_raw(ew, eh, data);
+
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rect {
+	x: i32,
+	y: i32,
+	w: i32,
+	h: i32,
+}
+
+impl Rect {
+
+	fn contains(&self, other: &Self) -> bool {
+		return other.x >= self.x
+			&& other.y >= self.y
+			&& other.x + other.w <= self.x + self.w
+			&& other.y + other.h <= self.y + self.h;
+	}
+
+	fn overlaps(&self, other: &Self) -> bool {
+		return self.x < other.x + other.w
+			&& other.x < self.x + self.w
+			&& self.y < other.y + other.h
+			&& other.y < self.y + self.h;
+	}
+
+}
+
+// a segment of the skyline
+#[derive(Clone, Copy, Debug)]
+struct Skyline {
+	x: i32,
+	y: i32,
+	w: i32,
+}
+
+enum Packer {
+	Skyline {
+		width: i32,
+		height: i32,
+		lines: Vec<Skyline>,
+	},
+	MaxRects {
+		free: Vec<Rect>,
+	},
+}
+
+impl Packer {
+
+	fn new(method: AtlasPacking, w: i32, h: i32) -> Self {
+		return match method {
+			AtlasPacking::Skyline => Packer::Skyline {
+				width: w,
+				height: h,
+				lines: vec![Skyline {
+					x: 0,
+					y: 0,
+					w: w,
+				}],
+			},
+			AtlasPacking::MaxRects => Packer::MaxRects {
+				free: vec![Rect {
+					x: 0,
+					y: 0,
+					w: w,
+					h: h,
+				}],
+			},
+		};
+	}
+
+	fn pack(&mut self, w: i32, h: i32) -> Option<(i32, i32)> {
+		return match self {
+			Packer::Skyline { width, height, lines } => skyline_pack(lines, *width, *height, w, h),
+			Packer::MaxRects { free } => maxrects_pack(free, w, h),
+		};
+	}
+
+}
+
+// bottom left, picks the lowest position then the narrowest segment
+fn skyline_pack(lines: &mut Vec<Skyline>, width: i32, height: i32, w: i32, h: i32) -> Option<(i32, i32)> {
+
+	// the y the rect would sit at if placed at the start of line i
+	let fit = |lines: &[Skyline], i: usize| -> Option<i32> {
+
+		let x = lines[i].x;
+
+		if x + w > width {
+			return None;
+		}
+
+		let mut y = 0;
+		let mut left = w;
+
+		for l in &lines[i..] {
+			if left <= 0 {
+				break;
+			}
+			y = y.max(l.y);
+			left -= l.w;
+		}
+
+		if y + h > height {
+			return None;
+		}
+
+		return Some(y);
+
+	};
+
+	let mut best: Option<(usize, i32, i32)> = None;
+
+	for i in 0..lines.len() {
+		if let Some(y) = fit(lines, i) {
+			let better = match best {
+				Some((_, by, bw)) => y < by || (y == by && lines[i].w < bw),
+				None => true,
+			};
+			if better {
+				best = Some((i, y, lines[i].w));
+			}
+		}
+	}
+
+	let (i, y, _) = best?;
+	let x = lines[i].x;
+
+	lines.insert(i, Skyline {
+		x: x,
+		y: y + h,
+		w: w,
+	});
+
+	// shrink or remove the segments under the new one
+	while i + 1 < lines.len() {
+
+		let prev_end = lines[i].x + lines[i].w;
+		let next = &mut lines[i + 1];
+
+		if next.x >= prev_end {
+			break;
+		}
+
+		let shrink = prev_end - next.x;
+
+		if shrink >= next.w {
+			lines.remove(i + 1);
+		} else {
+			next.x += shrink;
+			next.w -= shrink;
+			break;
+		}
+
+	}
+
+	// merge segments with the same height
+	let mut j = 0;
+
+	while j + 1 < lines.len() {
+		if lines[j].y == lines[j + 1].y {
+			lines[j].w += lines[j + 1].w;
+			lines.remove(j + 1);
+		} else {
+			j += 1;
+		}
+	}
+
+	return Some((x, y));
+
+}
+
+// best short side fit
+fn maxrects_pack(free: &mut Vec<Rect>, w: i32, h: i32) -> Option<(i32, i32)> {
+
+	let mut best: Option<(Rect, i32, i32)> = None;
+
+	for r in free.iter() {
+
+		if r.w < w || r.h < h {
+			continue;
+		}
+
+		let short = (r.w - w).min(r.h - h);
+		let long = (r.w - w).max(r.h - h);
+
+		let better = match best {
+			Some((_, bs, bl)) => short < bs || (short == bs && long < bl),
+			None => true,
+		};
+
+		if better {
+			best = Some((Rect {
+				x: r.x,
+				y: r.y,
+				w: w,
+				h: h,
+			}, short, long));
+		}
+
+	}
+
+	let (placed, _, _) = best?;
+	let mut next = Vec::with_capacity(free.len() + 4);
+
+	// split every free rect that overlaps with the placed one into the parts around it
+	for r in free.iter() {
+
+		if !r.overlaps(&placed) {
+			next.push(*r);
+			continue;
+		}
+
+		if placed.x > r.x {
+			next.push(Rect { x: r.x, y: r.y, w: placed.x - r.x, h: r.h });
+		}
+
+		if placed.x + placed.w < r.x + r.w {
+			let x = placed.x + placed.w;
+			next.push(Rect { x: x, y: r.y, w: r.x + r.w - x, h: r.h });
+		}
+
+		if placed.y > r.y {
+			next.push(Rect { x: r.x, y: r.y, w: r.w, h: placed.y - r.y });
+		}
+
+		if placed.y + placed.h < r.y + r.h {
+			let y = placed.y + placed.h;
+			next.push(Rect { x: r.x, y: y, w: r.w, h: r.y + r.h - y });
+		}
+
+	}
+
+	// remove free rects that are inside other ones
+	let mut i = 0;
+
+	while i < next.len() {
+		let contained = next
+			.iter()
+			.enumerate()
+			.any(|(j, r)| j != i && r.contains(&next[i]) && (r != &next[i] || j < i));
+		if contained {
+			next.remove(i);
+		} else {
+			i += 1;
+		}
+	}
+
+	*free = next;
+
+	return Some((placed.x, placed.y));
+
+}
+
+#[test]
+fn packers() {
+
+	for method in &[AtlasPacking::Skyline, AtlasPacking::MaxRects] {
+
+		let mut packer = Packer::new(*method, 64, 64);
+		let mut placed: Vec<Rect> = vec![];
+
+		let sizes = [(16, 16), (32, 8), (8, 32), (20, 12), (12, 20), (16, 16), (10, 10), (30, 5)];
+
+		for (w, h) in sizes.iter() {
+			let (x, y) = packer.pack(*w, *h).unwrap();
+			let r = Rect { x: x, y: y, w: *w, h: *h };
+			assert!(r.x >= 0 && r.y >= 0 && r.x + r.w <= 64 && r.y + r.h <= 64);
+			assert!(placed.iter().all(|p| !p.overlaps(&r)));
+			placed.push(r);
+		}
+
+		assert!(packer.pack(65, 1).is_none());
+
+		// fill up the rest
+		let mut count = 0;
+		while packer.pack(8, 8).is_some() {
+			count += 1;
+		}
+		assert!(count > 0);
+
+	}
+
+	let img = img::Image::from_raw(2, 1, vec![
+		255, 0, 0, 255,
+		0, 255, 0, 255,
+	]).unwrap();
+
+	let e = extrude(&img, 2).unwrap();
+
+	assert_eq!((e.width(), e.height()), (6, 5));
+	assert_eq!(e.get(0, 0), Some(rgba!(1, 0, 0, 1)));
+	assert_eq!(e.get(5, 4), Some(rgba!(0, 1, 0, 1)));
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn add() {
+
+	let mut h = test_headless(64, 64);
+	let gfx = h.gfx();
+
+	let mut atlas = TextureAtlas::with_conf(gfx, AtlasConf {
+		width: 64,
+		height: 64,
+		..AtlasConf::default()
+	}).unwrap();
+
+	// a different color in each corner
+	let corners = img::Image::from_raw(2, 2, vec![
+		255, 0, 0, 255,
+		0, 255, 0, 255,
+		0, 0, 255, 255,
+		255, 255, 0, 255,
+	]).unwrap();
+
+	let wide = img::Image::from_raw(40, 1, vec![255; 40 * 4]).unwrap();
+
+	atlas.add(gfx, "wide", &wide).unwrap();
+	let r = atlas.add(gfx, "corners", &corners).unwrap();
+
+	assert_eq!(atlas.get("corners"), Some(r));
+	assert_eq!((r.width, r.height), (2, 2));
+
+	// the quad maps back to the pixels of the region
+	let page = atlas.texture(r.page).unwrap().capture().unwrap();
+	let (x, y) = ((r.quad.x * 64.0) as i32, (r.quad.y * 64.0) as i32);
+
+	assert_eq!((x, y), (r.x, r.y));
+	assert_eq!(((r.quad.w * 64.0) as i32, (r.quad.h * 64.0) as i32), (2, 2));
+	assert_eq!(page.get(x, y), Some(rgba!(1, 0, 0, 1)));
+	assert_eq!(page.get(x + 1, y), Some(rgba!(0, 1, 0, 1)));
+	assert_eq!(page.get(x, y + 1), Some(rgba!(0, 0, 1, 1)));
+	assert_eq!(page.get(x + 1, y + 1), Some(rgba!(1, 1, 0, 1)));
+	// extruded edges
+	assert_eq!(page.get(x - 1, y), Some(rgba!(1, 0, 0, 1)));
+
+	// too big for a page
+	assert!(atlas.add(gfx, "huge", &img::Image::from_raw(65, 1, vec![0; 65 * 4]).unwrap()).is_err());
+
+}
@@ -2,12 +2,168 @@
 
 use super::*;
 
-// TODO: take blending and stuff into account
-#[derive(Clone, PartialEq)]
+// everything that has to be the same for 2 draws to be in the same batch
+#[derive(Clone)]
 struct RenderState<V: VertexLayout, U: UniformLayout> {
 	pipeline: Pipeline<V, U>,
 	prim: Primitive,
 	uniform: U,
+	gl: GLState,
+}
+
+impl<V: VertexLayout, U: UniformLayout + PartialEq> PartialEq for RenderState<V, U> {
+	fn eq(&self, other: &Self) -> bool {
+		return self.pipeline == other.pipeline
+			&& self.prim == other.prim
+			&& self.gl == other.gl
+			&& self.uniform == other.uniform;
+	}
+}
+
+impl<V: VertexLayout, U: UniformLayout> RenderState<V, U> {
+
+	// the first thing that's different, checked from the most expensive change
+	fn diff(&self, other: &Self) -> Option<FlushReason> {
+
+		if self.pipeline != other.pipeline {
+			return Some(FlushReason::Shader);
+		}
+
+		if self.gl.blend != other.gl.blend {
+			return Some(FlushReason::Blend);
+		}
+
+		if
+			self.gl.depth_test != other.gl.depth_test
+			|| self.gl.depth_write != other.gl.depth_write
+			|| self.gl.stencil_test != other.gl.stencil_test
+			|| self.gl.stencil_write != other.gl.stencil_write
+		{
+			return Some(FlushReason::Depth);
+		}
+
+		if self.prim != other.prim {
+			return Some(FlushReason::Prim);
+		}
+
+		let data1 = self.uniform.data();
+		let data2 = other.uniform.data();
+
+		let textures = |data: &[(&'static str, UniformData)]| {
+			return data
+				.iter()
+				.filter_map(|(_, d)| match d {
+					UniformData::Texture(t) => Some(t.id()),
+					UniformData::CubeTexture(t) => Some(t.id()),
+					_ => None,
+				})
+				.collect::<Vec<glow::Texture>>();
+		};
+
+		if textures(&data1) != textures(&data2) {
+			return Some(FlushReason::Texture);
+		}
+
+		if data1 != data2 {
+			return Some(FlushReason::Uniform);
+		}
+
+		return None;
+
+	}
+
+}
+
+// a draw waiting to be sorted
+struct Deferred<V: VertexLayout, U: UniformLayout> {
+	layer: i32,
+	state: RenderState<V, U>,
+	verts: Vec<V>,
+	indices: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum FlushReason {
+	Texture,
+	Shader,
+	Uniform,
+	Blend,
+	Depth,
+	Prim,
+	Full,
+	Direct,
+	Explicit,
+}
+
+/// How Many Times Batches Were Flushed for Each Reason
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlushStats {
+	/// texture changed
+	pub texture: usize,
+	/// shader changed
+	pub shader: usize,
+	/// other uniforms changed, like transform or color of meshes, or custom uniforms
+	pub uniform: usize,
+	/// blend mode changed
+	pub blend: usize,
+	/// depth or stencil state changed
+	pub depth: usize,
+	/// primitive type changed
+	pub prim: usize,
+	/// vertex or index buffer is full
+	pub full: usize,
+	/// before a draw that doesn't go through the batch, like meshes and instancing
+	pub direct: usize,
+	/// [`Gfx::flush`](struct.Gfx.html#method.flush), end of frame, or scopes like canvases, masks and scissors
+	pub explicit: usize,
+}
+
+impl FlushStats {
+
+	fn add(&mut self, r: FlushReason) {
+		match r {
+			FlushReason::Texture => self.texture += 1,
+			FlushReason::Shader => self.shader += 1,
+			FlushReason::Uniform => self.uniform += 1,
+			FlushReason::Blend => self.blend += 1,
+			FlushReason::Depth => self.depth += 1,
+			FlushReason::Prim => self.prim += 1,
+			FlushReason::Full => self.full += 1,
+			FlushReason::Direct => self.direct += 1,
+			FlushReason::Explicit => self.explicit += 1,
+		}
+	}
+
+	/// sum of all reasons
+	pub fn total(&self) -> usize {
+		return self.texture
+			+ self.shader
+			+ self.uniform
+			+ self.blend
+			+ self.depth
+			+ self.prim
+			+ self.full
+			+ self.direct
+			+ self.explicit;
+	}
+
+}
+
+/// Draw Statistics of a Frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DrawStats {
+	/// total draw calls, batched and direct
+	pub draw_calls: usize,
+	/// draw calls from the batch
+	pub batches: usize,
+	/// vertices sent through the batch
+	pub vertices: usize,
+	/// indices sent through the batch
+	pub indices: usize,
+	/// draws that were reordered by [`Gfx::draw_sorted`](struct.Gfx.html#method.draw_sorted)
+	pub sorted: usize,
+	/// why batches were flushed
+	pub flushes: FlushStats,
 }
 
 pub(super) struct BatchedRenderer<V: VertexLayout, U: UniformLayout + PartialEq + Clone> {
@@ -17,7 +173,11 @@ pub(super) struct BatchedRenderer<V: VertexLayout, U: UniformLayout + PartialEq
 	vqueue: Vec<V>,
 	iqueue: Vec<u32>,
 	cur_state: Option<RenderState<V, U>>,
-	draw_count: usize,
+	// what's currently set on the gl context
+	applied: Option<GLState>,
+	deferred: Option<Vec<Deferred<V, U>>>,
+	layer: i32,
+	stats: DrawStats,
 }
 
 impl<V: VertexLayout, U: UniformLayout + PartialEq + Clone> BatchedRenderer<V, U> {
@@ -34,12 +194,14 @@ impl<V: VertexLayout, U: UniformLayout + PartialEq + Clone> BatchedRenderer<V, U
 			vqueue: Vec::with_capacity(max_vertices),
 			iqueue: Vec::with_capacity(max_indices),
 			cur_state: None,
-			draw_count: 0,
+			applied: None,
+			deferred: None,
+			layer: 0,
+			stats: DrawStats::default(),
 		});
 
 	}
 
-	// TODO: review the logic here
 	pub fn push(
 		&mut self,
 		prim: Primitive,
@@ -47,64 +209,83 @@ impl<V: VertexLayout, U: UniformLayout + PartialEq + Clone> BatchedRenderer<V, U
 		indices: &[u32],
 		pipeline: &Pipeline<V, U>,
 		uniform: &U,
+		gl: &GLState,
 	) -> Result<()> {
 
-		let mut reset = false;
-
-		if let Some(state) = &self.cur_state {
-			if
-				&state.pipeline != pipeline
-				|| &state.uniform != uniform
-				|| state.prim != prim
-			{
-				reset = true;
-			}
-		} else {
-			reset = true;
+		if verts.len() > self.vqueue.capacity() || indices.len() > self.iqueue.capacity() {
+			return Err(format!("too many vertices for one draw"));
 		}
 
-		if reset {
-			self.flush();
-			self.cur_state = Some(RenderState {
-				pipeline: pipeline.clone(),
-				uniform: uniform.clone(),
-				prim,
+		let state = RenderState {
+			pipeline: pipeline.clone(),
+			uniform: uniform.clone(),
+			prim: prim,
+			gl: *gl,
+		};
+
+		if let Some(deferred) = &mut self.deferred {
+			deferred.push(Deferred {
+				layer: self.layer,
+				state: state,
+				verts: verts.to_vec(),
+				indices: indices.to_vec(),
 			});
+			return Ok(());
 		}
 
-		if self.vqueue.len() + verts.len() >= self.vqueue.capacity() {
-			self.flush();
+		self.push_state(state, verts, indices);
+
+		return Ok(());
+
+	}
+
+	fn push_state(&mut self, state: RenderState<V, U>, verts: &[V], indices: &[u32]) {
+
+		let reason = match &self.cur_state {
+			Some(cur) => cur.diff(&state),
+			None => None,
+		};
+
+		if let Some(reason) = reason {
+			self.flush_batch(reason);
 		}
 
-		if self.iqueue.len() + indices.len() >= self.iqueue.capacity() {
-			self.flush();
+		if
+			self.vqueue.len() + verts.len() > self.vqueue.capacity()
+			|| self.iqueue.len() + indices.len() > self.iqueue.capacity()
+		{
+			self.flush_batch(FlushReason::Full);
 		}
 
-		let offset = (self.vqueue.len()) as u32;
+		if reason.is_some() || self.cur_state.is_none() {
+			self.cur_state = Some(state);
+		}
 
-		let indices = indices
-			.iter()
-			.map(|i| *i + offset)
-			.collect::<Vec<u32>>();
+		let offset = self.vqueue.len() as u32;
 
 		self.vqueue.extend_from_slice(&verts);
-		self.iqueue.extend_from_slice(&indices);
+		self.iqueue.extend(indices.iter().map(|i| *i + offset));
 
-		return Ok(());
+	}
 
+	// draw everything queued
+	pub fn flush(&mut self, reason: FlushReason) {
+		self.submit_deferred();
+		self.flush_batch(reason);
 	}
 
-	pub fn flush(&mut self) {
+	fn flush_batch(&mut self, reason: FlushReason) {
 
 		if self.empty() {
 			return;
 		}
 
-		let state = match &self.cur_state {
+		let state = match self.cur_state.take() {
 			Some(s) => s,
 			None => return,
 		};
 
+		self.apply(&state.gl);
 		self.vbuf.data(0, &self.vqueue);
 		self.ibuf.data(0, &self.iqueue);
 
@@ -118,24 +299,180 @@ impl<V: VertexLayout, U: UniformLayout + PartialEq + Clone> BatchedRenderer<V, U
 			&state.uniform,
 		);
 
-		self.cur_state = None;
+		self.stats.draw_calls += 1;
+		self.stats.batches += 1;
+		self.stats.vertices += self.vqueue.len();
+		self.stats.indices += self.iqueue.len();
+		self.stats.flushes.add(reason);
+
+		self.cur_state = Some(state);
 		self.vqueue.clear();
 		self.iqueue.clear();
-		self.draw_count += 1;
 
 	}
 
-	pub fn empty(&self) -> bool {
-		return self.vqueue.is_empty();
+	// flush and set gl state for a draw that doesn't go through the batch
+	pub fn begin_direct(&mut self, gl: &GLState) {
+		self.flush(FlushReason::Direct);
+		self.apply(gl);
+		self.stats.draw_calls += 1;
 	}
 
-	pub fn clear_draw_count(&mut self) {
-		self.draw_count = 0;
+	pub fn apply(&mut self, s: &GLState) {
+
+		if self.applied.as_ref() == Some(s) {
+			return;
+		}
+
+		unsafe {
+
+			self.gl.blend_func_separate(
+				s.blend.rgb_src.as_glow(),
+				s.blend.rgb_dest.as_glow(),
+				s.blend.a_src.as_glow(),
+				s.blend.a_dest.as_glow(),
+			);
+
+			self.gl.depth_mask(s.depth_write);
+
+			if let Some(cmp) = s.depth_test {
+				self.gl.enable(Capability::DepthTest.as_glow());
+				self.gl.depth_func(cmp.as_glow());
+			} else {
+				self.gl.disable(Capability::DepthTest.as_glow());
+			}
+
+		}
+
+		self.applied = Some(*s);
+
+	}
+
+	// start collecting draws to sort
+	pub fn begin_sort(&mut self) -> Result<()> {
+
+		if self.deferred.is_some() {
+			return Err(format!("cannot sort inside a sort"));
+		}
+
+		self.flush(FlushReason::Explicit);
+		self.deferred = Some(vec![]);
+
+		return Ok(());
+
+	}
+
+	pub fn end_sort(&mut self) {
+		self.flush(FlushReason::Explicit);
+		self.deferred = None;
+	}
+
+	pub fn set_layer(&mut self, l: i32) -> i32 {
+		return std::mem::replace(&mut self.layer, l);
+	}
+
+	// sort by layer, then group the same states in the order they first appear, the order of draws with the same state is kept
+	fn submit_deferred(&mut self) {
+
+		let queue = match &mut self.deferred {
+			Some(q) if !q.is_empty() => std::mem::take(q),
+			_ => return,
+		};
+
+		let mut groups: Vec<RenderState<V, U>> = vec![];
+		let mut keyed = Vec::with_capacity(queue.len());
+
+		for (i, d) in queue.into_iter().enumerate() {
+			let group = match groups.iter().position(|s| s == &d.state) {
+				Some(g) => g,
+				None => {
+					groups.push(d.state.clone());
+					groups.len() - 1
+				},
+			};
+			keyed.push(((d.layer, group), i, d));
+		}
+
+		keyed.sort_by_key(|(k, _, _)| *k);
+
+		for (pos, (_, i, d)) in keyed.into_iter().enumerate() {
+			if pos != i {
+				self.stats.sorted += 1;
+			}
+			self.push_state(d.state, &d.verts, &d.indices);
+		}
+
 	}
 
-	pub fn draw_count(&self) -> usize {
-		return self.draw_count;
+	pub fn empty(&self) -> bool {
+		return self.vqueue.is_empty();
+	}
+
+	pub fn take_stats(&mut self) -> DrawStats {
+		return std::mem::take(&mut self.stats);
 	}
 
 }
 
+#[cfg(all(test, not(web)))]
+#[test]
+fn batching() {
+
+	let mut h = test_headless(64, 64);
+
+	let red = Texture::from_raw(h.gfx(), 1, 1, &[255, 0, 0, 255]).unwrap();
+	let blue = Texture::from_raw(h.gfx(), 1, 1, &[0, 0, 255, 255]).unwrap();
+
+	let sprites = |gfx: &mut Gfx| -> Result<()> {
+		for i in 0..8 {
+			let tex = if i % 2 == 0 { &red } else { &blue };
+			gfx.draw_t(
+				mat4!().t2(vec2!(i as f32 * 8.0 - 28.0, 0)).s2(vec2!(6)),
+				&shapes::sprite(tex),
+			)?;
+		}
+		return Ok(());
+	};
+
+	let a = h.render(|gfx| sprites(gfx)).unwrap();
+	let stats = h.gfx().stats();
+
+	assert_eq!(stats.draw_calls, 8);
+	assert_eq!(stats.flushes.texture, 7);
+	assert_eq!(stats.flushes.explicit, 1);
+
+	let b = h.render(|gfx| gfx.draw_sorted(|gfx| sprites(gfx))).unwrap();
+	let stats = h.gfx().stats();
+
+	assert_eq!(stats.draw_calls, 2);
+	assert_eq!(stats.flushes.total(), 2);
+	assert!(a.diff(&b).unwrap() < 0.001);
+
+	// layers are drawn in order even if the state is the same
+	let c = h.render(|gfx| {
+		return gfx.draw_sorted(|gfx| {
+			gfx.use_layer(1, |gfx| {
+				return gfx.draw_t(mat4!().s2(vec2!(16)), &shapes::sprite(&red));
+			})?;
+			gfx.draw_t(mat4!().s2(vec2!(32)), &shapes::sprite(&blue))?;
+			return gfx.use_layer(1, |gfx| {
+				return gfx.draw_t(mat4!().s2(vec2!(8)), &shapes::sprite(&blue));
+			});
+		});
+	}).unwrap();
+
+	assert_eq!(h.gfx().stats().draw_calls, 3);
+	assert_eq!(c.get(32, 32), Some(rgba!(0, 0, 1, 1)));
+	assert_eq!(c.get(32 + 6, 32), Some(rgba!(1, 0, 0, 1)));
+
+	// blend is part of the state
+	h.render(|gfx| {
+		gfx.draw(&shapes::sprite(&red))?;
+		return gfx.use_blend(Blend::Add, |gfx| {
+			return gfx.draw(&shapes::sprite(&red));
+		});
+	}).unwrap();
+
+	assert_eq!(h.gfx().stats().flushes.blend, 1);
+
+}
@@ -3,6 +3,11 @@
 use super::*;
 
 pub(super) fn bind_attrs<V: VertexLayout>(gl: &glow::Context) {
+	bind_attrs_at::<V>(gl, 0);
+}
+
+// bind attributes starting from a location, for when multiple buffers are used in a draw
+pub(super) fn bind_attrs_at<V: VertexLayout>(gl: &glow::Context, start: usize) {
 
 	unsafe {
 
@@ -10,6 +15,8 @@ pub(super) fn bind_attrs<V: VertexLayout>(gl: &glow::Context) {
 
 		for (i, (name, size)) in V::attrs().into_iter().enumerate() {
 
+			let i = start + i;
+
 			gl.vertex_attrib_pointer_f32(
 				i as u32,
 				*size as i32,
@@ -11,6 +11,10 @@ pub trait Camera {
 	// TODO: test implementations of items below
 	/// convert a 2d point to a ray
 	fn pt_to_ray(&self, ctx: &Gfx, pt: Vec2) -> Ray3;
+	/// get the view frustum in world space
+	fn frustum(&self) -> Frustum {
+		return Frustum::from_mat(self.proj() * self.view());
+	}
 	/// convert a point into 2d screen space
 	fn to_screen(&self, ctx: &Gfx, pt: Vec3) -> Vec2 {
 		let cp = self.proj() * self.view() * vec4!(pt.x, pt.y, pt.z, 1.0);
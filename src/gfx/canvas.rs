@@ -2,17 +2,29 @@
 
 use super::*;
 
+/// Max Number of Color Targets in a [`Canvas`](struct.Canvas.html), the least every gles 3 / webgl 2 driver supports
+pub const MAX_CANVAS_TARGETS: usize = 4;
+
 /// Off-screen Rendering Canvas
 #[derive(Clone)]
 pub struct Canvas {
 	gl: Rc<glow::Context>,
 	gl_fbuf: Rc<FramebufferHandle>,
-	color_tex: Texture,
+	color_texs: Vec<Texture>,
 	depth_stencil_tex: Texture,
+	msaa: Option<Rc<Multisample>>,
 	width: i32,
 	height: i32,
 }
 
+// multisampled buffers that are drawn to, resolved to the textures when drawing ends
+struct Multisample {
+	gl_fbuf: FramebufferHandle,
+	_color_bufs: Vec<RenderbufferHandle>,
+	_depth_stencil_buf: RenderbufferHandle,
+	samples: i32,
+}
+
 impl Canvas {
 
 	/// create a new canvas with default conf
@@ -22,6 +34,46 @@ impl Canvas {
 
 	/// create a new canvas
 	pub fn new_with_conf(ctx: &Gfx, w: i32, h: i32, conf: TextureConf) -> Result<Self> {
+		return Self::with_targets(ctx, w, h, &[conf]);
+	}
+
+	/// create a canvas with multiple color targets, written with `frag_data[i]` from shaders created with [`Shader::from_frag_mrt`](struct.Shader.html#method.from_frag_mrt)
+	/// ```ignore
+	/// // g-buffer with color, normal and position
+	/// let gbuf = Canvas::with_targets(d.gfx, w, h, &[
+	///     TextureConf::default(),
+	///     TextureConf { format: TextureFormat::RGBA16F, ..TextureConf::default() },
+	///     TextureConf { format: TextureFormat::RGBA32F, ..TextureConf::default() },
+	/// ])?;
+	/// ```
+	pub fn with_targets(ctx: &Gfx, w: i32, h: i32, targets: &[TextureConf]) -> Result<Self> {
+		return Self::multisampled(ctx, w, h, 0, targets);
+	}
+
+	/// create a canvas with multisample antialiasing
+	///
+	/// drawing goes to multisampled buffers, which are resolved to the canvas textures when [`draw_on`](struct.Gfx.html#method.draw_on) ends, samples are capped to what the driver supports, 0 means no multisampling
+	pub fn multisampled(ctx: &Gfx, w: i32, h: i32, samples: u8, targets: &[TextureConf]) -> Result<Self> {
+
+		if targets.is_empty() {
+			return Err(format!("canvas needs at least 1 color target"));
+		}
+
+		if targets.iter().any(|t| t.format.is_depth()) {
+			return Err(format!("canvas color target cannot be a depth format"));
+		}
+
+		if targets.len() > MAX_CANVAS_TARGETS {
+			return Err(format!("canvas cannot have more than {} color targets", MAX_CANVAS_TARGETS));
+		}
+
+		if !ctx.caps.glsl3 && targets.iter().any(|t| t.format.needs_gl3()) {
+			return Err(format!("canvas target format needs gl 3 / gles 3"));
+		}
+
+		if samples > 1 && targets.iter().any(|t| t.format.is_int()) {
+			return Err(format!("integer canvas targets cannot be multisampled"));
+		}
 
 		let dpi = ctx.dpi();
 		let tw = (w as f32 * dpi) as i32;
@@ -32,19 +84,31 @@ impl Canvas {
 			let gl = ctx.gl().clone();
 			let gl_fbuf = FramebufferHandle::new(&gl)?;
 
-			let pixels = vec![0.0 as u8; (tw * th * 4) as usize];
-			let color_tex = Texture::from_raw_with_conf(ctx, tw, th, &pixels, conf)?;
+			let color_texs = targets
+				.iter()
+				.map(|conf| Texture::new_with_conf(ctx, tw, th, *conf))
+				.collect::<Result<Vec<Texture>>>()?;
+
 			let depth_stencil_tex = Texture::new_depth_stencil(ctx, tw, th)?;
 
 			gl.bind_framebuffer(glow::FRAMEBUFFER, Some(gl_fbuf.id()));
 
-			gl.framebuffer_texture_2d(
-				glow::FRAMEBUFFER,
-				glow::COLOR_ATTACHMENT0,
-				glow::TEXTURE_2D,
-				Some(color_tex.id()),
-				0,
-			);
+			for (i, tex) in color_texs.iter().enumerate() {
+				gl.framebuffer_texture_2d(
+					glow::FRAMEBUFFER,
+					glow::COLOR_ATTACHMENT0 + i as u32,
+					glow::TEXTURE_2D,
+					Some(tex.id()),
+					0,
+				);
+			}
+
+			if color_texs.len() > 1 {
+				let bufs = (0..color_texs.len())
+					.map(|i| glow::COLOR_ATTACHMENT0 + i as u32)
+					.collect::<Vec<u32>>();
+				gl.draw_buffers(&bufs);
+			}
 
 			gl.framebuffer_texture_2d(
 				glow::FRAMEBUFFER,
@@ -57,6 +121,7 @@ impl Canvas {
 			gl.clear(Surface::Color.as_glow());
 			gl.clear(Surface::Depth.as_glow());
 			gl.clear(Surface::Stencil.as_glow());
+			clear_int_targets(&gl, &color_texs);
 
 			if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
 				return Err(format!("failed to create framebuffer"));
@@ -64,11 +129,77 @@ impl Canvas {
 
 			gl.bind_framebuffer(glow::FRAMEBUFFER, None);
 
+			// GL_MAX_SAMPLES is not in gl 2 / gles 2
+			let max_samples = if samples > 1 && ctx.caps.glsl3 {
+				gl.get_parameter_i32(glow::MAX_SAMPLES)
+			} else {
+				0
+			};
+
+			let samples = (samples as i32).min(max_samples);
+
+			let msaa = if samples > 1 {
+				Some(Rc::new(Multisample::new(&gl, tw, th, samples, targets)?))
+			} else {
+				None
+			};
+
 			return Ok(Self {
 				gl: gl,
 				gl_fbuf: Rc::new(gl_fbuf),
-				color_tex: color_tex,
+				color_texs: color_texs,
 				depth_stencil_tex: depth_stencil_tex,
+				msaa: msaa,
+				width: w,
+				height: h,
+			});
+
+		}
+
+	}
+
+	// depth only target without color, size is in pixels, for shadow maps
+	pub(super) fn depth_only(ctx: &Gfx, w: i32, h: i32) -> Result<Self> {
+
+		unsafe {
+
+			let gl = ctx.gl().clone();
+			let gl_fbuf = FramebufferHandle::new(&gl)?;
+
+			let depth_tex = Texture::new_with_conf(ctx, w, h, TextureConf {
+				format: TextureFormat::Depth,
+				wrap_x: WrapMode::ClampToEdge,
+				wrap_y: WrapMode::ClampToEdge,
+				..TextureConf::default()
+			})?;
+
+			gl.bind_framebuffer(glow::FRAMEBUFFER, Some(gl_fbuf.id()));
+
+			gl.framebuffer_texture_2d(
+				glow::FRAMEBUFFER,
+				glow::DEPTH_ATTACHMENT,
+				glow::TEXTURE_2D,
+				Some(depth_tex.id()),
+				0,
+			);
+
+			gl.draw_buffers(&[glow::NONE]);
+			gl.read_buffer(glow::NONE);
+			gl.clear(Surface::Depth.as_glow());
+
+			if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+				gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+				return Err(format!("failed to create depth framebuffer"));
+			}
+
+			gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+			return Ok(Self {
+				gl: gl,
+				gl_fbuf: Rc::new(gl_fbuf),
+				color_texs: vec![],
+				depth_stencil_tex: depth_tex,
+				msaa: None,
 				width: w,
 				height: h,
 			});
@@ -79,16 +210,79 @@ impl Canvas {
 
 	pub(super) fn bind(&self) {
 		unsafe {
-			self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.gl_fbuf.id()));
+			let fbuf = match &self.msaa {
+				Some(msaa) => msaa.gl_fbuf.id(),
+				None => self.gl_fbuf.id(),
+			};
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbuf));
 		}
 	}
 
 	pub(super) fn unbind(&self) {
+		self.resolve();
 		unsafe {
 			self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
 		}
 	}
 
+	// blit the multisampled buffers to the textures
+	fn resolve(&self) {
+
+		let msaa = match &self.msaa {
+			Some(msaa) => msaa,
+			None => return,
+		};
+
+		let (w, h) = (self.color_tex().width(), self.color_tex().height());
+		let count = self.color_texs.len();
+
+		unsafe {
+
+			self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(msaa.gl_fbuf.id()));
+			self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(self.gl_fbuf.id()));
+
+			for i in 0..count {
+
+				let attachment = glow::COLOR_ATTACHMENT0 + i as u32;
+
+				self.gl.read_buffer(attachment);
+
+				// gles 3 / webgl 2 require draw buffer i to be at index i
+				if count > 1 {
+					let mut bufs = vec![glow::NONE; i];
+					bufs.push(attachment);
+					self.gl.draw_buffers(&bufs);
+				}
+
+				let mask = if i == 0 {
+					glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT
+				} else {
+					glow::COLOR_BUFFER_BIT
+				};
+
+				self.gl.blit_framebuffer(0, 0, w, h, 0, 0, w, h, mask, glow::NEAREST);
+
+			}
+
+			if count > 1 {
+				let bufs = (0..count)
+					.map(|i| glow::COLOR_ATTACHMENT0 + i as u32)
+					.collect::<Vec<u32>>();
+				self.gl.draw_buffers(&bufs);
+			}
+
+			self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+			self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+
+		}
+
+	}
+
+	/// number of samples, 0 if not multisampled
+	pub fn samples(&self) -> i32 {
+		return self.msaa.as_ref().map(|m| m.samples).unwrap_or(0);
+	}
+
 	/// get canvas width
 	pub fn width(&self) -> i32 {
 		return self.width;
@@ -101,7 +295,17 @@ impl Canvas {
 
 	/// get canvas texture
 	pub fn color_tex(&self) -> &Texture {
-		return &self.color_tex;
+		return &self.color_texs[0];
+	}
+
+	/// get the texture of a color target
+	pub fn target(&self, i: usize) -> Option<&Texture> {
+		return self.color_texs.get(i);
+	}
+
+	/// get all color target textures
+	pub fn targets(&self) -> &[Texture] {
+		return &self.color_texs;
 	}
 
 	/// get canvas texture
@@ -111,7 +315,7 @@ impl Canvas {
 
 	/// capture content to an [`Image`](../img/struct.Image.html)
 	pub fn capture(&self) -> Result<img::Image> {
-		return Ok(self.color_tex.capture()?.flip_v());
+		return Ok(self.color_tex().capture()?.flip_v());
 	}
 
 	/// get position of a canvas [`Origin`](struct.Origin.html)
@@ -119,24 +323,149 @@ impl Canvas {
 		return orig.as_pt() / 2.0 * vec2!(self.width, self.height);
 	}
 
-	// TODO: support picking natively
-	/// read a pixel value
+	/// read a pixel value, for picking objects see [`Picker`](struct.Picker.html)
 	pub fn read(&self, pos: Vec2) -> Color {
+		let data = self.read_pixels(pos.x as i32, pos.y as i32, 1, 1);
+		return Color::from_u8(data[0], data[1], data[2], data[3]);
+	}
+
+	// read rgba8 pixels of the first target, from bottom left
+	pub(super) fn read_pixels(&self, x: i32, y: i32, w: i32, h: i32) -> Vec<u8> {
+
+		let mut data = vec![0; w as usize * h as usize * 4];
+
 		unsafe {
-			self.bind();
-			let mut data = [0; 4];
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.gl_fbuf.id()));
 			self.gl.read_pixels(
-				pos.x as i32,
-				pos.y as i32,
-				1,
-				1,
+				x,
+				y,
+				w,
+				h,
 				glow::RGBA,
 				glow::UNSIGNED_BYTE,
 				glow::PixelPackData::Slice(&mut data),
 			);
-			self.unbind();
-			return Color::from_u8(data[0], data[1], data[2], data[3]);
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+		}
+
+		return data;
+
+	}
+
+	// read the first target of an integer canvas, from bottom left
+	pub(super) fn read_pixels_u32(&self, x: i32, y: i32, w: i32, h: i32) -> Vec<u32> {
+
+		// RGBA_INTEGER is the only integer format gles 3 guarantees for reading
+		let mut data = vec![0; w as usize * h as usize * 16];
+
+		unsafe {
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.gl_fbuf.id()));
+			self.gl.read_pixels(
+				x,
+				y,
+				w,
+				h,
+				glow::RGBA_INTEGER,
+				glow::UNSIGNED_INT,
+				glow::PixelPackData::Slice(&mut data),
+			);
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+		}
+
+		return data
+			.chunks(16)
+			.map(|px| u32::from_ne_bytes([px[0], px[1], px[2], px[3]]))
+			.collect();
+
+	}
+
+	// clear the color targets of the bound canvas
+	pub(super) fn clear_color(&self) {
+		unsafe {
+			self.gl.clear(Surface::Color.as_glow());
+			clear_int_targets(&self.gl, &self.color_texs);
+		}
+	}
+
+}
+
+// glClear leaves integer targets undefined
+unsafe fn clear_int_targets(gl: &glow::Context, texs: &[Texture]) {
+	for (i, tex) in texs.iter().enumerate() {
+		if tex.format().is_int() {
+			gl.clear_buffer_u32_slice(glow::COLOR, i as u32, &mut [0; 4]);
+		}
+	}
+}
+
+impl Multisample {
+
+	fn new(gl: &Rc<glow::Context>, w: i32, h: i32, samples: i32, targets: &[TextureConf]) -> Result<Self> {
+
+		unsafe {
+
+			let gl_fbuf = FramebufferHandle::new(gl)?;
+
+			gl.bind_framebuffer(glow::FRAMEBUFFER, Some(gl_fbuf.id()));
+
+			let storage = |format: u32| -> Result<RenderbufferHandle> {
+				let buf = RenderbufferHandle::new(gl)?;
+				gl.bind_renderbuffer(glow::RENDERBUFFER, Some(buf.id()));
+				gl.renderbuffer_storage_multisample(glow::RENDERBUFFER, samples, format, w, h);
+				gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+				return Ok(buf);
+			};
+
+			let mut color_bufs = vec![];
+
+			for (i, conf) in targets.iter().enumerate() {
+				let (internal, _, _) = conf.format.as_glow();
+				let buf = storage(internal as u32)?;
+				gl.framebuffer_renderbuffer(
+					glow::FRAMEBUFFER,
+					glow::COLOR_ATTACHMENT0 + i as u32,
+					glow::RENDERBUFFER,
+					Some(buf.id()),
+				);
+				color_bufs.push(buf);
+			}
+
+			let depth_stencil_buf = storage(glow::DEPTH24_STENCIL8)?;
+
+			gl.framebuffer_renderbuffer(
+				glow::FRAMEBUFFER,
+				glow::DEPTH_STENCIL_ATTACHMENT,
+				glow::RENDERBUFFER,
+				Some(depth_stencil_buf.id()),
+			);
+
+			if color_bufs.len() > 1 {
+				let bufs = (0..color_bufs.len())
+					.map(|i| glow::COLOR_ATTACHMENT0 + i as u32)
+					.collect::<Vec<u32>>();
+				gl.draw_buffers(&bufs);
+			}
+
+			gl.clear(Surface::Color.as_glow());
+			gl.clear(Surface::Depth.as_glow());
+			gl.clear(Surface::Stencil.as_glow());
+
+			if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+				gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+				return Err(format!("failed to create multisampled framebuffer"));
+			}
+
+			gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+			return Ok(Self {
+				gl_fbuf: gl_fbuf,
+				_color_bufs: color_bufs,
+				_depth_stencil_buf: depth_stencil_buf,
+				samples: samples,
+			});
+
 		}
+
 	}
 
 }
@@ -147,3 +476,113 @@ impl PartialEq for Canvas {
 	}
 }
 
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn targets() {
+
+	let mut h = test_headless(8, 8);
+
+	let gfx = h.gfx();
+
+	let canvas = Canvas::with_targets(gfx, 8, 8, &[
+		TextureConf::default(),
+		TextureConf {
+			format: TextureFormat::RGBA16F,
+			..TextureConf::default()
+		},
+		TextureConf {
+			format: TextureFormat::R8,
+			..TextureConf::default()
+		},
+	]).unwrap();
+
+	let shader = Shader::<()>::from_frag_mrt(gfx, "
+		void frag() {
+			frag_data[0] = default_color();
+			frag_data[1] = vec4(4.0, 2.0, 0.5, 1.0);
+			frag_data[2] = vec4(0.5, 0.0, 0.0, 1.0);
+		}
+	").unwrap();
+
+	gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+		return gfx.draw_with(&shader, &(), |gfx| {
+			return gfx.draw(&shapes::rect(vec2!(-4), vec2!(4)).fill(rgba!(1, 0, 0, 1)));
+		});
+	}).unwrap();
+
+	assert_eq!(canvas.targets().len(), 3);
+	assert_eq!(canvas.capture().unwrap().get(4, 4), Some(rgba!(1, 0, 0, 1)));
+
+	let hdr = canvas.target(1).unwrap().capture_f32().unwrap();
+
+	assert_eq!(&hdr[0..4], &[4.0, 2.0, 0.5, 1.0]);
+
+	let r = canvas.target(2).unwrap().capture_f32().unwrap();
+
+	assert!((r[0] - 0.5).abs() < 0.01);
+
+	assert!(Canvas::with_targets(gfx, 8, 8, &[]).is_err());
+	assert!(Canvas::with_targets(gfx, 8, 8, &[TextureConf::default(); MAX_CANVAS_TARGETS + 1]).is_err());
+
+	// without gl 3 only RGBA8 targets can be rendered to
+	gfx.caps.glsl3 = false;
+	let r8 = Canvas::with_targets(gfx, 8, 8, &[TextureConf::default(), TextureConf {
+		format: TextureFormat::R8,
+		..TextureConf::default()
+	}]);
+	gfx.caps.glsl3 = true;
+	assert!(r8.is_err());
+
+	let data = [1.5, -2.0, 0.0, 1.0];
+	let tex = Texture::from_f32_with_conf(gfx, 1, 1, &data, TextureConf {
+		format: TextureFormat::RGBA32F,
+		..TextureConf::default()
+	}).unwrap();
+
+	assert_eq!(tex.capture_f32().unwrap(), data.to_vec());
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn msaa() {
+
+	let mut h = test_headless(32, 32);
+
+	let gfx = h.gfx();
+
+	// count pixels that are partially covered
+	let edges = |canvas: &Canvas, gfx: &mut Gfx| {
+
+		gfx.draw_on(canvas, CanvasAction::clear(), |gfx| {
+			return gfx.draw(&shapes::circle(vec2!(0), 12.0).fill(rgba!(1)));
+		}).unwrap();
+
+		let img = canvas.capture().unwrap();
+		let mut count = 0;
+
+		for x in 0..img.width() {
+			for y in 0..img.height() {
+				let c = img.get(x, y).unwrap();
+				if c.r > 0.05 && c.r < 0.95 {
+					count += 1;
+				}
+			}
+		}
+
+		return count;
+
+	};
+
+	let canvas = Canvas::new(gfx, 32, 32).unwrap();
+	let msaa_canvas = Canvas::multisampled(gfx, 32, 32, 4, &[TextureConf::default()]).unwrap();
+
+	assert_eq!(canvas.samples(), 0);
+	// the headless context is gl 3+
+	assert!(msaa_canvas.samples() > 0);
+
+	assert_eq!(edges(&canvas, gfx), 0);
+	assert!(edges(&msaa_canvas, gfx) > 0);
+
+}
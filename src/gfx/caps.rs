@@ -0,0 +1,116 @@
+// wengwengweng
+
+use super::*;
+
+// driver capabilities, queried once when the context is created
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Caps {
+	// gl 3 / gles 3 / webgl 2, for integer textures & glsl 3 shaders
+	pub glsl3: bool,
+	pub es: bool,
+	// gl 3.3 / gles 3 / webgl 2, for vertex attrib divisors & instanced draws
+	pub instancing: bool,
+	// GL_EXT_draw_buffers, for gl_FragData in gles 2 shaders
+	pub ext_draw_buffers: bool,
+	// 1.0 if anisotropic filtering is not available
+	pub max_anisotropy: f32,
+}
+
+impl Caps {
+
+	pub unsafe fn query(gl: &glow::Context) -> Self {
+
+		let version = gl.get_parameter_string(glow::VERSION);
+		let (es, major, minor) = parse_version(&version);
+
+		return Self {
+			glsl3: major >= 3,
+			es: es,
+			instancing: if es { major >= 3 } else { (major, minor) >= (3, 3) },
+			ext_draw_buffers: es && has_ext(gl, major, "GL_EXT_draw_buffers"),
+			max_anisotropy: max_anisotropy(gl, major),
+		};
+
+	}
+
+	// header that makes the glsl 1 templates compile as glsl 3
+	pub fn glsl3_header(&self, stage: ShaderType) -> &'static str {
+		return match (self.es, stage) {
+			(true, ShaderType::Vertex) => "#version 300 es\n#define attribute in\n#define varying out\n#define texture2D texture\n",
+			(true, ShaderType::Fragment) => "#version 300 es\nprecision mediump float;\nprecision highp int;\n#define varying in\n#define texture2D texture\n",
+			(false, ShaderType::Vertex) => "#version 130\n#define attribute in\n#define varying out\n#define texture2D texture\n",
+			(false, ShaderType::Fragment) => "#version 130\n#define varying in\n#define texture2D texture\n",
+		};
+	}
+
+}
+
+// glow enables the webgl extensions it knows when it wraps the context, so only the limit needs a query
+#[cfg(web)]
+unsafe fn max_anisotropy(gl: &glow::Context, _: i32) -> f32 {
+	// 0 when EXT_texture_filter_anisotropic is not available
+	return (gl.get_parameter_i32(glow::MAX_TEXTURE_MAX_ANISOTROPY) as f32).max(1.0);
+}
+
+#[cfg(not(web))]
+unsafe fn max_anisotropy(gl: &glow::Context, major: i32) -> f32 {
+
+	if !has_ext(gl, major, "_texture_filter_anisotropic") {
+		return 1.0;
+	}
+
+	return (gl.get_parameter_i32(glow::MAX_TEXTURE_MAX_ANISOTROPY) as f32).max(1.0);
+
+}
+
+// the web backend is webgl 2, glow doesn't support draw buffers on webgl 1
+#[cfg(web)]
+unsafe fn has_ext(_: &glow::Context, _: i32, _: &str) -> bool {
+	return false;
+}
+
+// if any extension name ends with suffix, to match all vendor prefixes
+#[cfg(not(web))]
+unsafe fn has_ext(gl: &glow::Context, major: i32, suffix: &str) -> bool {
+
+	let is_ext = |e: &str| e.ends_with(suffix);
+
+	// gl 2 & gles 2 only have the extension string
+	if major >= 3 {
+		let count = gl.get_parameter_i32(glow::NUM_EXTENSIONS).max(0) as u32;
+		return (0..count).any(|i| is_ext(&gl.get_parameter_indexed_string(glow::EXTENSIONS, i)));
+	} else {
+		return gl.get_parameter_string(glow::EXTENSIONS).split_whitespace().any(is_ext);
+	}
+
+}
+
+// (is es, major version, minor version) from the GL_VERSION string, webgl 2 is gles 3
+fn parse_version(s: &str) -> (bool, i32, i32) {
+
+	let es = s.starts_with("OpenGL ES") || s.starts_with("WebGL");
+	let mut nums = s
+		.split(|c: char| !c.is_ascii_digit())
+		.filter(|n| !n.is_empty())
+		.map(|n| n.parse::<i32>().unwrap_or(0));
+	let major = nums.next().unwrap_or(0);
+	let minor = nums.next().unwrap_or(0);
+
+	if s.starts_with("WebGL") {
+		return (es, major + 1, 0);
+	}
+
+	return (es, major, minor);
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn version() {
+	assert_eq!(parse_version("4.6 (Compatibility Profile) Mesa 23.2.1"), (false, 4, 6));
+	assert_eq!(parse_version("3.1 Mesa 23.2.1"), (false, 3, 1));
+	assert_eq!(parse_version("2.1 INTEL-14.7.8"), (false, 2, 1));
+	assert_eq!(parse_version("OpenGL ES 3.2 Mesa 23.2.1"), (true, 3, 2));
+	assert_eq!(parse_version("WebGL 2.0 (OpenGL ES 3.0 Chromium)"), (true, 3, 0));
+	assert_eq!(parse_version(""), (false, 0, 0));
+}
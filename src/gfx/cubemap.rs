@@ -0,0 +1,448 @@
+// wengwengweng
+
+use std::f32::consts::PI;
+
+use super::*;
+
+/// Cube Map Faces in Upload Order
+pub const CUBE_FACES: [CubeFace; 6] = [
+	CubeFace::PosX,
+	CubeFace::NegX,
+	CubeFace::PosY,
+	CubeFace::NegY,
+	CubeFace::PosZ,
+	CubeFace::NegZ,
+];
+
+impl CubeFace {
+
+	/// world direction through a point on the face, u & v from -1 to 1 with v going down
+	pub fn dir(&self, u: f32, v: f32) -> Vec3 {
+		return match self {
+			CubeFace::PosX => vec3!(1, -v, -u),
+			CubeFace::NegX => vec3!(-1, -v, u),
+			CubeFace::PosY => vec3!(u, 1, v),
+			CubeFace::NegY => vec3!(u, -1, -v),
+			CubeFace::PosZ => vec3!(u, -v, 1),
+			CubeFace::NegZ => vec3!(-u, -v, -1),
+		}.unit();
+	}
+
+}
+
+/// Cube Map Texture, for Skyboxes & Environment Maps
+#[derive(Clone)]
+pub struct CubeTexture {
+	gl_tex: Rc<TextureHandle>,
+	gl: Rc<glow::Context>,
+	size: i32,
+	format: TextureFormat,
+}
+
+impl CubeTexture {
+
+	/// create a new empty cube texture with default conf
+	pub fn new(ctx: &impl GLCtx, size: i32) -> Result<Self> {
+		return Self::new_with_conf(ctx, size, TextureConf::default());
+	}
+
+	/// create a new empty cube texture, wrap modes are always clamped to edge to hide the seams
+	pub fn new_with_conf(ctx: &impl GLCtx, size: i32, conf: TextureConf) -> Result<Self> {
+
+		if conf.format.is_depth() {
+			return Err(format!("depth cube textures are not supported"));
+		}
+
+		unsafe {
+
+			let gl = ctx.gl().clone();
+			let gl_tex = TextureHandle::new(&gl)?;
+			let (internal, format, ty) = conf.format.as_glow();
+
+			gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(gl_tex.id()));
+
+			for face in &CUBE_FACES {
+				gl.tex_image_2d(
+					face.as_glow(),
+					0,
+					internal,
+					size,
+					size,
+					0,
+					format,
+					ty,
+					None,
+				);
+			}
+
+			let wrap = WrapMode::ClampToEdge.as_glow();
+
+			conf.apply_filter(&gl, glow::TEXTURE_CUBE_MAP, ctx.max_anisotropy());
+			gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, glow::TEXTURE_WRAP_S, wrap);
+			gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, glow::TEXTURE_WRAP_T, wrap);
+			gl.tex_parameter_i32(glow::TEXTURE_CUBE_MAP, glow::TEXTURE_WRAP_R, wrap);
+
+			if conf.mipmap_filter.is_some() {
+				gl.generate_mipmap(glow::TEXTURE_CUBE_MAP);
+			}
+
+			gl.bind_texture(glow::TEXTURE_CUBE_MAP, None);
+
+			return Ok(Self {
+				gl_tex: Rc::new(gl_tex),
+				gl: gl,
+				size: size,
+				format: conf.format,
+			});
+
+		}
+
+	}
+
+	/// create from 6 square images in the order of [`CUBE_FACES`](constant.CUBE_FACES.html): +x, -x, +y, -y, +z, -z
+	pub fn from_imgs_with_conf(ctx: &impl GLCtx, imgs: [img::Image; 6], conf: TextureConf) -> Result<Self> {
+
+		let size = imgs[0].width();
+
+		for img in &imgs {
+			if img.width() != size || img.height() != size {
+				return Err(format!("cube faces should be square and of the same size"));
+			}
+		}
+
+		if conf.format != TextureFormat::RGBA8 {
+			return Err(format!("images can only be loaded to {:?} cube textures", TextureFormat::RGBA8));
+		}
+
+		let tex = Self::new_with_conf(ctx, size, conf)?;
+
+		for (face, img) in CUBE_FACES.iter().zip(imgs.iter()) {
+			tex.face_data(*face, img.as_raw());
+		}
+
+		if conf.mipmap_filter.is_some() {
+			tex.gen_mipmaps();
+		}
+
+		return Ok(tex);
+
+	}
+
+	/// create from 6 square images in the order of [`CUBE_FACES`](constant.CUBE_FACES.html): +x, -x, +y, -y, +z, -z
+	pub fn from_imgs(ctx: &impl GLCtx, imgs: [img::Image; 6]) -> Result<Self> {
+		return Self::from_imgs_with_conf(ctx, imgs, TextureConf::default());
+	}
+
+	/// create from bytes of 6 image files
+	pub fn from_bytes(ctx: &impl GLCtx, data: [&[u8]; 6]) -> Result<Self> {
+		return Self::from_imgs(ctx, [
+			img::Image::from_bytes(data[0])?,
+			img::Image::from_bytes(data[1])?,
+			img::Image::from_bytes(data[2])?,
+			img::Image::from_bytes(data[3])?,
+			img::Image::from_bytes(data[4])?,
+			img::Image::from_bytes(data[5])?,
+		]);
+	}
+
+	/// create from an equirectangular panorama, -z is at the center of the image
+	pub fn from_equirect_with_conf(ctx: &impl GLCtx, img: &img::Image, size: i32, conf: TextureConf) -> Result<Self> {
+
+		let face = |f: CubeFace| -> Result<img::Image> {
+
+			let mut out = img::Image::new(size, size);
+
+			for y in 0..size {
+				for x in 0..size {
+					let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+					let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+					out.set(x, y, sample_equirect(img, f.dir(u, v)))?;
+				}
+			}
+
+			return Ok(out);
+
+		};
+
+		return Self::from_imgs_with_conf(ctx, [
+			face(CubeFace::PosX)?,
+			face(CubeFace::NegX)?,
+			face(CubeFace::PosY)?,
+			face(CubeFace::NegY)?,
+			face(CubeFace::PosZ)?,
+			face(CubeFace::NegZ)?,
+		], conf);
+
+	}
+
+	/// create from an equirectangular panorama, -z is at the center of the image
+	pub fn from_equirect(ctx: &impl GLCtx, img: &img::Image, size: i32) -> Result<Self> {
+		return Self::from_equirect_with_conf(ctx, img, size, TextureConf {
+			filter: FilterMode::Linear,
+			..TextureConf::default()
+		});
+	}
+
+	/// create from bytes of an equirectangular panorama image file, face size is a quarter of the width
+	pub fn from_equirect_bytes(ctx: &impl GLCtx, data: &[u8]) -> Result<Self> {
+		let img = img::Image::from_bytes(data)?;
+		return Self::from_equirect(ctx, &img, (img.width() / 4).max(1));
+	}
+
+	/// update a face with raw data in the texture format, call [`gen_mipmaps`](#method.gen_mipmaps) after updating if mipmaps are used
+	pub fn face_data(&self, face: CubeFace, data: &[u8]) {
+
+		let (_, format, ty) = self.format.as_glow();
+
+		unsafe {
+
+			self.bind();
+			self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+
+			self.gl.tex_sub_image_2d(
+				face.as_glow(),
+				0,
+				0,
+				0,
+				self.size,
+				self.size,
+				format,
+				ty,
+				glow::PixelUnpackData::Slice(data),
+			);
+
+			self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+			self.unbind();
+
+		}
+
+	}
+
+	/// regenerate mipmaps from the base level of each face
+	pub fn gen_mipmaps(&self) {
+		unsafe {
+			self.bind();
+			self.gl.generate_mipmap(glow::TEXTURE_CUBE_MAP);
+			self.unbind();
+		}
+	}
+
+	/// capture a face to an [`Image`](../img/struct.Image.html), float values are clamped to 0.0 - 1.0
+	pub fn capture(&self, face: CubeFace) -> Result<img::Image> {
+
+		if self.format.is_int() {
+			return Err(format!("cannot capture integer cube texture to image"));
+		}
+
+		let float = self.format.is_float();
+		let size = (self.size * self.size * 4) as usize;
+		let mut data = vec![0; if float { size * 4 } else { size }];
+
+		unsafe {
+
+			// gles & webgl can't get_tex_image, read the face through a framebuffer instead
+			let fbuf = FramebufferHandle::new(&self.gl)?;
+
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbuf.id()));
+			self.gl.framebuffer_texture_2d(
+				glow::FRAMEBUFFER,
+				glow::COLOR_ATTACHMENT0,
+				face.as_glow(),
+				Some(self.gl_tex.id()),
+				0,
+			);
+
+			let complete = self.gl.check_framebuffer_status(glow::FRAMEBUFFER) == glow::FRAMEBUFFER_COMPLETE;
+
+			if complete {
+				self.gl.read_pixels(
+					0,
+					0,
+					self.size,
+					self.size,
+					glow::RGBA,
+					// float targets can only be read as floats on gles
+					if float { glow::FLOAT } else { glow::UNSIGNED_BYTE },
+					glow::PixelPackData::Slice(&mut data),
+				);
+			}
+
+			self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+			if !complete {
+				return Err(format!("cannot read {:?} cube texture", self.format));
+			}
+
+		}
+
+		let pixels = if float {
+			data
+				.chunks(4)
+				.map(|b| (f32::from_ne_bytes([b[0], b[1], b[2], b[3]]).max(0.0).min(1.0) * 255.0).round() as u8)
+				.collect()
+		} else {
+			data
+		};
+
+		return img::Image::from_raw(self.size, self.size, pixels);
+
+	}
+
+	pub(super) fn bind(&self) {
+		unsafe {
+			self.gl.bind_texture(glow::TEXTURE_CUBE_MAP, Some(self.gl_tex.id()));
+		}
+	}
+
+	pub(super) fn unbind(&self) {
+		unsafe {
+			self.gl.bind_texture(glow::TEXTURE_CUBE_MAP, None);
+		}
+	}
+
+	/// get width & height of each face
+	pub fn size(&self) -> i32 {
+		return self.size;
+	}
+
+	/// get pixel format
+	pub fn format(&self) -> TextureFormat {
+		return self.format;
+	}
+
+	pub(super) fn id(&self) -> glow::Texture {
+		return self.gl_tex.id();
+	}
+
+}
+
+impl PartialEq for CubeTexture {
+	fn eq(&self, other: &Self) -> bool {
+		return self.gl_tex == other.gl_tex;
+	}
+}
+
+// bilinear sample of a panorama in a direction
+fn sample_equirect(img: &img::Image, dir: Vec3) -> Color {
+
+	let w = img.width();
+	let h = img.height();
+	let lon = f32::atan2(dir.x, -dir.z);
+	let lat = dir.y.max(-1.0).min(1.0).asin();
+	let x = (lon / (2.0 * PI) + 0.5) * w as f32 - 0.5;
+	let y = (0.5 - lat / PI) * h as f32 - 0.5;
+	let (x0, y0) = (x.floor(), y.floor());
+	let (fx, fy) = (x - x0, y - y0);
+
+	// wraps around horizontally
+	let get = |x: i32, y: i32| {
+		return img
+			.get(x.rem_euclid(w), y.max(0).min(h - 1))
+			.unwrap_or(rgba!(0));
+	};
+
+	let (x0, y0) = (x0 as i32, y0 as i32);
+	let top = get(x0, y0).lerp(get(x0 + 1, y0), fx);
+	let bottom = get(x0, y0 + 1).lerp(get(x0 + 1, y0 + 1), fx);
+
+	return top.lerp(bottom, fy);
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn cubemap() {
+
+	let mut h = test_headless(16, 16);
+
+	let gfx = h.gfx();
+	let colors = [
+		rgba!(1, 0, 0, 1),
+		rgba!(0, 1, 1, 1),
+		rgba!(0, 1, 0, 1),
+		rgba!(1, 0, 1, 1),
+		rgba!(0, 0, 1, 1),
+		rgba!(1, 1, 0, 1),
+	];
+
+	let solid = |c: Color| {
+		let mut img = img::Image::new(4, 4);
+		for x in 0..4 {
+			for y in 0..4 {
+				img.set(x, y, c).unwrap();
+			}
+		}
+		return img;
+	};
+
+	let tex = CubeTexture::from_imgs(gfx, [
+		solid(colors[0]),
+		solid(colors[1]),
+		solid(colors[2]),
+		solid(colors[3]),
+		solid(colors[4]),
+		solid(colors[5]),
+	]).unwrap();
+
+	assert_eq!(tex.capture(CubeFace::NegY).unwrap().get(1, 1), Some(colors[3]));
+	assert!(CubeTexture::from_imgs(gfx, [
+		img::Image::new(4, 4),
+		img::Image::new(4, 4),
+		img::Image::new(4, 4),
+		img::Image::new(4, 4),
+		img::Image::new(4, 4),
+		img::Image::new(4, 2),
+	]).is_err());
+
+	// 4 bands centered at +z, -x, -z, +x
+	let mut pano = img::Image::new(64, 32);
+	let bands = [colors[4], colors[1], colors[5], colors[0]];
+
+	for x in 0..64 {
+		for y in 0..32 {
+			pano.set(x, y, bands[((x + 8) / 16 % 4) as usize]).unwrap();
+		}
+	}
+
+	let equi = CubeTexture::from_equirect(gfx, &pano, 8).unwrap();
+
+	for (i, face) in [CubeFace::PosX, CubeFace::NegX, CubeFace::PosZ, CubeFace::NegZ].iter().enumerate() {
+		let i = [0, 1, 4, 5][i];
+		assert_eq!(equi.capture(*face).unwrap().get(4, 4), Some(colors[i]));
+	}
+
+	let canvas = Canvas::new(gfx, 16, 16).unwrap();
+	let cube = Mesh::from_meshdata(gfx, &geom::meshgen::cube()).unwrap();
+
+	let draw = |gfx: &mut Gfx, dir: Vec3| {
+
+		let cam = PerspectiveCam {
+			fov: f32::to_radians(60.0),
+			up: vec3!(0, 1, 0),
+			aspect: 1.0,
+			near: 0.1,
+			far: 100.0,
+			pos: vec3!(0, 0, 3),
+			dir: dir,
+		};
+
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.use_cam(&cam, |gfx| {
+				gfx.draw_t(mat4!().t3(vec3!(0, 0, -5)), &shapes::mesh(&cube))?;
+				// drawn after but stays behind
+				return gfx.draw(&shapes::skybox(&tex));
+			});
+		}).unwrap();
+
+		return (canvas.read(vec2!(8, 8)), canvas.read(vec2!(1, 1)));
+
+	};
+
+	let (center, corner) = draw(gfx, vec3!(0, 0, -1));
+
+	assert_eq!(center, rgba!(1));
+	assert_eq!(corner, colors[5]);
+	assert_eq!(draw(gfx, vec3!(1, 0, 0)).1, colors[0]);
+	assert_eq!(draw(gfx, vec3!(0, 0, 1)).1, colors[4]);
+
+}
@@ -23,6 +23,57 @@ impl VertexLayout for Vertex {
 	}
 }
 
+/// Per Vertex Skinning Data, indices & weights of up to 4 joints
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SkinVertex {
+	pub joints: Vec4,
+	pub weights: Vec4,
+}
+
+impl VertexLayout for SkinVertex {
+	fn attrs() -> &'static[(&'static str, usize)] {
+		return &[
+			("a_joints", 4),
+			("a_weights", 4),
+		];
+	}
+}
+
+/// Max Number of Morph Targets in a Mesh, their weights are passed to the shader in a vec4
+pub const MAX_MORPH_TARGETS: usize = 4;
+
+/// Per Vertex Morph Target Data, offsets from the base vertex
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct MorphVertex {
+	pub pos: Vec3,
+	pub normal: Vec3,
+}
+
+impl VertexLayout for MorphVertex {
+	fn attrs() -> &'static[(&'static str, usize)] {
+		return &[
+			("a_morph_pos", 3),
+			("a_morph_normal", 3),
+		];
+	}
+}
+
+// skin & morph target attributes bound after the vertex attributes, 1 MorphVertex for each morph slot
+pub(super) const DEFORM_ATTRS: [(&str, usize); 2 + MAX_MORPH_TARGETS * 2] = [
+	("a_joints", 4),
+	("a_weights", 4),
+	("a_morph_pos0", 3),
+	("a_morph_normal0", 3),
+	("a_morph_pos1", 3),
+	("a_morph_normal1", 3),
+	("a_morph_pos2", 3),
+	("a_morph_normal2", 3),
+	("a_morph_pos3", 3),
+	("a_morph_normal3", 3),
+];
+
 #[derive(Clone, PartialEq)]
 pub(super) struct Uniform {
 	pub proj: Mat4,
@@ -41,8 +92,14 @@ impl UniformLayout for Uniform {
 			("u_proj", UniformData::Mat4(self.proj)),
 			("u_view", UniformData::Mat4(self.view)),
 			("u_model", UniformData::Mat4(self.model)),
+			("u_normal_mat", UniformData::Mat4(normal_mat(&self.model))),
 			("u_color", UniformData::Vec4(self.color.as_vec4())),
 			("u_tex", UniformData::Texture(self.tex.clone())),
+			// set by use_shadows
+			("u_shadow_count", UniformData::Int(0)),
+			// set by skinned meshes
+			("u_skinned", UniformData::Int(0)),
+			("u_morphed", UniformData::Int(0)),
 		];
 
 		if let Some(custom) = &self.custom {
@@ -57,3 +114,97 @@ impl UniformLayout for Uniform {
 
 }
 
+// inverse transpose of the model matrix, keeps normals perpendicular to surfaces under non-uniform scaling
+fn normal_mat(model: &Mat4) -> Mat4 {
+
+	let inv = model.inverse();
+
+	// flat models have no inverse
+	if !inv.as_arr().iter().all(|v| v.is_finite()) {
+		return *model;
+	}
+
+	return inv.transpose();
+
+}
+
+/// Per Instance Data for Instanced Drawing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instance {
+	pub transform: Mat4,
+	pub color: Color,
+	/// texture region, for sprites this also scales the quad like [`Sprite::quad`](shapes/struct.Sprite.html#method.quad)
+	pub quad: Quad,
+}
+
+impl Instance {
+	pub fn new(t: Mat4) -> Self {
+		return Self {
+			transform: t,
+			color: rgba!(1),
+			quad: quad!(0, 0, 1, 1),
+		};
+	}
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+	pub fn quad(mut self, q: Quad) -> Self {
+		self.quad = q;
+		return self;
+	}
+}
+
+impl Default for Instance {
+	fn default() -> Self {
+		return Self::new(mat4!());
+	}
+}
+
+// the layout uploaded to the instance buffer
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub(super) struct InstanceData {
+	model: [f32; 16],
+	color: [f32; 4],
+	quad: [f32; 4],
+}
+
+impl From<&Instance> for InstanceData {
+	fn from(i: &Instance) -> Self {
+		return Self {
+			model: i.transform.as_arr(),
+			color: [i.color.r, i.color.g, i.color.b, i.color.a],
+			quad: [i.quad.x, i.quad.y, i.quad.w, i.quad.h],
+		};
+	}
+}
+
+impl VertexLayout for InstanceData {
+	fn attrs() -> &'static[(&'static str, usize)] {
+		return &[
+			("a_inst_model0", 4),
+			("a_inst_model1", 4),
+			("a_inst_model2", 4),
+			("a_inst_model3", 4),
+			("a_inst_color", 4),
+			("a_inst_quad", 4),
+		];
+	}
+}
+
+#[derive(Clone, PartialEq)]
+pub(super) struct InstancedUniform {
+	pub base: Uniform,
+	pub local: Mat4,
+	pub sprite_size: Vec2,
+}
+
+impl UniformLayout for InstancedUniform {
+	fn data(&self) -> Vec<(&'static str, UniformData)> {
+		let mut values = self.base.data();
+		values.push(("u_local", UniformData::Mat4(self.local)));
+		values.push(("u_sprite_size", UniformData::Vec2(self.sprite_size)));
+		return values;
+	}
+}
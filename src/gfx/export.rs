@@ -0,0 +1,907 @@
+// wengwengweng
+
+use std::path::Path;
+
+use serde_json::json;
+use serde_json::Value;
+
+use super::*;
+
+type NodeID = usize;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+
+// binary buffer with views & accessors into it
+struct GLTFBuffer {
+	bin: Vec<u8>,
+	views: Vec<Value>,
+	accessors: Vec<Value>,
+}
+
+impl GLTFBuffer {
+
+	fn new() -> Self {
+		return Self {
+			bin: vec![],
+			views: vec![],
+			accessors: vec![],
+		};
+	}
+
+	fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+
+		// views need 4 byte alignment
+		while self.bin.len() % 4 != 0 {
+			self.bin.push(0);
+		}
+
+		let mut view = json!({
+			"buffer": 0,
+			"byteOffset": self.bin.len(),
+			"byteLength": bytes.len(),
+		});
+
+		if let Some(target) = target {
+			view["target"] = json!(target);
+		}
+
+		self.bin.extend_from_slice(bytes);
+		self.views.push(view);
+
+		return self.views.len() - 1;
+
+	}
+
+	// each item is a "SCALAR", "VECN" or "MAT4" of floats
+	fn floats(&mut self, data: &[f32], kind: &str, bounds: bool, target: Option<u32>) -> usize {
+
+		let size = match kind {
+			"VEC2" => 2,
+			"VEC3" => 3,
+			"VEC4" => 4,
+			"MAT4" => 16,
+			_ => 1,
+		};
+
+		let bytes = data
+			.iter()
+			.flat_map(|f| f.to_le_bytes().to_vec())
+			.collect::<Vec<u8>>();
+
+		let view = self.view(&bytes, target);
+
+		let mut accessor = json!({
+			"bufferView": view,
+			"componentType": FLOAT,
+			"count": data.len() / size,
+			"type": kind,
+		});
+
+		// required for positions & anim inputs
+		if bounds {
+
+			let mut min = vec![std::f32::MAX; size];
+			let mut max = vec![std::f32::MIN; size];
+
+			for item in data.chunks(size) {
+				for (i, v) in item.iter().enumerate() {
+					min[i] = min[i].min(*v);
+					max[i] = max[i].max(*v);
+				}
+			}
+
+			accessor["min"] = json!(min);
+			accessor["max"] = json!(max);
+
+		}
+
+		self.accessors.push(accessor);
+
+		return self.accessors.len() - 1;
+
+	}
+
+	fn vec2s(&mut self, data: &[Vec2]) -> usize {
+		let data = data.iter().flat_map(|v| vec![v.x, v.y]).collect::<Vec<f32>>();
+		return self.floats(&data, "VEC2", false, Some(ARRAY_BUFFER));
+	}
+
+	fn vec3s(&mut self, data: &[Vec3], bounds: bool) -> usize {
+		let data = data.iter().flat_map(|v| vec![v.x, v.y, v.z]).collect::<Vec<f32>>();
+		return self.floats(&data, "VEC3", bounds, Some(ARRAY_BUFFER));
+	}
+
+	fn vec4s(&mut self, data: &[Vec4]) -> usize {
+		let data = data.iter().flat_map(|v| vec![v.x, v.y, v.z, v.w]).collect::<Vec<f32>>();
+		return self.floats(&data, "VEC4", false, Some(ARRAY_BUFFER));
+	}
+
+	fn joints(&mut self, data: &[Vec4]) -> usize {
+
+		let bytes = data
+			.iter()
+			.flat_map(|j| vec![j.x, j.y, j.z, j.w])
+			.flat_map(|j| (j as u16).to_le_bytes().to_vec())
+			.collect::<Vec<u8>>();
+
+		let view = self.view(&bytes, Some(ARRAY_BUFFER));
+
+		self.accessors.push(json!({
+			"bufferView": view,
+			"componentType": UNSIGNED_SHORT,
+			"count": data.len(),
+			"type": "VEC4",
+		}));
+
+		return self.accessors.len() - 1;
+
+	}
+
+	fn indices(&mut self, data: &[u32]) -> usize {
+
+		let bytes = data
+			.iter()
+			.flat_map(|i| i.to_le_bytes().to_vec())
+			.collect::<Vec<u8>>();
+
+		let view = self.view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+
+		self.accessors.push(json!({
+			"bufferView": view,
+			"componentType": UNSIGNED_INT,
+			"count": data.len(),
+			"type": "SCALAR",
+		}));
+
+		return self.accessors.len() - 1;
+
+	}
+
+}
+
+fn same_img(a: &img::Image, b: &img::Image) -> bool {
+	return a.width() == b.width() && a.height() == b.height() && a.as_raw() == b.as_raw();
+}
+
+fn interpolation_name(i: Interpolation) -> &'static str {
+	return match i {
+		Interpolation::Step => "STEP",
+		Interpolation::Linear => "LINEAR",
+		Interpolation::CubicSpline => "CUBICSPLINE",
+	};
+}
+
+// morph weight tracks are packed into 1 output, they need the same keyframes
+fn pack_weights(tracks: &[Track<f32>]) -> Option<Track<f32>> {
+
+	let first = tracks.first()?;
+	let count = tracks.len();
+
+	let same = tracks
+		.iter()
+		.all(|t| t.times == first.times && t.interpolation == first.interpolation);
+
+	if same {
+
+		let per_key = first.values.len() / first.times.len().max(1);
+		let mut values = Vec::with_capacity(first.values.len() * count);
+
+		for k in 0..first.times.len() * per_key {
+			for t in tracks {
+				values.push(t.values.get(k).cloned().unwrap_or(0.0));
+			}
+		}
+
+		return Some(Track {
+			interpolation: first.interpolation,
+			times: first.times.clone(),
+			values: values,
+		});
+
+	}
+
+	// different keyframes, resample at all of them
+	let mut times = tracks
+		.iter()
+		.flat_map(|t| t.times.clone())
+		.collect::<Vec<f32>>();
+
+	times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+	times.dedup();
+
+	let values = times
+		.iter()
+		.flat_map(|time| tracks
+			.iter()
+			.map(move |t| get_track_val(t, *time).unwrap_or(0.0)))
+		.collect();
+
+	return Some(Track {
+		interpolation: Interpolation::Linear,
+		times: times,
+		values: values,
+	});
+
+}
+
+impl ModelData {
+
+	// build the gltf document, with 1 binary buffer that's not included
+	fn gltf_doc(&self) -> Result<(Value, Vec<u8>)> {
+
+		let mut buf = GLTFBuffer::new();
+
+		// gltf indices are continuous, node ids might not be
+		let mut ids = self.nodes.keys().cloned().collect::<Vec<NodeID>>();
+
+		ids.sort();
+
+		let index_of = |id: &NodeID| ids.binary_search(id).ok();
+
+		// images, each distinct image is only stored once even if it's used by several materials
+		let mut imgs: Vec<&img::Image> = vec![];
+
+		let used = self.materials
+			.iter()
+			.flat_map(|m| vec![
+				&m.base_color_img,
+				&m.metallic_roughness_img,
+				&m.normal_img,
+				&m.emissive_img,
+				&m.occlusion_img,
+			])
+			.chain(std::iter::once(&self.img))
+			.flatten();
+
+		for img in used {
+			if !imgs.iter().any(|i| same_img(i, img)) {
+				imgs.push(img);
+			}
+		}
+
+		let mut images = vec![];
+
+		for img in &imgs {
+			let view = buf.view(&img.to_png()?, None);
+			images.push(json!({
+				"bufferView": view,
+				"mimeType": "image/png",
+			}));
+		}
+
+		let img_index = |img: &img::Image| imgs.iter().position(|i| same_img(i, img)).unwrap_or(0);
+
+		// materials
+		let mut materials = vec![];
+
+		for m in &self.materials {
+
+			let c = m.base_color.to_linear();
+			let e = m.emissive.to_linear();
+
+			let mut pbr = json!({
+				"baseColorFactor": [c.r, c.g, c.b, c.a],
+				"metallicFactor": m.metallic,
+				"roughnessFactor": m.roughness,
+			});
+
+			let mut mat = json!({
+				"pbrMetallicRoughness": {},
+				"emissiveFactor": [e.r, e.g, e.b],
+			});
+
+			if let Some(name) = &m.name {
+				mat["name"] = json!(name);
+			}
+
+			if let Some(img) = &m.base_color_img {
+				pbr["baseColorTexture"] = json!({ "index": img_index(img) });
+			}
+
+			if let Some(img) = &m.metallic_roughness_img {
+				pbr["metallicRoughnessTexture"] = json!({ "index": img_index(img) });
+			}
+
+			if let Some(img) = &m.normal_img {
+				mat["normalTexture"] = json!({
+					"index": img_index(img),
+					"scale": m.normal_scale,
+				});
+			}
+
+			if let Some(img) = &m.emissive_img {
+				mat["emissiveTexture"] = json!({ "index": img_index(img) });
+			}
+
+			if let Some(img) = &m.occlusion_img {
+				mat["occlusionTexture"] = json!({
+					"index": img_index(img),
+					"strength": m.occlusion_strength,
+				});
+			}
+
+			mat["pbrMetallicRoughness"] = pbr;
+			materials.push(mat);
+
+		}
+
+		// the model texture goes to meshes without a material
+		let tex_mat = match &self.img {
+			Some(img) => {
+				materials.push(json!({
+					"pbrMetallicRoughness": {
+						"baseColorTexture": { "index": img_index(img) },
+					},
+				}));
+				Some(materials.len() - 1)
+			},
+			None => None,
+		};
+
+		// nodes & meshes
+		let mut nodes = vec![];
+		let mut meshes = vec![];
+
+		for id in &ids {
+
+			let node = &self.nodes[id];
+			let tr = node.transform;
+
+			let mut n = json!({
+				"translation": [tr.pos.x, tr.pos.y, tr.pos.z],
+				"rotation": [tr.rot.x, tr.rot.y, tr.rot.z, tr.rot.w],
+				"scale": [tr.scale.x, tr.scale.y, tr.scale.z],
+			});
+
+			if let Some(name) = &node.name {
+				n["name"] = json!(name);
+			}
+
+			let children = node.children
+				.iter()
+				.filter_map(index_of)
+				.collect::<Vec<usize>>();
+
+			if !children.is_empty() {
+				n["children"] = json!(children);
+			}
+
+			if let Some(skin) = node.skin {
+				n["skin"] = json!(skin);
+			}
+
+			if !node.weights.is_empty() {
+				n["weights"] = json!(node.weights);
+			}
+
+			if !node.meshes.is_empty() {
+
+				let mut prims = vec![];
+
+				for p in &node.meshes {
+
+					let verts = &p.mesh.vertices;
+					let pos = verts.iter().map(|v| v.pos).collect::<Vec<Vec3>>();
+					let normals = verts.iter().map(|v| v.normal).collect::<Vec<Vec3>>();
+					let uvs = verts.iter().map(|v| v.uv).collect::<Vec<Vec2>>();
+					// gltf vertex colors are linear
+					let colors = verts.iter().map(|v| v.color.to_linear().as_vec4()).collect::<Vec<Vec4>>();
+
+					let mut attrs = json!({
+						"POSITION": buf.vec3s(&pos, true),
+						"NORMAL": buf.vec3s(&normals, false),
+						"TEXCOORD_0": buf.vec2s(&uvs),
+						"COLOR_0": buf.vec4s(&colors),
+					});
+
+					if let Some(skin) = &p.skin {
+						let joints = skin.iter().map(|s| s.joints).collect::<Vec<Vec4>>();
+						let weights = skin.iter().map(|s| s.weights).collect::<Vec<Vec4>>();
+						attrs["JOINTS_0"] = json!(buf.joints(&joints));
+						attrs["WEIGHTS_0"] = json!(buf.vec4s(&weights));
+					}
+
+					let mut prim = json!({
+						"attributes": attrs,
+						"indices": buf.indices(&p.mesh.indices),
+					});
+
+					if let Some(mat) = p.material.or(tex_mat) {
+						prim["material"] = json!(mat);
+					}
+
+					if !p.morphs.is_empty() {
+						let targets = p.morphs
+							.iter()
+							.map(|m| {
+								let pos = m.iter().map(|v| v.pos).collect::<Vec<Vec3>>();
+								let normals = m.iter().map(|v| v.normal).collect::<Vec<Vec3>>();
+								return json!({
+									"POSITION": buf.vec3s(&pos, true),
+									"NORMAL": buf.vec3s(&normals, false),
+								});
+							})
+							.collect::<Vec<Value>>();
+						prim["targets"] = json!(targets);
+					}
+
+					prims.push(prim);
+
+				}
+
+				meshes.push(json!({
+					"primitives": prims,
+				}));
+
+				n["mesh"] = json!(meshes.len() - 1);
+
+			}
+
+			nodes.push(n);
+
+		}
+
+		// skins
+		let skins = self.skins
+			.iter()
+			.map(|s| {
+				let mats = s.inverse_binds
+					.iter()
+					.flat_map(|m| m.as_arr().to_vec())
+					.collect::<Vec<f32>>();
+				return json!({
+					"joints": s.joints.iter().filter_map(index_of).collect::<Vec<usize>>(),
+					"inverseBindMatrices": buf.floats(&mats, "MAT4", false, None),
+				});
+			})
+			.collect::<Vec<Value>>();
+
+		// animations
+		let mut animations = vec![];
+
+		for clip in &self.clips {
+
+			let mut channels = vec![];
+			let mut samplers = vec![];
+
+			let mut anim_ids = clip.anims.keys().cloned().collect::<Vec<NodeID>>();
+
+			anim_ids.sort();
+
+			for id in anim_ids {
+
+				let node = match index_of(&id) {
+					Some(i) => i,
+					None => continue,
+				};
+
+				let anim = &clip.anims[&id];
+
+				let mut channel = |path: &str, interpolation: Interpolation, times: &[f32], output: usize, buf: &mut GLTFBuffer| {
+					let input = buf.floats(times, "SCALAR", true, None);
+					samplers.push(json!({
+						"input": input,
+						"output": output,
+						"interpolation": interpolation_name(interpolation),
+					}));
+					channels.push(json!({
+						"sampler": samplers.len() - 1,
+						"target": { "node": node, "path": path },
+					}));
+				};
+
+				if let Some(t) = &anim.pos {
+					let output = buf.vec3s(&t.values, false);
+					channel("translation", t.interpolation, &t.times, output, &mut buf);
+				}
+
+				if let Some(t) = &anim.rot {
+					let output = buf.vec4s(&t.values);
+					channel("rotation", t.interpolation, &t.times, output, &mut buf);
+				}
+
+				if let Some(t) = &anim.scale {
+					let output = buf.vec3s(&t.values, false);
+					channel("scale", t.interpolation, &t.times, output, &mut buf);
+				}
+
+				if let Some(t) = pack_weights(&anim.weights) {
+					let output = buf.floats(&t.values, "SCALAR", false, None);
+					channel("weights", t.interpolation, &t.times, output, &mut buf);
+				}
+
+			}
+
+			if channels.is_empty() {
+				continue;
+			}
+
+			let mut a = json!({
+				"channels": channels,
+				"samplers": samplers,
+			});
+
+			if let Some(name) = &clip.name {
+				a["name"] = json!(name);
+			}
+
+			animations.push(a);
+
+		}
+
+		while buf.bin.len() % 4 != 0 {
+			buf.bin.push(0);
+		}
+
+		let mut doc = json!({
+			"asset": {
+				"version": "2.0",
+				"generator": "dirty",
+			},
+			"scene": 0,
+			"scenes": [{
+				"nodes": self.root_nodes.iter().filter_map(index_of).collect::<Vec<usize>>(),
+			}],
+			"nodes": nodes,
+			"buffers": [{
+				"byteLength": buf.bin.len(),
+			}],
+		});
+
+		let mut set = |key: &str, list: Vec<Value>| {
+			if !list.is_empty() {
+				doc[key] = json!(list);
+			}
+		};
+
+		set("meshes", meshes);
+		set("materials", materials);
+		set("textures", (0..images.len()).map(|i| json!({ "source": i, "sampler": 0 })).collect());
+		set("samplers", if images.is_empty() { vec![] } else { vec![json!({})] });
+		set("images", images);
+		set("skins", skins);
+		set("animations", animations);
+		set("bufferViews", buf.views);
+		set("accessors", buf.accessors);
+
+		return Ok((doc, buf.bin));
+
+	}
+
+	/// encode to glb bytes
+	pub fn to_glb(&self) -> Result<Vec<u8>> {
+
+		let (doc, bin) = self.gltf_doc()?;
+
+		let mut json = serde_json::to_vec(&doc)
+			.map_err(|_| format!("failed to encode gltf"))?;
+
+		while json.len() % 4 != 0 {
+			json.push(b' ');
+		}
+
+		let len = 12 + 8 + json.len() + 8 + bin.len();
+		let mut bytes = Vec::with_capacity(len);
+
+		// header
+		bytes.extend_from_slice(b"glTF");
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(&(len as u32).to_le_bytes());
+
+		// chunks
+		bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+		bytes.extend_from_slice(b"JSON");
+		bytes.extend_from_slice(&json);
+		bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+		bytes.extend_from_slice(b"BIN\0");
+		bytes.extend_from_slice(&bin);
+
+		return Ok(bytes);
+
+	}
+
+	/// encode to gltf text, with the binary data embedded
+	pub fn to_gltf(&self) -> Result<String> {
+
+		let (mut doc, bin) = self.gltf_doc()?;
+
+		doc["buffers"][0]["uri"] = json!(format!("data:application/octet-stream;base64,{}", base64::encode(&bin)));
+
+		return serde_json::to_string_pretty(&doc)
+			.map_err(|_| format!("failed to encode gltf"));
+
+	}
+
+	/// encode to obj & mtl text, `name` is used to reference `name.mtl` & `name.png`
+	///
+	/// meshes are transformed by their nodes, vertex colors are written after positions, only the model texture is kept
+	pub fn to_obj(&self, name: &str) -> (String, String) {
+
+		let mut obj = format!("mtllib {}.mtl\n", name);
+		let mut mtl = String::new();
+
+		for (i, m) in self.materials.iter().enumerate() {
+
+			let c = m.base_color;
+			let a = m.ambient;
+
+			mtl.push_str(&format!("newmtl mat{}\n", i));
+			mtl.push_str(&format!("Kd {} {} {}\n", c.r, c.g, c.b));
+			mtl.push_str(&format!("Ka {} {} {}\n", a.r, a.g, a.b));
+
+			if let Some(s) = m.specular {
+				mtl.push_str(&format!("Ks {} {} {}\n", s.r, s.g, s.b));
+			}
+
+			// inverse of the roughness mapping in load_obj_with()
+			let ns = 2.0 / m.roughness.max(0.01).powi(4) - 2.0;
+
+			mtl.push_str(&format!("Ns {}\n", ns.max(0.0)));
+			mtl.push_str(&format!("d {}\n", c.a));
+
+			if m.base_color_img.is_some() {
+				mtl.push_str(&format!("map_Kd {}_{}.png\n", name, i));
+			}
+
+			mtl.push_str("\n");
+
+		}
+
+		let mut offset = 1;
+		let mut stack = self.root_nodes
+			.iter()
+			.map(|id| (*id, mat4!()))
+			.collect::<Vec<(NodeID, Mat4)>>();
+
+		stack.reverse();
+
+		while let Some((id, parent)) = stack.pop() {
+
+			let node = match self.nodes.get(&id) {
+				Some(n) => n,
+				None => continue,
+			};
+
+			let tr = parent * node.transform.as_mat4();
+			let ntr = tr.inverse().transpose();
+
+			for (i, p) in node.meshes.iter().enumerate() {
+
+				let verts = &p.mesh.vertices;
+				let mat_name = match p.material {
+					Some(m) if m < self.materials.len() => format!("mat{}", m),
+					_ => {
+
+						let mat_name = format!("node{}_{}", id, i);
+						let color = verts.first().map(|v| v.color).unwrap_or(rgba!(1));
+
+						// obj colors come from the material
+						mtl.push_str(&format!("newmtl {}\n", mat_name));
+						mtl.push_str(&format!("Kd {} {} {}\n", color.r, color.g, color.b));
+
+						if self.img.is_some() {
+							mtl.push_str(&format!("map_Kd {}.png\n", name));
+						}
+
+						mtl.push_str("\n");
+
+						mat_name
+
+					},
+				};
+
+				obj.push_str(&format!("o {}\n", node.name.clone().unwrap_or(mat_name.clone())));
+				obj.push_str(&format!("usemtl {}\n", mat_name));
+
+				for v in verts {
+					let pos = tr * v.pos;
+					obj.push_str(&format!("v {} {} {} {} {} {}\n", pos.x, pos.y, pos.z, v.color.r, v.color.g, v.color.b));
+				}
+
+				for v in verts {
+					obj.push_str(&format!("vt {} {}\n", v.uv.x, 1.0 - v.uv.y));
+				}
+
+				for v in verts {
+					let n = (ntr * vec4!(v.normal.x, v.normal.y, v.normal.z, 0)).xyz().unit();
+					obj.push_str(&format!("vn {} {} {}\n", n.x, n.y, n.z));
+				}
+
+				for f in p.mesh.indices.chunks(3) {
+					if let [a, b, c] = f {
+						let (a, b, c) = (a + offset, b + offset, c + offset);
+						obj.push_str(&format!("f {}/{}/{} {}/{}/{} {}/{}/{}\n", a, a, a, b, b, b, c, c, c));
+					}
+				}
+
+				offset += verts.len() as u32;
+
+			}
+
+			for c in node.children.iter().rev() {
+				stack.push((*c, tr));
+			}
+
+		}
+
+		return (obj, mtl);
+
+	}
+
+	/// save to a glb, gltf or obj file, obj also writes the mtl & png next to it
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+
+		let path = path.as_ref();
+		let ext = path
+			.extension()
+			.and_then(|e| e.to_str())
+			.unwrap_or("");
+
+		match ext {
+
+			"glb" => fs::write(path, self.to_glb()?)?,
+			"gltf" => fs::write(path, self.to_gltf()?)?,
+
+			"obj" => {
+
+				let name = path
+					.file_stem()
+					.and_then(|s| s.to_str())
+					.ok_or_else(|| format!("failed to get basename: {}", path.display()))?;
+
+				let (obj, mtl) = self.to_obj(name);
+
+				fs::write(path, obj)?;
+				fs::write(path.with_extension("mtl"), mtl)?;
+
+				if let Some(img) = &self.img {
+					fs::write(path.with_extension("png"), img.to_png()?)?;
+				}
+
+				for (i, m) in self.materials.iter().enumerate() {
+					if let Some(img) = &m.base_color_img {
+						fs::write(path.with_file_name(format!("{}_{}.png", name, i)), img.to_png()?)?;
+					}
+				}
+
+			},
+
+			_ => return Err(format!("unsupported 3d format")),
+
+		}
+
+		return Ok(());
+
+	}
+
+}
+
+impl MeshData {
+
+	/// encode to obj text
+	pub fn to_obj(&self) -> String {
+
+		let mut obj = String::new();
+
+		for v in &self.vertices {
+			obj.push_str(&format!("v {} {} {} {} {} {}\n", v.pos.x, v.pos.y, v.pos.z, v.color.r, v.color.g, v.color.b));
+		}
+
+		for v in &self.vertices {
+			obj.push_str(&format!("vt {} {}\n", v.uv.x, 1.0 - v.uv.y));
+		}
+
+		for v in &self.vertices {
+			obj.push_str(&format!("vn {} {} {}\n", v.normal.x, v.normal.y, v.normal.z));
+		}
+
+		for f in self.indices.chunks(3) {
+			if let [a, b, c] = f {
+				let (a, b, c) = (a + 1, b + 1, c + 1);
+				obj.push_str(&format!("f {}/{}/{} {}/{}/{} {}/{}/{}\n", a, a, a, b, b, b, c, c, c));
+			}
+		}
+
+		return obj;
+
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn export() {
+
+	let mut data = Model::load_meshdata(geom::meshgen::cube());
+
+	data.img = Some(img::Image::new(2, 2));
+
+	if let Some(node) = data.nodes.get_mut(&0) {
+		node.name = Some(String::from("box"));
+		node.transform.pos = vec3!(1, 2, 3);
+		node.children = vec![7];
+		for v in &mut node.meshes[0].mesh.vertices {
+			v.color = rgba!(1, 0.5, 0, 1);
+		}
+	}
+
+	// a sparse node id
+	data.nodes.insert(7, NodeData {
+		id: 7,
+		children: vec![],
+		transform: Transform::new(),
+		meshes: vec![],
+		skin: None,
+		weights: vec![],
+		name: Some(String::from("child")),
+	});
+
+	data.clips.push(AnimationClip::new(Some(String::from("move")), hmap![
+		7 => Anim {
+			pos: Some(Track {
+				interpolation: Interpolation::Step,
+				..Track::linear(&[(0.0, vec3!(0)), (2.0, vec3!(4, 0, 0))])
+			}),
+			..Anim::default()
+		},
+	]));
+
+	let check = |back: ModelData| {
+
+		assert_eq!(back.nodes.len(), 2);
+		assert_eq!(back.root_nodes, vec![0]);
+
+		let node = &back.nodes[&0];
+		let verts = &node.meshes[0].mesh.vertices;
+		let src = &data.nodes[&0].meshes[0].mesh.vertices;
+
+		assert_eq!(node.name.as_deref(), Some("box"));
+		assert_eq!(node.transform.pos, vec3!(1, 2, 3));
+		assert_eq!(node.children, vec![1]);
+		assert_eq!(verts.len(), src.len());
+		assert_eq!(verts[3].pos, src[3].pos);
+		assert_eq!(verts[3].uv, src[3].uv);
+		assert!((verts[3].color.g - 0.5).abs() < 0.01);
+		assert!(back.materials[0].base_color_img.is_some());
+		assert_eq!(back.nodes[&1].name.as_deref(), Some("child"));
+
+		let clip = &back.clips[0];
+		let track = clip.anims[&1].pos.as_ref().unwrap();
+
+		assert_eq!(clip.name.as_deref(), Some("move"));
+		assert_eq!(clip.len(), 2.0);
+		assert_eq!(track.interpolation, Interpolation::Step);
+		assert_eq!(track.values[1], vec3!(4, 0, 0));
+
+	};
+
+	check(Model::load_glb(&data.to_glb().unwrap()).unwrap());
+	check(Model::load_gltf(&data.to_gltf().unwrap()).unwrap());
+
+	let (obj, mtl) = data.to_obj("box");
+	let back = Model::load_obj(&obj, Some(&mtl), None).unwrap();
+	let verts = &back.nodes[&0].meshes[0].mesh.vertices;
+	let src = &data.nodes[&0].meshes[0].mesh.vertices;
+
+	assert!(mtl.contains("map_Kd box.png"));
+	assert_eq!(verts.len(), src.len());
+	assert_eq!(verts[3].pos, src[3].pos + vec3!(1, 2, 3));
+	assert!((verts[3].uv.y - src[3].uv.y).abs() < 0.0001);
+	assert_eq!(back.materials[0].base_color, rgba!(1, 0.5, 0, 1));
+	assert_eq!(back.nodes[&0].meshes[0].material, Some(0));
+
+	// the same image as the model texture is only stored once
+	data.materials.push(MaterialData {
+		base_color_img: data.img.clone(),
+		..MaterialData::default()
+	});
+
+	let doc: Value = serde_json::from_str(&data.to_gltf().unwrap()).unwrap();
+
+	assert_eq!(doc["images"].as_array().map(|a| a.len()), Some(1));
+
+}
@@ -22,6 +22,18 @@ pub trait Font {
 	fn height(&self) -> f32;
 	/// if there's a fixed character width
 	fn width(&self) -> Option<f32>;
+	/// distance field spread in pixels if glyphs are signed distance fields
+	fn sdf_spread(&self) -> Option<f32> {
+		return None;
+	}
+	/// extra space between 2 characters drawn next to each other
+	fn kerning(&self, _left: char, _right: char) -> f32 {
+		return 0.0;
+	}
+	/// make characters available to [`get`](#tymethod.get), for fonts that rasterize glyphs on demand
+	fn cache_str(&mut self, _s: &str) -> Result<()> {
+		return Ok(());
+	}
 }
 
 /// Data for Creating [`BitmapFont`](struct.BitmapFont.html)
@@ -130,13 +142,23 @@ impl Font for BitmapFont {
 	}
 }
 
-// TODO
 /// Font Loaded from a Truetype File
+///
+/// only cached characters are drawn, cache everything a text might use with [`cache_str`](#method.cache_str) first
+///
+/// line height is the ascent - descent of the font at `size`, usually taller than `size`
+///
+/// kerning is only read from the legacy `kern` table, pairs only in GPOS are ignored
 pub struct TruetypeFont {
 	font: fontdue::Font,
 	size: i32,
+	ascent: f32,
+	height: f32,
 	cur_pt: Pt,
+	row_height: i32,
 	map: HashMap<char, Char>,
+	// glyph pairs, chars are looked up when asked
+	kerning: HashMap<(u16, u16), f32>,
 	tex: Texture,
 }
 
@@ -145,20 +167,25 @@ impl TruetypeFont {
 	/// load from bytes of a truetype font file
 	pub fn from_bytes(ctx: &impl GLCtx, b: &[u8], size: i32) -> Result<Self> {
 
+		if size > 72 {
+			return Err(format!("font size cannot exceed 72"));
+		}
+
 		let font = fontdue::Font::from_bytes(b, fontdue::FontSettings::default())?;
 		let (max_w, max_h) = (size * 32, size * 32);
 		// TODO: make sure this doesn't exceed 2048x2048
 		let tex = Texture::new(ctx, max_w, max_h)?;
-
-		if size > 72 {
-			return Err(format!("font size cannot exceed 72"));
-		}
+		let (ascent, height) = line_metrics(&font, size as f32);
 
 		return Ok(Self {
 			font,
 			size,
+			ascent,
+			height,
 			map: HashMap::new(),
+			kerning: read_kerning(b, size as f32),
 			cur_pt: pt!(0, 0),
+			row_height: 0,
 			tex,
 		});
 
@@ -184,14 +211,17 @@ impl TruetypeFont {
 
 		if x + w >= tw {
 			x = 0;
-			y += h;
+			y += self.row_height;
+			self.row_height = 0;
 		}
 
-		if y >= th {
+		if y + h >= th {
 			return Err(format!("reached font texture size limit"));
 		}
 
-		self.tex.sub_data(x as i32, y as i32, w as i32, self.size as i32, &nbitmap);
+		if w > 0 && h > 0 {
+			self.tex.sub_data(x as i32, y as i32, w as i32, h as i32, &nbitmap);
+		}
 
 		self.map.insert(ch, Char {
 			ch: ch,
@@ -202,13 +232,14 @@ impl TruetypeFont {
 				h as f32 / th as f32,
 			),
 			tex: self.tex.clone(),
-			bearing_x: 0.0,
-			bearing_y: 0.0,
-			advance: 0.0,
+			bearing_x: metrics.bounds.xmin.floor(),
+			bearing_y: self.ascent - (metrics.bounds.ymin.floor() + h as f32),
+			advance: metrics.advance_width,
 		});
 
 		x += w;
 		self.cur_pt = pt!(x, y);
+		self.row_height = self.row_height.max(h);
 
 		return Ok(());
 
@@ -232,12 +263,22 @@ impl TruetypeFont {
 
 	/// get width for a piece of string
 	pub fn width(&self, s: &str) -> f32 {
-		return s
-			.chars()
-			.map(|c| self.map.get(&c))
-			.flatten()
-			.map(|c| c.quad.w * self.tex.width() as f32)
-			.sum();
+
+		let mut w = 0.0;
+		let mut prev = None;
+
+		for ch in s.chars() {
+			if let Some(c) = self.map.get(&ch) {
+				if let Some(prev) = prev {
+					w += self.kerning(prev, ch);
+				}
+				w += c.advance;
+				prev = Some(ch);
+			}
+		}
+
+		return w;
+
 	}
 
 }
@@ -247,12 +288,436 @@ impl Font for TruetypeFont {
 		return self.map.get(&ch);
 	}
 	fn height(&self) -> f32 {
-		return self.size as f32;
+		return self.height;
+	}
+	fn width(&self) -> Option<f32> {
+		return None;
+	}
+	fn kerning(&self, left: char, right: char) -> f32 {
+		return glyph_kerning(&self.font, &self.kerning, left, right);
+	}
+	fn cache_str(&mut self, s: &str) -> Result<()> {
+		// the inherent method
+		return TruetypeFont::cache_str(self, s);
+	}
+}
+
+// (ascent, line height) at a pixel size
+fn line_metrics(font: &fontdue::Font, size: f32) -> (f32, f32) {
+	return match font.horizontal_line_metrics(size) {
+		Some(m) => (m.ascent, m.ascent - m.descent),
+		None => (size, size),
+	};
+}
+
+fn glyph_kerning(font: &fontdue::Font, pairs: &HashMap<(u16, u16), f32>, left: char, right: char) -> f32 {
+	if pairs.is_empty() {
+		return 0.0;
+	}
+	let (l, r) = (font.lookup_glyph_index(left) as u16, font.lookup_glyph_index(right) as u16);
+	return pairs.get(&(l, r)).cloned().unwrap_or(0.0);
+}
+
+// horizontal pairs of the first format 0 subtables in a version 0 "kern" table, in pixels at size
+fn read_kerning(data: &[u8], size: f32) -> HashMap<(u16, u16), f32> {
+
+	let mut pairs = HashMap::new();
+
+	let face = match ttf_parser::Face::from_slice(data, 0) {
+		Ok(face) => face,
+		Err(_) => return pairs,
+	};
+
+	let scale = match face.units_per_em() {
+		Some(upem) if upem > 0 => size / upem as f32,
+		_ => return pairs,
+	};
+
+	let kern = match face.table_data(ttf_parser::Tag::from_bytes(b"kern")) {
+		Some(kern) => kern,
+		None => return pairs,
+	};
+
+	let u16_at = |i: usize| kern.get(i..i + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+
+	// apple's version 1 tables are not read
+	if u16_at(0) != Some(0) {
+		return pairs;
+	}
+
+	let count = u16_at(2).unwrap_or(0);
+	let mut offset = 4;
+
+	for _ in 0..count {
+
+		let (len, coverage) = match (u16_at(offset + 2), u16_at(offset + 4)) {
+			(Some(len), Some(coverage)) => (len as usize, coverage),
+			_ => break,
+		};
+
+		let format = coverage >> 8;
+		// horizontal, not minimum values or cross stream
+		let horizontal = coverage & 0b111 == 0b001;
+
+		if format == 0 && horizontal {
+
+			let n = u16_at(offset + 6).unwrap_or(0) as usize;
+
+			for i in 0..n {
+				let p = offset + 14 + i * 6;
+				if let (Some(l), Some(r), Some(v)) = (u16_at(p), u16_at(p + 2), u16_at(p + 4)) {
+					if v != 0 {
+						pairs.entry((l, r)).or_insert(v as i16 as f32 * scale);
+					}
+				}
+			}
+
+		}
+
+		if len < 6 {
+			break;
+		}
+
+		offset += len;
+
+	}
+
+	return pairs;
+
+}
+
+const SDF_ATLAS_SIZE: i32 = 1024;
+
+/// Truetype Font with Signed Distance Field Glyphs, Stays Crisp at Any Scale
+///
+/// glyphs are rasterized once at `size` then scaled freely with [`shapes::Text::size`](shapes/struct.Text.html#method.size), they also support [`outline`](shapes/struct.Text.html#method.outline) & [`glow`](shapes/struct.Text.html#method.glow)
+///
+/// kerning is only read from the legacy `kern` table, pairs only in GPOS are ignored
+pub struct SdfFont {
+	font: fontdue::Font,
+	size: f32,
+	spread: f32,
+	ascent: f32,
+	height: f32,
+	cur_pt: Pt,
+	row_height: i32,
+	map: HashMap<char, Char>,
+	// glyph pairs, chars are looked up when asked
+	kerning: HashMap<(u16, u16), f32>,
+	tex: Texture,
+}
+
+impl SdfFont {
+
+	/// load from bytes of a truetype font file, spread is how far in pixels the distance field reaches outside the glyph
+	pub fn from_bytes(ctx: &impl GLCtx, b: &[u8], size: f32, spread: f32) -> Result<Self> {
+
+		if size <= 0.0 || spread < 1.0 {
+			return Err(format!("invalid sdf font size or spread"));
+		}
+
+		let font = fontdue::Font::from_bytes(b, fontdue::FontSettings::default())?;
+		let (ascent, height) = line_metrics(&font, size);
+
+		// distances are interpolated
+		let tex = Texture::new_with_conf(ctx, SDF_ATLAS_SIZE, SDF_ATLAS_SIZE, TextureConf {
+			filter: FilterMode::Linear,
+			wrap_x: WrapMode::ClampToEdge,
+			wrap_y: WrapMode::ClampToEdge,
+			..TextureConf::default()
+		})?;
+
+		let mut font = Self {
+			font: font,
+			size: size,
+			spread: spread,
+			ascent: ascent,
+			height: height,
+			cur_pt: pt!(0, 0),
+			row_height: 0,
+			map: HashMap::new(),
+			kerning: read_kerning(b, size),
+			tex: tex,
+		};
+
+		font.cache_str(ASCII_CHARS)?;
+
+		return Ok(font);
+
+	}
+
+	/// cache a character to the atlas
+	pub fn cache(&mut self, ch: char) -> Result<()> {
+
+		if self.map.get(&ch).is_some() {
+			return Ok(());
+		}
+
+		let (metrics, bitmap) = self.font.rasterize(ch, self.size);
+		let pad = self.spread.ceil() as i32;
+		let (w, h) = (metrics.width as i32 + pad * 2, metrics.height as i32 + pad * 2);
+		let (tw, th) = (self.tex.width(), self.tex.height());
+		let (mut x, mut y) = (self.cur_pt.x, self.cur_pt.y);
+
+		if x + w > tw {
+			x = 0;
+			y += self.row_height;
+			self.row_height = 0;
+		}
+
+		if w > tw || y + h > th {
+			return Err(format!("reached font texture size limit"));
+		}
+
+		let data = gen_sdf(&bitmap, metrics.width, metrics.height, pad as usize, self.spread)
+			.into_iter()
+			.flat_map(|d| vec![255, 255, 255, d])
+			.collect::<Vec<u8>>();
+
+		self.tex.sub_data(x, y, w, h, &data);
+
+		let xmin = metrics.bounds.xmin.floor();
+		let ymin = metrics.bounds.ymin.floor();
+
+		self.map.insert(ch, Char {
+			ch: ch,
+			quad: quad!(
+				x as f32 / tw as f32,
+				y as f32 / th as f32,
+				w as f32 / tw as f32,
+				h as f32 / th as f32,
+			),
+			tex: self.tex.clone(),
+			bearing_x: xmin - pad as f32,
+			bearing_y: self.ascent - (ymin + metrics.height as f32) - pad as f32,
+			advance: metrics.advance_width,
+		});
+
+		self.cur_pt = pt!(x + w, y);
+		self.row_height = self.row_height.max(h);
+
+		return Ok(());
+
+	}
+
+	/// cache a whole string
+	pub fn cache_str(&mut self, s: &str) -> Result<()> {
+
+		for ch in s.chars() {
+			self.cache(ch)?;
+		}
+
+		return Ok(());
+
+	}
+
+	/// size glyphs are rasterized at
+	pub fn size(&self) -> f32 {
+		return self.size;
+	}
+
+}
+
+impl Font for SdfFont {
+	fn get(&self, ch: char) -> Option<&Char> {
+		return self.map.get(&ch);
+	}
+	fn height(&self) -> f32 {
+		return self.height;
 	}
 	fn width(&self) -> Option<f32> {
 		return None;
 	}
+	fn sdf_spread(&self) -> Option<f32> {
+		return Some(self.spread);
+	}
+	fn kerning(&self, left: char, right: char) -> f32 {
+		return glyph_kerning(&self.font, &self.kerning, left, right);
+	}
+	fn cache_str(&mut self, s: &str) -> Result<()> {
+		// the inherent method
+		return SdfFont::cache_str(self, s);
+	}
+}
+
+// squared distance transform of a line, from "Distance Transforms of Sampled Functions" by Felzenszwalb & Huttenlocher
+fn edt_1d(f: &[f32], out: &mut [f32]) {
+
+	let n = f.len();
+	let mut v = vec![0; n];
+	let mut z = vec![0.0; n + 1];
+	let mut k = 0;
+
+	let inter = |q: usize, p: usize| {
+		return ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * (q as f32 - p as f32));
+	};
+
+	z[0] = f32::NEG_INFINITY;
+	z[1] = f32::INFINITY;
+
+	for q in 1..n {
+
+		let mut s = inter(q, v[k]);
+
+		while s <= z[k] {
+			k -= 1;
+			s = inter(q, v[k]);
+		}
+
+		k += 1;
+		v[k] = q;
+		z[k] = s;
+		z[k + 1] = f32::INFINITY;
+
+	}
+
+	k = 0;
+
+	for q in 0..n {
+		while z[k + 1] < q as f32 {
+			k += 1;
+		}
+		let d = q as f32 - v[k] as f32;
+		out[q] = d * d + f[v[k]];
+	}
+
+}
+
+// distance from each pixel to the closest pixel where target is true
+fn edt(mask: &[bool], w: usize, h: usize, target: bool) -> Vec<f32> {
+
+	// finite so the parabola intersections don't get NaN
+	const FAR: f32 = 1e20;
+
+	let mut grid = mask
+		.iter()
+		.map(|m| if *m == target { 0.0 } else { FAR })
+		.collect::<Vec<f32>>();
+
+	let mut line = vec![0.0; w.max(h)];
+	let mut out = vec![0.0; w.max(h)];
+
+	for x in 0..w {
+		for y in 0..h {
+			line[y] = grid[y * w + x];
+		}
+		edt_1d(&line[..h], &mut out[..h]);
+		for y in 0..h {
+			grid[y * w + x] = out[y];
+		}
+	}
+
+	for y in 0..h {
+		edt_1d(&grid[y * w..(y + 1) * w], &mut out[..w]);
+		grid[y * w..(y + 1) * w].copy_from_slice(&out[..w]);
+	}
+
+	return grid.into_iter().map(f32::sqrt).collect();
+
+}
+
+// signed distance field of a coverage bitmap with padding, 0.5 is the edge and 1.0 is spread pixels inside
+fn gen_sdf(coverage: &[u8], w: usize, h: usize, pad: usize, spread: f32) -> Vec<u8> {
+
+	let (pw, ph) = (w + pad * 2, h + pad * 2);
+	let mut mask = vec![false; pw * ph];
+
+	for y in 0..h {
+		for x in 0..w {
+			mask[(y + pad) * pw + x + pad] = coverage[y * w + x] >= 128;
+		}
+	}
+
+	let to_inside = edt(&mask, pw, ph, true);
+	let to_outside = edt(&mask, pw, ph, false);
+
+	return mask
+		.iter()
+		.enumerate()
+		.map(|(i, inside)| {
+			// edges are between pixels
+			let d = if *inside {
+				to_outside[i] - 0.5
+			} else {
+				-(to_inside[i] - 0.5)
+			};
+			return ((0.5 + d / (spread * 2.0)).max(0.0).min(1.0) * 255.0).round() as u8;
+		})
+		.collect();
+
 }
 
 // TODO: 3d extruded text
 
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn sdf() {
+
+	// 4x4 square in a 8x8 bitmap
+	let mut bitmap = vec![0; 64];
+
+	for x in 2..6 {
+		for y in 2..6 {
+			bitmap[y * 8 + x] = 255;
+		}
+	}
+
+	let field = gen_sdf(&bitmap, 8, 8, 2, 2.0);
+	let at = |x: usize, y: usize| field[y * 12 + x] as f32 / 255.0;
+
+	// inside, edge & outside
+	assert!(at(6, 6) > 0.75);
+	assert!((at(4, 6) - 0.5).abs() < 0.2);
+	assert!(at(6, 4) > 0.5);
+	assert!(at(6, 3) < 0.5);
+	assert!(at(0, 0) == 0.0);
+
+	// generated by fonts/test/make.py, fonts with a format 4 cmap trip the debug checks of fontdue 0.2
+	let bytes = include_bytes!("fonts/test/test.ttf");
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 64, 64).unwrap();
+	let font = SdfFont::from_bytes(gfx, bytes, 48.0, 8.0).unwrap();
+
+	assert!(font.sdf_spread() == Some(8.0));
+	assert!(font.get('I').map(|c| c.advance > 0.0).unwrap_or(false));
+
+	// A & V are kerned -100 units at 1000 units per em
+	assert!((font.kerning('A', 'V') + 4.8).abs() < 0.01);
+	assert!(font.kerning('A', 'I') == 0.0);
+
+	// fallback fonts can still cache after they're added
+	let mut tt = TruetypeFont::from_bytes(gfx, bytes, 16).unwrap();
+	tt.cache_str("A").unwrap();
+	gfx.add_fallback_font(tt);
+	gfx.fallback_fonts_mut().for_each(|f| f.cache_str("AV").unwrap());
+	let tt = gfx.fallback_fonts_mut().next().unwrap();
+	assert!(tt.get('V').is_some());
+	assert!((tt.kerning('A', 'V') + 1.6).abs() < 0.01);
+
+	// a big "I" with its stem across the center
+	let draw = |gfx: &mut Gfx, t: shapes::Text| {
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.draw(&t);
+		}).unwrap();
+		return (canvas.read(vec2!(32, 32)), canvas.read(vec2!(4, 32)));
+	};
+
+	let (center, side) = draw(gfx, shapes::text("I").font(&font).size(200.0));
+	assert!(center.r > 0.9 && center.a > 0.9);
+	assert!(side.a < 0.1);
+
+	let (center, side) = draw(gfx, shapes::text("I").font(&font).size(200.0).outline(24.0, rgba!(1, 0, 0, 1)));
+	assert!(center.g > 0.9);
+	assert!(side.r > 0.9 && side.g < 0.1 && side.a > 0.9);
+
+	let (_, side) = draw(gfx, shapes::text("I").font(&font).size(200.0).glow(28.0, rgba!(0, 0, 1, 1)));
+	assert!(side.b > 0.05 && side.b < 0.9 && side.r < 0.01);
+
+	let (_, side) = draw(gfx, shapes::text("I").font(&font).size(200.0).shadow(vec2!(-28, 0), rgba!(0, 1, 0, 1)));
+	assert!(side.g > 0.9 && side.a > 0.9);
+
+}
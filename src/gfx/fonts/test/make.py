@@ -0,0 +1,145 @@
+#!/usr/bin/env python3
+# wengwengweng
+
+# builds test.ttf, a tiny font with straight line glyphs for the font tests
+#
+# - only a format 12 cmap, the 0xffff -> 0 entry every format 4 cmap ends with trips a debug check in fontdue 0.2
+# - a legacy "kern" table with A V pairs
+
+import struct
+
+UPEM = 1000
+ASCENT = 800
+DESCENT = -200
+
+# name, char, advance, contours
+GLYPHS = [
+	(".notdef", None, 500, []),
+	("space", " ", 250, []),
+	("A", "A", 600, [[(0, 0), (300, 700), (600, 0)]]),
+	("I", "I", 400, [[(100, 0), (100, 700), (300, 700), (300, 0)]]),
+	("V", "V", 600, [[(0, 700), (600, 700), (300, 0)]]),
+]
+
+KERN = [("A", "V", -100), ("V", "A", -100)]
+
+def glyph_id(name):
+	return [g[0] for g in GLYPHS].index(name)
+
+def bounds(contours):
+	pts = [p for c in contours for p in c]
+	if not pts:
+		return (0, 0, 0, 0)
+	xs = [p[0] for p in pts]
+	ys = [p[1] for p in pts]
+	return (min(xs), min(ys), max(xs), max(ys))
+
+def glyf_data(contours):
+	if not contours:
+		return b""
+	pts = [p for c in contours for p in c]
+	ends = []
+	n = 0
+	for c in contours:
+		n += len(c)
+		ends.append(n - 1)
+	data = struct.pack(">hhhhh", len(contours), *bounds(contours))
+	data += struct.pack(">%dH" % len(ends), *ends)
+	# no instructions, every point on curve with 16 bit deltas
+	data += struct.pack(">H", 0)
+	data += bytes([0x01] * len(pts))
+	prev = (0, 0)
+	xs = b""
+	ys = b""
+	for p in pts:
+		xs += struct.pack(">h", p[0] - prev[0])
+		ys += struct.pack(">h", p[1] - prev[1])
+		prev = p
+	data += xs + ys
+	if len(data) % 2:
+		data += b"\0"
+	return data
+
+def build():
+
+	glyfs = [glyf_data(g[3]) for g in GLYPHS]
+	all_bounds = bounds([c for g in GLYPHS for c in g[3]])
+	max_points = max(sum(len(c) for c in g[3]) for g in GLYPHS)
+	max_contours = max(len(g[3]) for g in GLYPHS)
+
+	glyf = b"".join(glyfs)
+	offsets = [0]
+	for g in glyfs:
+		offsets.append(offsets[-1] + len(g))
+	loca = struct.pack(">%dH" % len(offsets), *[o // 2 for o in offsets])
+
+	head = struct.pack(
+		">IIIIHHqqhhhhHHhhh",
+		0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UPEM,
+		0, 0,
+		*all_bounds,
+		0, 8, 2, 0, 0,
+	)
+
+	hhea = struct.pack(
+		">Ihhh H hhh hhh hhhh h H",
+		0x00010000, ASCENT, DESCENT, 0,
+		max(g[2] for g in GLYPHS),
+		0, 0, all_bounds[2],
+		1, 0, 0,
+		0, 0, 0, 0,
+		0, len(GLYPHS),
+	)
+
+	maxp = struct.pack(
+		">IHHHHHHHHHHHHHH",
+		0x00010000, len(GLYPHS), max_points, max_contours, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+	)
+
+	hmtx = b"".join(struct.pack(">Hh", g[2], bounds(g[3])[0]) for g in GLYPHS)
+
+	groups = sorted((ord(g[1]), i) for i, g in enumerate(GLYPHS) if g[1])
+	cmap12 = struct.pack(">HHIII", 12, 0, 16 + len(groups) * 12, 0, len(groups))
+	cmap12 += b"".join(struct.pack(">III", c, c, i) for c, i in groups)
+	cmap = struct.pack(">HH", 0, 1) + struct.pack(">HHI", 3, 10, 12) + cmap12
+
+	pairs = sorted((glyph_id(l), glyph_id(r), v) for l, r, v in KERN)
+	entry = 1 << (len(pairs).bit_length() - 1)
+	kern_sub = struct.pack(">HHHH", len(pairs), entry * 6, entry.bit_length() - 1, len(pairs) * 6 - entry * 6)
+	kern_sub += b"".join(struct.pack(">HHh", *p) for p in pairs)
+	kern = struct.pack(">HH", 0, 1) + struct.pack(">HHH", 0, 6 + len(kern_sub), 0x0001) + kern_sub
+
+	post = struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0)
+
+	tables = {
+		b"cmap": cmap,
+		b"glyf": glyf,
+		b"head": head,
+		b"hhea": hhea,
+		b"hmtx": hmtx,
+		b"kern": kern,
+		b"loca": loca,
+		b"maxp": maxp,
+		b"post": post,
+	}
+
+	def checksum(data):
+		data += b"\0" * (-len(data) % 4)
+		return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+	count = len(tables)
+	entry = 1 << (count.bit_length() - 1)
+	font = struct.pack(">IHHHH", 0x00010000, count, entry * 16, entry.bit_length() - 1, count * 16 - entry * 16)
+	offset = 12 + count * 16
+	body = b""
+
+	for tag in sorted(tables):
+		data = tables[tag]
+		font += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+		body += data + b"\0" * (-len(data) % 4)
+
+	return font + body
+
+if __name__ == "__main__":
+	with open("test.ttf", "wb") as f:
+		f.write(build())
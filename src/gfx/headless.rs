@@ -0,0 +1,402 @@
+// wengwengweng
+
+use std::path::Path;
+
+use super::*;
+
+/// Headless Graphics Context
+///
+/// renders without a window into a [`Canvas`](struct.Canvas.html), useful for tests and offline rendering
+///
+/// on linux it uses an EGL surfaceless context, which runs on software renderers like llvmpipe on machines without a GPU or a display server
+/// ```ignore
+/// let mut h = Headless::new(64, 64)?;
+///
+/// let img = h.render(|gfx| {
+///     gfx.draw(&shapes::rect(vec2!(-28), vec2!(-4)).fill(rgba!(1, 0, 0, 1)))?;
+///     gfx.draw(&shapes::circle(vec2!(16, -16), 12.0).fill(rgba!(0, 1, 0, 1)))?;
+///     gfx.draw(&shapes::line(vec2!(-28, 16), vec2!(28, 28)).color(rgba!(0, 0, 1, 1)).width(3.0))?;
+///     return Ok(());
+/// })?;
+///
+/// gfx::check_golden(&img, "misc/golden/shapes.png", 0.01)?;
+/// ```
+pub struct Headless {
+	canvas: Canvas,
+	gfx: Gfx,
+	// dropped last
+	_ctx: HeadlessContext,
+}
+
+impl Headless {
+
+	/// create a headless context that renders to a canvas with the given size
+	pub fn new(w: i32, h: i32) -> Result<Self> {
+		return Self::with_conf(&conf::Conf {
+			width: w,
+			height: h,
+			..Default::default()
+		});
+	}
+
+	/// create from a [`Conf`](../conf/struct.Conf.html), only graphics related fields are used
+	pub fn with_conf(conf: &conf::Conf) -> Result<Self> {
+
+		let ctx = HeadlessContext::new(conf)?;
+		let mut gfx = Gfx::from_gl(&ctx.gl, conf.width, conf.height, 1.0, conf)?;
+		let canvas = Canvas::multisampled(
+			&gfx,
+			conf.width,
+			conf.height,
+			conf.multi_sample.unwrap_or(0) as u8,
+			&[TextureConf::default()],
+		)?;
+
+		gfx.begin_frame();
+
+		return Ok(Self {
+			canvas: canvas,
+			gfx: gfx,
+			_ctx: ctx,
+		});
+
+	}
+
+	pub fn gfx(&mut self) -> &mut Gfx {
+		return &mut self.gfx;
+	}
+
+	/// the canvas everything is rendered to
+	pub fn canvas(&self) -> &Canvas {
+		return &self.canvas;
+	}
+
+	/// clear the canvas, draw a frame and capture it
+	pub fn render(&mut self, f: impl FnOnce(&mut Gfx) -> Result<()>) -> Result<img::Image> {
+
+		self.gfx.begin_frame();
+		self.gfx.draw_on(&self.canvas, CanvasAction::clear(), f)?;
+		self.gfx.end_frame();
+
+		unsafe {
+			self.gfx.gl().finish();
+		}
+
+		return self.canvas.capture();
+
+	}
+
+}
+
+/// compare an image against a reference image file, fails if any channel of any pixel differs by more than `tolerance` (0.0 - 1.0)
+///
+/// a missing reference is an error, set the `DIRTY_BLESS` environment variable to save the image as the new reference instead, on failure the image is saved next to the reference with a `.actual.png` suffix for inspection
+pub fn check_golden(img: &img::Image, path: impl AsRef<Path>, tolerance: f32) -> Result<()> {
+
+	let path = path.as_ref();
+
+	if std::env::var_os("DIRTY_BLESS").is_some() {
+		if let Some(dir) = path.parent() {
+			std::fs::create_dir_all(dir)
+				.map_err(|_| format!("failed to create dir {}", dir.display()))?;
+		}
+		return img.save(path);
+	}
+
+	if !path.exists() {
+		let actual = path.with_extension("actual.png");
+		img.save(&actual)?;
+		return Err(format!(
+			"reference {} doesn't exist, saved result to {}, run with DIRTY_BLESS=1 to accept it",
+			path.display(),
+			actual.display(),
+		));
+	}
+
+	let data = std::fs::read(path)
+		.map_err(|_| format!("failed to read {}", path.display()))?;
+	let reference = img::Image::from_bytes(&data)?;
+	let diff = img.diff(&reference)?;
+
+	if diff > tolerance {
+		let actual = path.with_extension("actual.png");
+		img.save(&actual)?;
+		return Err(format!(
+			"{} doesn't match reference (diff {} > {}), saved result to {}",
+			path.display(),
+			diff,
+			tolerance,
+			actual.display(),
+		));
+	}
+
+	return Ok(());
+
+}
+
+/// headless context for gl tests, fails the test if a context can't be created so a missing driver doesn't pass silently
+#[cfg(test)]
+pub(crate) fn test_headless(w: i32, h: i32) -> Headless {
+	return match Headless::new(w, h) {
+		Ok(h) => h,
+		Err(e) => panic!("failed to create headless gl context for tests: {}", e),
+	};
+}
+
+struct HeadlessContext {
+	gl: Rc<glow::Context>,
+	#[cfg(linux)]
+	egl: egl::Surfaceless,
+	#[cfg(not(linux))]
+	_ctx: glutin::Context<glutin::PossiblyCurrent>,
+	#[cfg(not(linux))]
+	_event_loop: glutin::event_loop::EventLoop<()>,
+}
+
+impl HeadlessContext {
+
+	#[cfg(linux)]
+	fn new(_: &conf::Conf) -> Result<Self> {
+
+		let egl = egl::Surfaceless::new()?;
+
+		let gl = unsafe { glow::Context::from_loader_function(|s| {
+			return egl.get_proc_address(s);
+		}) };
+
+		return Ok(Self {
+			gl: Rc::new(gl),
+			egl: egl,
+		});
+
+	}
+
+	#[cfg(not(linux))]
+	fn new(conf: &conf::Conf) -> Result<Self> {
+
+		let event_loop = glutin::event_loop::EventLoop::new();
+
+		let ctx = unsafe {
+			glutin::ContextBuilder::new()
+				.with_multisampling(conf.multi_sample.unwrap_or(0))
+				.with_gl(glutin::GlRequest::GlThenGles {
+					opengl_version: (2, 1),
+					opengles_version: (2, 0),
+				})
+				.build_headless(&event_loop, glutin::dpi::PhysicalSize::new(conf.width as u32, conf.height as u32))
+				.map_err(|_| format!("failed to build headless context"))?
+				.make_current()
+				.map_err(|_| format!("failed to make opengl context"))?
+		};
+
+		let gl = unsafe { glow::Context::from_loader_function(|s| {
+			return ctx.get_proc_address(s) as *const _;
+		}) };
+
+		return Ok(Self {
+			gl: Rc::new(gl),
+			_ctx: ctx,
+			_event_loop: event_loop,
+		});
+
+	}
+
+}
+
+#[cfg(linux)]
+impl Drop for HeadlessContext {
+	fn drop(&mut self) {
+		self.egl.destroy();
+	}
+}
+
+// EGL is loaded at runtime so it's not a link dependency for normal windowed apps
+#[cfg(linux)]
+mod egl {
+
+	use std::ffi::CString;
+	use std::os::raw::c_char;
+	use std::os::raw::c_void;
+
+	use crate::Result;
+
+	type Display = *mut c_void;
+	type Ctx = *mut c_void;
+	type Surface = *mut c_void;
+	type Config = *mut c_void;
+
+	const PLATFORM_SURFACELESS_MESA: u32 = 0x31dd;
+	const OPENGL_API: u32 = 0x30a2;
+	const OPENGL_ES_API: u32 = 0x30a0;
+	const CONTEXT_CLIENT_VERSION: i32 = 0x3098;
+	const NONE: i32 = 0x3038;
+
+	type GetProcAddress = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+	type GetPlatformDisplay = unsafe extern "C" fn(u32, *mut c_void, *const i32) -> Display;
+	type GetDisplay = unsafe extern "C" fn(*mut c_void) -> Display;
+	type Initialize = unsafe extern "C" fn(Display, *mut i32, *mut i32) -> u32;
+	type BindAPI = unsafe extern "C" fn(u32) -> u32;
+	type CreateContext = unsafe extern "C" fn(Display, Config, Ctx, *const i32) -> Ctx;
+	type MakeCurrent = unsafe extern "C" fn(Display, Surface, Surface, Ctx) -> u32;
+	type DestroyContext = unsafe extern "C" fn(Display, Ctx) -> u32;
+	type Terminate = unsafe extern "C" fn(Display) -> u32;
+
+	pub struct Surfaceless {
+		lib: libloading::Library,
+		get_proc_address: GetProcAddress,
+		display: Display,
+		ctx: Ctx,
+	}
+
+	impl Surfaceless {
+
+		pub fn new() -> Result<Self> {
+
+			unsafe {
+
+				let lib = libloading::Library::new("libEGL.so.1")
+					.or_else(|_| libloading::Library::new("libEGL.so"))
+					.map_err(|_| format!("failed to load libEGL"))?;
+
+				macro_rules! sym {
+					($name:expr, $ty:ty) => {
+						*lib.get::<$ty>($name)
+							.map_err(|_| format!("failed to load egl function {}", String::from_utf8_lossy($name)))?
+					}
+				}
+
+				let get_proc_address = sym!(b"eglGetProcAddress\0", GetProcAddress);
+				let get_display = sym!(b"eglGetDisplay\0", GetDisplay);
+				let initialize = sym!(b"eglInitialize\0", Initialize);
+				let bind_api = sym!(b"eglBindAPI\0", BindAPI);
+				let create_context = sym!(b"eglCreateContext\0", CreateContext);
+				let make_current = sym!(b"eglMakeCurrent\0", MakeCurrent);
+
+				let get_platform_display = get_proc_address(b"eglGetPlatformDisplayEXT\0".as_ptr() as *const c_char);
+
+				// prefer the surfaceless platform, which doesn't need a display server
+				let display = if get_platform_display.is_null() {
+					get_display(std::ptr::null_mut())
+				} else {
+					let f: GetPlatformDisplay = std::mem::transmute(get_platform_display);
+					let d = f(PLATFORM_SURFACELESS_MESA, std::ptr::null_mut(), std::ptr::null());
+					if d.is_null() {
+						get_display(std::ptr::null_mut())
+					} else {
+						d
+					}
+				};
+
+				if display.is_null() {
+					return Err(format!("failed to get egl display"));
+				}
+
+				let (mut major, mut minor) = (0, 0);
+
+				if initialize(display, &mut major, &mut minor) == 0 {
+					return Err(format!("failed to initialize egl"));
+				}
+
+				// try desktop gl first, then gles 2
+				let mut ctx = std::ptr::null_mut();
+
+				if bind_api(OPENGL_API) != 0 {
+					ctx = create_context(display, std::ptr::null_mut(), std::ptr::null_mut(), [NONE].as_ptr());
+				}
+
+				if ctx.is_null() && bind_api(OPENGL_ES_API) != 0 {
+					ctx = create_context(
+						display,
+						std::ptr::null_mut(),
+						std::ptr::null_mut(),
+						[CONTEXT_CLIENT_VERSION, 2, NONE].as_ptr(),
+					);
+				}
+
+				if ctx.is_null() {
+					return Err(format!("failed to create egl context"));
+				}
+
+				if make_current(display, std::ptr::null_mut(), std::ptr::null_mut(), ctx) == 0 {
+					return Err(format!("failed to make egl context current"));
+				}
+
+				return Ok(Self {
+					lib: lib,
+					get_proc_address: get_proc_address,
+					display: display,
+					ctx: ctx,
+				});
+
+			}
+
+		}
+
+		pub fn get_proc_address(&self, s: &str) -> *const c_void {
+			let name = match CString::new(s) {
+				Ok(n) => n,
+				Err(_) => return std::ptr::null(),
+			};
+			return unsafe { (self.get_proc_address)(name.as_ptr()) as *const c_void };
+		}
+
+		pub fn destroy(&mut self) {
+
+			unsafe {
+
+				if let Ok(make_current) = self.lib.get::<MakeCurrent>(b"eglMakeCurrent\0") {
+					make_current(self.display, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut());
+				}
+
+				if let Ok(destroy_context) = self.lib.get::<DestroyContext>(b"eglDestroyContext\0") {
+					destroy_context(self.display, self.ctx);
+				}
+
+				if let Ok(terminate) = self.lib.get::<Terminate>(b"eglTerminate\0") {
+					terminate(self.display);
+				}
+
+			}
+
+		}
+
+	}
+
+}
+
+#[cfg(test)]
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/misc/golden");
+
+#[test]
+fn golden() {
+
+	let mut h = test_headless(64, 64);
+
+	let check = |name: &str, img: img::Image| {
+		check_golden(&img, format!("{}/{}.png", GOLDEN_DIR, name), 0.01).unwrap();
+	};
+
+	check("shapes", h.render(|gfx| {
+		gfx.draw(&shapes::rect(vec2!(-28), vec2!(-4)).fill(rgba!(1, 0, 0, 1)))?;
+		gfx.draw(&shapes::circle(vec2!(16, -16), 12.0).fill(rgba!(0, 1, 0, 1)))?;
+		gfx.draw(&shapes::line(vec2!(-28, 16), vec2!(28, 28)).color(rgba!(0, 0, 1, 1)).width(3.0))?;
+		return Ok(());
+	}).unwrap());
+
+	check("transform", h.render(|gfx| {
+		gfx.draw_t(
+			mat4!()
+				.t2(vec2!(8, 8))
+				.rz(f32::to_radians(45.0))
+				.s2(vec2!(2)),
+			&shapes::rect(vec2!(-8), vec2!(8)).fill(rgba!(1, 1, 0, 1)),
+		)?;
+		return Ok(());
+	}).unwrap());
+
+	check("text", h.render(|gfx| {
+		gfx.draw(&shapes::text("hi").size(24.0).color(rgba!(1, 0, 1, 1)))?;
+		return Ok(());
+	}).unwrap());
+
+}
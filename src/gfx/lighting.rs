@@ -0,0 +1,335 @@
+// wengwengweng
+
+use super::*;
+
+/// Max Number of Lights in a [`Lighting`](struct.Lighting.html)
+pub const MAX_LIGHTS: usize = 8;
+
+struct LightNames {
+	kind: &'static str,
+	pos: &'static str,
+	dir: &'static str,
+	color: &'static str,
+	intensity: &'static str,
+	range: &'static str,
+	inner: &'static str,
+	outer: &'static str,
+}
+
+macro_rules! light_names {
+	($($i:literal),*$(,)?) => {
+		[$(LightNames {
+			kind: concat!("u_lights[", $i, "].kind"),
+			pos: concat!("u_lights[", $i, "].pos"),
+			dir: concat!("u_lights[", $i, "].dir"),
+			color: concat!("u_lights[", $i, "].color"),
+			intensity: concat!("u_lights[", $i, "].intensity"),
+			range: concat!("u_lights[", $i, "].range"),
+			inner: concat!("u_lights[", $i, "].inner"),
+			outer: concat!("u_lights[", $i, "].outer"),
+		}),*]
+	}
+}
+
+const LIGHT_NAMES: [LightNames; MAX_LIGHTS] = light_names![0, 1, 2, 3, 4, 5, 6, 7];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+	Directional,
+	Point,
+	Spot,
+}
+
+/// A Light Source
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+	pub kind: LightKind,
+	pub pos: Vec3,
+	pub dir: Vec3,
+	pub color: Color,
+	pub intensity: f32,
+	/// distance where point & spot lights fade out, 0 for no limit
+	pub range: f32,
+	/// spot light cone angles in radians, full intensity inside inner
+	pub inner: f32,
+	pub outer: f32,
+	/// cast shadows with [`Shadows`](struct.Shadows.html), directional and spot lights only
+	pub shadow: bool,
+}
+
+impl Light {
+
+	fn new(kind: LightKind) -> Self {
+		return Self {
+			kind: kind,
+			pos: vec3!(0),
+			dir: vec3!(0, -1, 0),
+			color: rgba!(1),
+			intensity: 1.0,
+			range: 0.0,
+			inner: 0.0,
+			outer: 0.0,
+			shadow: false,
+		};
+	}
+
+	/// light from a direction, like the sun
+	pub fn directional(dir: Vec3) -> Self {
+		return Self {
+			dir: dir.unit(),
+			..Self::new(LightKind::Directional)
+		};
+	}
+
+	/// light from a point to all directions
+	pub fn point(pos: Vec3) -> Self {
+		return Self {
+			pos: pos,
+			..Self::new(LightKind::Point)
+		};
+	}
+
+	/// light from a point inside a cone
+	pub fn spot(pos: Vec3, dir: Vec3, angle: f32) -> Self {
+		return Self {
+			pos: pos,
+			dir: dir.unit(),
+			inner: angle * 0.8,
+			outer: angle,
+			..Self::new(LightKind::Spot)
+		};
+	}
+
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+
+	pub fn intensity(mut self, i: f32) -> Self {
+		self.intensity = i;
+		return self;
+	}
+
+	pub fn range(mut self, r: f32) -> Self {
+		self.range = r;
+		return self;
+	}
+
+	pub fn cone(mut self, inner: f32, outer: f32) -> Self {
+		self.inner = inner;
+		self.outer = outer;
+		return self;
+	}
+
+	pub fn shadow(mut self, b: bool) -> Self {
+		self.shadow = b;
+		return self;
+	}
+
+}
+
+/// Lighting Model
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shading {
+	BlinnPhong,
+	PBR,
+}
+
+/// Lights & Settings for [`Gfx::use_lighting`](struct.Gfx.html#method.use_lighting)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+	pub shading: Shading,
+	pub ambient: Color,
+	pub lights: Vec<Light>,
+}
+
+impl Default for Lighting {
+	fn default() -> Self {
+		return Self {
+			shading: Shading::PBR,
+			ambient: rgba!(0.1, 0.1, 0.1, 1),
+			lights: vec![],
+		};
+	}
+}
+
+impl Lighting {
+
+	pub fn new(shading: Shading) -> Self {
+		return Self {
+			shading: shading,
+			..Self::default()
+		};
+	}
+
+	pub fn ambient(mut self, c: Color) -> Self {
+		self.ambient = c;
+		return self;
+	}
+
+	pub fn light(mut self, l: Light) -> Self {
+		self.lights.push(l);
+		return self;
+	}
+
+}
+
+impl UniformLayout for Lighting {
+
+	fn data(&self) -> Vec<(&'static str, UniformData)> {
+
+		let shading = match self.shading {
+			Shading::BlinnPhong => 0,
+			Shading::PBR => 1,
+		};
+
+		let mut values = vec![
+			("u_shading", UniformData::Int(shading)),
+			("u_ambient", UniformData::Vec3(self.ambient.rgb())),
+			("u_light_count", UniformData::Int(self.lights.len().min(MAX_LIGHTS) as i32)),
+		];
+
+		for (l, names) in self.lights.iter().zip(LIGHT_NAMES.iter()) {
+
+			let kind = match l.kind {
+				LightKind::Directional => 0,
+				LightKind::Point => 1,
+				LightKind::Spot => 2,
+			};
+
+			values.extend(vec![
+				(names.kind, UniformData::Int(kind)),
+				(names.pos, UniformData::Vec3(l.pos)),
+				(names.dir, UniformData::Vec3(l.dir)),
+				(names.color, UniformData::Vec3(l.color.rgb())),
+				(names.intensity, UniformData::Float(l.intensity)),
+				(names.range, UniformData::Float(l.range)),
+				(names.inner, UniformData::Float(l.inner.cos())),
+				(names.outer, UniformData::Float(l.outer.cos())),
+			]);
+
+		}
+
+		// meshes without a material use the default one
+		values.extend(Material::default().data());
+
+		return values;
+
+	}
+
+}
+
+impl Gfx {
+
+	/// draw everything inside with lights, see [`Material`](struct.Material.html) for surface properties
+	///
+	/// uses the built-in lit shader, normals come from vertex normals (and normal maps)
+	pub fn use_lighting(
+		&mut self,
+		lighting: &Lighting,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		if lighting.lights.len() > MAX_LIGHTS {
+			return Err(format!("too many lights: {}, max {}", lighting.lights.len(), MAX_LIGHTS));
+		}
+
+		// pick & shadow passes use their own shader
+		if self.picking || self.shadow_pass {
+			return f(self);
+		}
+
+		let pipeline = self.lit_pipeline.clone();
+
+		return self.draw_with_data(&pipeline, lighting.data(), f);
+
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn lighting() {
+
+	let mut h = test_headless(32, 32);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 32, 32).unwrap();
+	let cube = Mesh::from_meshdata(gfx, &geom::meshgen::cube()).unwrap();
+
+	let draw = |gfx: &mut Gfx, lighting: &Lighting, mat: &Material| {
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.use_lighting(lighting, |gfx| {
+				return gfx.draw_t(
+					mat4!().s3(vec3!(32)),
+					&shapes::mesh(&cube).material(mat),
+				);
+			});
+		}).unwrap();
+		return canvas.read(vec2!(16, 16));
+	};
+
+	let mat = Material::default();
+	let dark = draw(gfx, &Lighting::default().ambient(rgba!(0, 0, 0, 1)), &mat);
+
+	assert!(dark.r < 0.05);
+
+	for shading in &[Shading::BlinnPhong, Shading::PBR] {
+
+		let lit = Lighting::new(*shading)
+			.ambient(rgba!(0, 0, 0, 1))
+			.light(Light::directional(vec3!(0, 0, -1)).color(rgba!(1, 0, 0, 1)));
+
+		let c = draw(gfx, &lit, &mat);
+
+		assert!(c.r > 0.5);
+		assert!(c.g < 0.05);
+
+		// light from behind
+		let back = Lighting {
+			lights: vec![Light::directional(vec3!(0, 0, 1))],
+			..lit.clone()
+		};
+
+		assert!(draw(gfx, &back, &mat).r < 0.05);
+
+		// out of range
+		let far = Lighting {
+			lights: vec![Light::point(vec3!(0, 0, 100)).range(10.0)],
+			..lit.clone()
+		};
+
+		assert!(draw(gfx, &far, &mat).r < 0.05);
+
+	}
+
+	// a stretched face still faces the light, only the model matrix would tilt its normal to the side
+	gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+		let lit = Lighting::new(Shading::BlinnPhong)
+			.ambient(rgba!(0, 0, 0, 1))
+			.light(Light::directional(vec3!(0, 0, -1)).color(rgba!(1, 0, 0, 1)));
+		return gfx.use_lighting(&lit, |gfx| {
+			return gfx.draw_t(
+				mat4!().s3(vec3!(32, 32, 4)).ry(f32::to_radians(30.0)),
+				&shapes::mesh(&cube).material(&mat),
+			);
+		});
+	}).unwrap();
+
+	assert!(canvas.read(vec2!(16, 16)).r > 0.8);
+
+	let glow = Material {
+		emissive: rgba!(0, 0, 1, 1),
+		..Material::default()
+	};
+
+	assert!(draw(gfx, &Lighting::default().ambient(rgba!(0, 0, 0, 1)), &glow).b > 0.9);
+
+	let too_many = Lighting {
+		lights: vec![Light::point(vec3!(0)); MAX_LIGHTS + 1],
+		..Lighting::default()
+	};
+
+	assert!(gfx.use_lighting(&too_many, |_| Ok(())).is_err());
+
+}
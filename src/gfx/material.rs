@@ -0,0 +1,244 @@
+// wengwengweng
+
+use super::*;
+
+/// Data for Creating [`Material`](struct.Material.html)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MaterialData {
+	pub name: Option<String>,
+	pub base_color: Color,
+	pub base_color_img: Option<img::Image>,
+	pub metallic: f32,
+	pub roughness: f32,
+	pub metallic_roughness_img: Option<img::Image>,
+	pub normal_img: Option<img::Image>,
+	pub normal_scale: f32,
+	pub emissive: Color,
+	pub emissive_img: Option<img::Image>,
+	pub occlusion_img: Option<img::Image>,
+	pub occlusion_strength: f32,
+	pub ambient: Color,
+	pub specular: Option<Color>,
+}
+
+impl Default for MaterialData {
+	fn default() -> Self {
+		return Self {
+			name: None,
+			base_color: rgba!(1),
+			base_color_img: None,
+			metallic: 0.0,
+			roughness: 0.5,
+			metallic_roughness_img: None,
+			normal_img: None,
+			normal_scale: 1.0,
+			emissive: rgba!(0, 0, 0, 1),
+			emissive_img: None,
+			occlusion_img: None,
+			occlusion_strength: 1.0,
+			ambient: rgba!(1),
+			specular: None,
+		};
+	}
+}
+
+/// Surface Properties for [`Gfx::use_lighting`](struct.Gfx.html#method.use_lighting)
+///
+/// follows the glTF metallic-roughness model, metallic is read from the blue channel and roughness from the green channel of `metallic_roughness_tex`, both multiplied by the factors
+#[derive(Clone, PartialEq)]
+pub struct Material {
+	pub name: Option<String>,
+	pub base_color: Color,
+	pub base_color_tex: Option<Texture>,
+	pub metallic: f32,
+	pub roughness: f32,
+	pub metallic_roughness_tex: Option<Texture>,
+	pub normal_tex: Option<Texture>,
+	pub normal_scale: f32,
+	pub emissive: Color,
+	pub emissive_tex: Option<Texture>,
+	pub occlusion_tex: Option<Texture>,
+	pub occlusion_strength: f32,
+	/// multiplies the scene ambient light
+	pub ambient: Color,
+	/// specular color for [`Shading::BlinnPhong`](enum.Shading.html#variant.BlinnPhong), derived from metallic if not set
+	pub specular: Option<Color>,
+}
+
+impl Default for Material {
+	fn default() -> Self {
+		let d = MaterialData::default();
+		return Self {
+			name: None,
+			base_color: d.base_color,
+			base_color_tex: None,
+			metallic: d.metallic,
+			roughness: d.roughness,
+			metallic_roughness_tex: None,
+			normal_tex: None,
+			normal_scale: d.normal_scale,
+			emissive: d.emissive,
+			emissive_tex: None,
+			occlusion_tex: None,
+			occlusion_strength: d.occlusion_strength,
+			ambient: d.ambient,
+			specular: d.specular,
+		};
+	}
+}
+
+impl Material {
+
+	/// create material from [`MaterialData`](struct.MaterialData.html)
+	pub fn from_data(ctx: &impl GLCtx, data: MaterialData) -> Result<Self> {
+
+		let tex = |img: Option<img::Image>| -> Result<Option<Texture>> {
+			return img
+				.map(|img| Texture::from_img_with_conf(ctx, img, TextureConf {
+					filter: FilterMode::Linear,
+					mipmap_filter: Some(FilterMode::Linear),
+					wrap_x: WrapMode::Repeat,
+					wrap_y: WrapMode::Repeat,
+					..TextureConf::default()
+				}))
+				.transpose();
+		};
+
+		return Ok(Self {
+			name: data.name,
+			base_color: data.base_color,
+			base_color_tex: tex(data.base_color_img)?,
+			metallic: data.metallic,
+			roughness: data.roughness,
+			metallic_roughness_tex: tex(data.metallic_roughness_img)?,
+			normal_tex: tex(data.normal_img)?,
+			normal_scale: data.normal_scale,
+			emissive: data.emissive,
+			emissive_tex: tex(data.emissive_img)?,
+			occlusion_tex: tex(data.occlusion_img)?,
+			occlusion_strength: data.occlusion_strength,
+			ambient: data.ambient,
+			specular: data.specular,
+		});
+
+	}
+
+}
+
+// base color goes through u_color and u_tex so unlit shaders get it too
+impl UniformLayout for Material {
+
+	fn data(&self) -> Vec<(&'static str, UniformData)> {
+
+		let mut values = vec![
+			("u_mat_metallic", UniformData::Float(self.metallic)),
+			("u_mat_roughness", UniformData::Float(self.roughness)),
+			("u_mat_normal_scale", UniformData::Float(self.normal_scale)),
+			("u_mat_occlusion_strength", UniformData::Float(self.occlusion_strength)),
+			("u_mat_emissive", UniformData::Vec3(self.emissive.rgb())),
+			("u_mat_ambient", UniformData::Vec3(self.ambient.rgb())),
+			("u_mat_has_specular", UniformData::Int(self.specular.is_some() as i32)),
+			("u_mat_specular", UniformData::Vec3(self.specular.unwrap_or(rgba!(1)).rgb())),
+		];
+
+		let texs = [
+			("u_mat_has_mr_tex", "u_mat_mr_tex", &self.metallic_roughness_tex),
+			("u_mat_has_normal_tex", "u_mat_normal_tex", &self.normal_tex),
+			("u_mat_has_emissive_tex", "u_mat_emissive_tex", &self.emissive_tex),
+			("u_mat_has_occlusion_tex", "u_mat_occlusion_tex", &self.occlusion_tex),
+		];
+
+		for (flag, name, tex) in texs.iter() {
+			values.push((flag, UniformData::Int(tex.is_some() as i32)));
+			if let Some(tex) = tex {
+				values.push((name, UniformData::Texture(tex.clone())));
+			}
+		}
+
+		return values;
+
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn obj_materials() {
+
+	let obj = "
+mtllib scene.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+o left
+usemtl red
+f 1/1 2/2 3/3
+o right
+usemtl blue
+f 1/1 3/3 4/4
+";
+
+	let mtl = "
+newmtl red
+Kd 1 0 0
+Ka 0.5 0.5 0.5
+Ks 0 0 0
+Ns 10
+map_Kd -bm 1.0 tex\\red.png
+
+newmtl blue
+Kd 0 0 1
+Ks 1 1 1
+d 0.5
+map_Kd blue.png
+
+newmtl green
+Kd 0 1 0
+";
+
+	let png = |w| img::Image::new(w, 1).to_png().unwrap();
+
+	let data = Model::load_obj_with(obj, |path| {
+		return match path {
+			"scene.mtl" => Some(mtl.as_bytes().to_vec()),
+			"tex/red.png" => Some(png(1)),
+			"blue.png" => Some(png(2)),
+			_ => None,
+		};
+	}).unwrap();
+
+	let mats = &data.materials;
+	let red = &mats[data.nodes[&0].meshes[0].material.unwrap()];
+	let blue = &mats[data.nodes[&1].meshes[0].material.unwrap()];
+
+	let green = mats.iter().find(|m| m.name.as_deref() == Some("green")).unwrap();
+
+	assert_eq!(mats.len(), 3);
+	assert_eq!(data.nodes[&0].name.as_deref(), Some("left"));
+	assert_eq!(red.base_color, rgba!(1, 0, 0, 1));
+	assert_eq!(red.ambient, rgba!(0.5, 0.5, 0.5, 1));
+	// explicitly black is not the same as unset
+	assert_eq!(red.specular, Some(rgba!(0, 0, 0, 1)));
+	assert_eq!(green.specular, None);
+	assert_eq!(red.base_color_img.as_ref().map(|i| i.width()), Some(1));
+	assert_eq!(blue.base_color, rgba!(0, 0, 1, 0.5));
+	assert_eq!(blue.ambient, rgba!(1));
+	assert_eq!(blue.specular, Some(rgba!(1)));
+	assert_eq!(blue.base_color_img.as_ref().map(|i| i.width()), Some(2));
+	assert!(red.roughness < blue.roughness);
+
+	// materials survive an obj round trip
+	let (obj, mtl) = data.to_obj("scene");
+	let back = Model::load_obj(&obj, Some(&mtl), None).unwrap();
+
+	assert_eq!(back.materials.len(), 3);
+	assert_eq!(back.materials[1].base_color, rgba!(0, 0, 1, 0.5));
+	assert!((back.materials[0].roughness - red.roughness).abs() < 0.01);
+	assert!(mtl.contains("map_Kd scene_1.png"));
+
+}
@@ -14,6 +14,9 @@ pub struct MeshData {
 pub struct Mesh {
 	vbuf: VertexBuffer<Vertex>,
 	ibuf: IndexBuffer,
+	skin_buf: Option<VertexBuffer<SkinVertex>>,
+	morph_bufs: Vec<VertexBuffer<MorphVertex>>,
+	vert_count: usize,
 	count: usize,
 }
 
@@ -28,11 +31,45 @@ impl Mesh {
 		return Ok(Self {
 			vbuf,
 			ibuf,
+			skin_buf: None,
+			morph_bufs: vec![],
+			vert_count: verts.len(),
 			count: indices.len(),
 		});
 
 	}
 
+	/// create a mesh with joints & weights for each vertex, drawn with [`shapes::Mesh::joints`](shapes/struct.Mesh.html#method.joints)
+	pub fn with_skin(ctx: &impl GLCtx, verts: &[Vertex], indices: &[u32], skin: &[SkinVertex]) -> Result<Self> {
+
+		if skin.len() != verts.len() {
+			return Err(format!("expected {} skin vertices, found {}", verts.len(), skin.len()));
+		}
+
+		return Ok(Self {
+			skin_buf: Some(VertexBuffer::<SkinVertex>::from(ctx, &skin)?),
+			..Self::new(ctx, verts, indices)?
+		});
+
+	}
+
+	/// add a morph target with an offset for each vertex, blended with [`shapes::Mesh::morph_weights`](shapes/struct.Mesh.html#method.morph_weights), up to [`MAX_MORPH_TARGETS`](constant.MAX_MORPH_TARGETS.html)
+	pub fn add_morph_target(&mut self, ctx: &impl GLCtx, target: &[MorphVertex]) -> Result<()> {
+
+		if self.morph_bufs.len() >= MAX_MORPH_TARGETS {
+			return Err(format!("too many morph targets, max {}", MAX_MORPH_TARGETS));
+		}
+
+		if target.len() != self.vert_count {
+			return Err(format!("expected {} morph target vertices, found {}", self.vert_count, target.len()));
+		}
+
+		self.morph_bufs.push(VertexBuffer::<MorphVertex>::from(ctx, &target)?);
+
+		return Ok(());
+
+	}
+
 	/// create a mesh from [`MeshData`](struct.MeshData.html)
 	pub fn from_meshdata(ctx: &impl GLCtx, data: &MeshData) -> Result<Self> {
 		return Self::new(ctx, &data.vertices, &data.indices);
@@ -46,9 +83,120 @@ impl Mesh {
 		return &self.ibuf;
 	}
 
+	pub(super) fn skin_buf(&self) -> Option<&VertexBuffer<SkinVertex>> {
+		return self.skin_buf.as_ref();
+	}
+
+	pub(super) fn morph_buf(&self, i: usize) -> Option<&VertexBuffer<MorphVertex>> {
+		return self.morph_bufs.get(i);
+	}
+
+	/// number of morph targets
+	pub fn morph_targets(&self) -> usize {
+		return self.morph_bufs.len();
+	}
+
+	/// if the mesh has skinning data
+	pub fn skinned(&self) -> bool {
+		return self.skin_buf.is_some();
+	}
+
 	pub(super) fn count(&self) -> usize {
 		return self.count;
 	}
 
 }
 
+impl Gfx {
+
+	// upload joint matrices for skinning, a row of 4 texels per joint, grows to fit larger skins
+	pub(super) fn joint_tex(&mut self, joints: &[Mat4]) -> Result<Texture> {
+
+		let rows = joints.len().max(1).next_power_of_two() as i32;
+
+		let tex = match &self.joint_tex {
+			Some(tex) if tex.height() >= rows => tex.clone(),
+			_ => {
+				let tex = Texture::new_with_conf(self, 4, rows, TextureConf {
+					format: TextureFormat::RGBA32F,
+					..TextureConf::default()
+				})?;
+				self.joint_tex = Some(tex.clone());
+				tex
+			},
+		};
+
+		let data = joints
+			.iter()
+			.flat_map(|m| m.as_arr().to_vec())
+			.flat_map(|f| f.to_ne_bytes().to_vec())
+			.collect::<Vec<u8>>();
+
+		tex.sub_data(0, 0, 4, joints.len() as i32, &data);
+
+		return Ok(tex);
+
+	}
+
+}
+
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn morph() {
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 64, 64).unwrap();
+
+	let vert = |x: f32, y: f32| {
+		return Vertex {
+			pos: vec3!(x, y, 0),
+			normal: vec3!(0, 0, 1),
+			uv: vec2!(0),
+			color: rgba!(1),
+		};
+	};
+
+	let verts = [vert(-8.0, -8.0), vert(8.0, -8.0), vert(8.0, 8.0), vert(-8.0, 8.0)];
+	let mut mesh = Mesh::new(gfx, &verts, &[0, 1, 2, 0, 2, 3]).unwrap();
+
+	let offset = |x: f32| {
+		return vec![MorphVertex {
+			pos: vec3!(x, 0, 0),
+			normal: vec3!(0),
+		}; 4];
+	};
+
+	assert!(mesh.add_morph_target(gfx, &[]).is_err());
+
+	// only the last one moves the quad
+	for _ in 0..MAX_MORPH_TARGETS - 1 {
+		mesh.add_morph_target(gfx, &offset(0.0)).unwrap();
+	}
+
+	mesh.add_morph_target(gfx, &offset(16.0)).unwrap();
+
+	assert!(mesh.add_morph_target(gfx, &offset(0.0)).is_err());
+
+	let mut draw = |weights: &[f32]| {
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.draw(&shapes::mesh(&mesh).morph_weights(weights));
+		}).unwrap();
+		return (canvas.read(vec2!(28, 32)).r, canvas.read(vec2!(44, 32)).r);
+	};
+
+	let (left, right) = draw(&[]);
+
+	assert!(left > 0.9 && right < 0.1);
+
+	let (left, right) = draw(&[0.1, 0.1, 0.1, 1.0]);
+
+	assert!(left < 0.1 && right > 0.9);
+
+	let (left, right) = draw(&[0.0, 0.0, 0.0, 0.5]);
+
+	assert!(left < 0.1 && right > 0.9);
+
+}
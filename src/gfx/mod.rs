@@ -22,6 +22,27 @@
 //! )?;
 //! ```
 //!
+//! [`SdfFont`](struct.SdfFont.html) stores glyphs as signed distance fields, so text stays crisp at any size and can have outlines and glow:
+//! ```ignore
+//! let font = SdfFont::from_bytes(gfx, include_bytes!("res/font.ttf"), 48.0, 6.0)?;
+//!
+//! gfx.draw(
+//!     &shapes::text("hi")
+//!         .font(&font)
+//!         .size(120.0)
+//!         .outline(4.0, rgba!(0, 0, 0, 1))
+//!         .shadow(vec2!(4, -4), rgba!(0, 0, 0, 0.5))
+//!         ,
+//! )?;
+//! ```
+//!
+//! Text is laid out with kerning, arabic shaping and bidirectional reordering. Chars missing from a font are taken from fallback fonts, added per text with [`Text::fallback`](shapes/struct.Text.html#method.fallback) or for all text with [`add_fallback_font`](struct.Gfx.html#method.add_fallback_font):
+//! ```ignore
+//! let mut cjk = TruetypeFont::from_bytes(gfx, include_bytes!("res/cjk.ttf"), 16)?;
+//! cjk.cache_str("日本語")?;
+//! gfx.add_fallback_font(cjk);
+//! ```
+//!
 //! You can transform objects with [`draw_t`](struct.Gfx.html#method.draw_t):
 //! ```ignore
 //! gfx.draw_t(
@@ -70,10 +91,14 @@
 //! ```
 //! Canvases can be used for a lot of things: post-processing, screenshots, ...
 //!
+//! For post-processing, [`PostProcess`](struct.PostProcess.html) manages the canvases and runs a list of [`Pass`](trait.Pass.html)es like [`Bloom`](struct.Bloom.html) or [`Crt`](struct.Crt.html)
+//!
 //! note that binding to a canvas resets the projection & view matrix, you may want to rebind your camera in a canvas call
 //!
 //! also remember to resize canvas when window resizes if you have a fullscreen canvas, and recreate canvas when window DPI changes
 //!
+//! [`Headless`](struct.Headless.html) renders to a canvas without a window, use it with [`check_golden`](fn.check_golden.html) for image tests
+//!
 //! ## Camera
 //!
 //! Cameras implement the [`Camera`](trait.Camera.html) trait, which lets you define your own projection and view matrix.
@@ -99,6 +124,22 @@
 //! })?;
 //! ```
 //!
+//! For bigger 3d worlds, [`Scene`](struct.Scene.html) keeps a node hierarchy with cached world transforms, and skips nodes outside of the camera frustum
+//!
+//! ```ignore
+//! // init
+//! let level = scene.add(SceneNode::with(level_model));
+//! let door = scene.add_child(level, SceneNode::with(door_model).pos(vec3!(4, 0, 0)))?;
+//!
+//! // update
+//! scene.get_mut(door).unwrap().transform.rot = rot;
+//!
+//! // draw
+//! scene.draw(gfx, &cam)?;
+//! ```
+//!
+//! [`shapes::skybox`](shapes/fn.skybox.html) draws a [`CubeTexture`](struct.CubeTexture.html) behind everything with the current camera rotation
+//!
 //! ## Shader
 //!
 //! Use [`Shader`](struct.Shader.html) to create custom shaders. It requires a type that implements [`UniformLayout`](trait.UniformLayout.html), a minimal example:
@@ -145,6 +186,7 @@
 //! | varing  | vec3      | v_normal      | vertex normal                   | all        |
 //! | varing  | vec2      | v_uv          | vertex texture coord            | all        |
 //! | varing  | vec4      | v_color       | vertex color                    | all        |
+//! | varing  | vec3      | v_world_pos   | world position after skinning & morphing | frag |
 //! | uniform | mat4      | u_model       | uniform model matrix            | vert       |
 //! | uniform | mat4      | u_proj        | uniform projection matrix       | vert       |
 //! | uniform | mat4      | u_view        | uniform view matrix             | vert       |
@@ -153,15 +195,22 @@
 //! | uniform | vec4      | u_color       | uniform color                   | frag       |
 //! |         | vec4()    | default_pos   | get the default vertex position | vert       |
 //! |         | vec4()    | default_color | get the default fragment color  | frag       |
+//! |         | float()   | default_shadow | get the shadow term inside [`use_shadows`](struct.Gfx.html#method.use_shadows), 0 is fully in shadow | frag |
+//!
+//! pass a [`CubeTexture`](struct.CubeTexture.html) with `UniformData::CubeTexture` to read it with a `samplerCube`, for things like reflections
 
 import!(buffer);
 import!(pipeline);
-import!(batch);
+import!(unicode);
+import!(caps);
+import!(export);
 
 export!(types);
+export!(batch);
 export!(desc);
 export!(mesh);
 export!(texture);
+export!(cubemap);
 export!(canvas);
 export!(shader);
 export!(transform);
@@ -169,6 +218,16 @@ export!(camera);
 export!(font);
 export!(uniform);
 export!(model);
+export!(anim);
+export!(postprocess);
+export!(atlas);
+export!(picking);
+export!(lighting);
+export!(material);
+export!(shadow);
+export!(scene);
+#[cfg(not(web))]
+export!(headless);
 
 pub mod shapes;
 pub mod fonts;
@@ -189,6 +248,7 @@ use window::*;
 use geom::*;
 
 const DRAW_COUNT: usize = 65536;
+const INSTANCE_COUNT: usize = 4096;
 const DEFAULT_NEAR: f32 = -4096.0;
 const DEFAULT_FAR: f32 = 4096.0;
 
@@ -196,6 +256,7 @@ const DEFAULT_FAR: f32 = 4096.0;
 pub struct Gfx {
 
 	gl: Rc<glow::Context>,
+	caps: Caps,
 
 	width: i32,
 	height: i32,
@@ -212,24 +273,43 @@ pub struct Gfx {
 	default_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
 	cur_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
 	cur_custom_uniform: Option<Vec<(&'static str, UniformData)>>,
+	lit_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
+	skybox_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
+	sdf_pipeline: Pipeline<gfx::Vertex, gfx::Uniform>,
+
+	instanced_pipeline: Pipeline<gfx::Vertex, gfx::InstancedUniform>,
+	instance_buf: VertexBuffer<gfx::InstanceData>,
+	quad_mesh: gfx::Mesh,
+	joint_tex: Option<gfx::Texture>,
+
+	gl_state: GLState,
 
 	on_canvas: bool,
+	picking: bool,
+	shadow_pass: bool,
 
 	default_font: gfx::BitmapFont,
+	fallback_fonts: Vec<Box<dyn Font>>,
 
-	draw_calls_last: usize,
-	draw_calls: usize,
+	stats: DrawStats,
 
 }
 
 pub trait GLCtx {
 	fn gl(&self) -> &Rc<glow::Context>;
+	/// max texture anisotropy of the driver, 1.0 if not supported
+	fn max_anisotropy(&self) -> f32 {
+		return 1.0;
+	}
 }
 
 impl GLCtx for Gfx {
 	fn gl(&self) -> &Rc<glow::Context> {
 		return &self.gl;
 	}
+	fn max_anisotropy(&self) -> f32 {
+		return self.caps.max_anisotropy;
+	}
 }
 
 impl GLCtx for Rc<glow::Context> {
@@ -241,8 +321,11 @@ impl GLCtx for Rc<glow::Context> {
 impl Gfx {
 
 	pub(crate) fn new(window: &Window, conf: &conf::Conf) -> Result<Self> {
+		return Self::from_gl(window.gl(), window.width(), window.height(), window.dpi(), conf);
+	}
 
-		let gl = window.gl();
+	// create from a current gl context, used by both window and headless
+	pub(crate) fn from_gl(gl: &Rc<glow::Context>, width: i32, height: i32, dpi: f32, conf: &conf::Conf) -> Result<Self> {
 
 		use types::*;
 
@@ -274,19 +357,69 @@ impl Gfx {
 		}
 
 		let cam = OrthoCam {
-			width: conf.width as f32,
-			height: conf.height as f32,
+			width: width as f32,
+			height: height as f32,
 			near: DEFAULT_NEAR,
 			far: DEFAULT_FAR,
 		};
 
 		let vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", shaders::DEFAULT_VERT);
-		let frag_src = shaders::TEMPLATE_FRAG.replace("{{user}}", shaders::DEFAULT_FRAG);
+		let frag_src = shaders::frag(shaders::TEMPLATE_FRAG, shaders::DEFAULT_FRAG);
 		#[cfg(any(web, mobile))]
 		let frag_src = format!("{}{}", "precision mediump float;", frag_src);
 
 		let pipeline = Pipeline::new(gl, &vert_src, &frag_src)?;
 
+		let lit_vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", shaders::LIT_VERT);
+		let lit_frag_src = shaders::frag(shaders::TEMPLATE_FRAG, shaders::LIT_FRAG);
+		#[cfg(any(web, mobile))]
+		let lit_frag_src = format!("{}{}", "#extension GL_OES_standard_derivatives : enable\nprecision mediump float;", lit_frag_src);
+
+		let lit_pipeline = Pipeline::new(gl, &lit_vert_src, &lit_frag_src)?;
+
+		let skybox_vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", shaders::SKYBOX_VERT);
+		let skybox_frag_src = shaders::frag(shaders::TEMPLATE_FRAG, shaders::SKYBOX_FRAG);
+		#[cfg(any(web, mobile))]
+		let skybox_frag_src = format!("{}{}", "precision mediump float;", skybox_frag_src);
+
+		let skybox_pipeline = Pipeline::new(gl, &skybox_vert_src, &skybox_frag_src)?;
+
+		let sdf_frag_src = shaders::frag(shaders::TEMPLATE_FRAG, shaders::SDF_FRAG);
+		#[cfg(any(web, mobile))]
+		let sdf_frag_src = format!("{}{}", "#extension GL_OES_standard_derivatives : enable\nprecision mediump float;", sdf_frag_src);
+
+		let sdf_pipeline = Pipeline::new(gl, &vert_src, &sdf_frag_src)?;
+
+		let instanced_vert_src = shaders::TEMPLATE_INSTANCED_VERT.replace("{{user}}", shaders::DEFAULT_VERT);
+		let instanced_pipeline = Pipeline::new_instanced::<InstanceData>(gl, &instanced_vert_src, &frag_src)?;
+
+		let quad_mesh = Mesh::new(gl, &[
+			Vertex {
+				pos: vec3!(-0.5, 0.5, 0),
+				normal: vec3!(0, 0, 1),
+				uv: vec2!(0, 0),
+				color: rgba!(1),
+			},
+			Vertex {
+				pos: vec3!(0.5, 0.5, 0),
+				normal: vec3!(0, 0, 1),
+				uv: vec2!(1, 0),
+				color: rgba!(1),
+			},
+			Vertex {
+				pos: vec3!(0.5, -0.5, 0),
+				normal: vec3!(0, 0, 1),
+				uv: vec2!(1, 1),
+				color: rgba!(1),
+			},
+			Vertex {
+				pos: vec3!(-0.5, -0.5, 0),
+				normal: vec3!(0, 0, 1),
+				uv: vec2!(0, 1),
+				color: rgba!(1),
+			},
+		], &[0, 3, 1, 1, 3, 2])?;
+
 		let font_data = conf.default_font
 			.clone()
 			.take()
@@ -309,9 +442,11 @@ impl Gfx {
 
 		return Ok(Self {
 
-			width: window.width(),
-			height: window.height(),
-			dpi: window.dpi(),
+			caps: unsafe { Caps::query(gl) },
+
+			width: width,
+			height: height,
+			dpi: dpi,
 
 			renderer: BatchedRenderer::<Vertex, Uniform>::new(gl, DRAW_COUNT, DRAW_COUNT)?,
 
@@ -322,18 +457,32 @@ impl Gfx {
 			default_pipeline: pipeline.clone(),
 			cur_pipeline: pipeline,
 			cur_custom_uniform: None,
+			lit_pipeline: lit_pipeline,
+			skybox_pipeline: skybox_pipeline,
+			sdf_pipeline: sdf_pipeline,
+
+			instanced_pipeline: instanced_pipeline,
+			instance_buf: VertexBuffer::new(gl, INSTANCE_COUNT, BufferUsage::Dynamic)?,
+			quad_mesh: quad_mesh,
+			joint_tex: None,
+
+			gl_state: init_state,
 
 			on_canvas: false,
+			picking: false,
+			shadow_pass: false,
 
-			draw_calls_last: 0,
-			draw_calls: 0,
+			stats: DrawStats::default(),
 
 			empty_tex: Texture::from_raw_with_conf(gl, 1, 1, &[255; 4], TextureConf {
 				filter: FilterMode::Nearest,
-				wrap: WrapMode::Repeat,
+				wrap_x: WrapMode::Repeat,
+				wrap_y: WrapMode::Repeat,
+				..TextureConf::default()
 			})?,
 
 			default_font: font,
+			fallback_fonts: vec![],
 
 			gl: gl.clone(),
 
@@ -407,6 +556,12 @@ impl Gfx {
 
 		canvas.bind();
 
+		// clearing depth needs depth write
+		self.renderer.apply(&GLState {
+			depth_write: true,
+			..self.gl_state
+		});
+
 		unsafe {
 
 			self.gl.viewport(
@@ -417,7 +572,7 @@ impl Gfx {
 			);
 
 			if action.color == CanvasOp::Clear {
-				self.gl.clear(Surface::Color.as_glow());
+				canvas.clear_color();
 			}
 
 			if action.depth == CanvasOp::Clear {
@@ -461,14 +616,29 @@ impl Gfx {
 		f: impl FnOnce(&mut Self) -> Result<()>,
 	) -> Result<()> {
 
+		// pick & shadow passes use their own shader
+		if self.picking || self.shadow_pass {
+			return f(self);
+		}
+
+		return self.draw_with_data(shader.pipeline(), uniform.data(), f);
+
+	}
+
+	// draw with a pipeline and raw uniform data, for internal shaders that add uniforms
+	fn draw_with_data(
+		&mut self,
+		pipeline: &Pipeline<Vertex, Uniform>,
+		data: Vec<(&'static str, UniformData)>,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
 		let prev_pipeline = self.cur_pipeline.clone();
 		let prev_uniform = self.cur_custom_uniform.clone();
 
-		self.flush();
-		self.cur_pipeline = Pipeline::clone(&shader.pipeline());
-		self.cur_custom_uniform = Some(uniform.data());
+		self.cur_pipeline = Pipeline::clone(pipeline);
+		self.cur_custom_uniform = Some(data);
 		f(self)?;
-		self.flush();
 		self.cur_pipeline = prev_pipeline;
 		self.cur_custom_uniform = prev_uniform;
 
@@ -570,31 +740,11 @@ impl Gfx {
 		f: impl FnOnce(&mut Self) -> Result<()>,
 	) -> Result<()> {
 
-		let default = Blend::Alpha.state();
-		let custom = b.state();
-
-		unsafe {
-
-			self.flush();
-
-			self.gl.blend_func_separate(
-				custom.rgb_src.as_glow(),
-				custom.rgb_dest.as_glow(),
-				custom.a_src.as_glow(),
-				custom.a_dest.as_glow(),
-			);
+		let prev = self.gl_state.blend;
 
-			f(self)?;
-			self.flush();
-
-			self.gl.blend_func_separate(
-				default.rgb_src.as_glow(),
-				default.rgb_dest.as_glow(),
-				default.a_src.as_glow(),
-				default.a_dest.as_glow(),
-			);
-
-		}
+		self.gl_state.blend = b.state();
+		f(self)?;
+		self.gl_state.blend = prev;
 
 		return Ok(());
 
@@ -636,13 +786,11 @@ impl Gfx {
 		f: impl FnOnce(&mut Self) -> Result<()>,
 	) -> Result<()> {
 
-		unsafe {
-			self.flush();
-			self.gl.depth_mask(false);
-			f(self)?;
-			self.flush();
-			self.gl.depth_mask(true);
-		}
+		let prev = self.gl_state.depth_write;
+
+		self.gl_state.depth_write = false;
+		f(self)?;
+		self.gl_state.depth_write = prev;
 
 		return Ok(());
 
@@ -654,13 +802,11 @@ impl Gfx {
 		f: impl FnOnce(&mut Self) -> Result<()>,
 	) -> Result<()> {
 
-		unsafe {
-			self.flush();
-			self.gl.disable(Capability::DepthTest.as_glow());
-			f(self)?;
-			self.flush();
-			self.gl.enable(Capability::DepthTest.as_glow());
-		}
+		let prev = self.gl_state.depth_test;
+
+		self.gl_state.depth_test = None;
+		f(self)?;
+		self.gl_state.depth_test = prev;
 
 		return Ok(());
 
@@ -691,8 +837,54 @@ impl Gfx {
 		return &self.default_font;
 	}
 
+	/// add a font for chars that text fonts don't have, e.g. cjk or emoji, tried in the order added
+	pub fn add_fallback_font(&mut self, f: impl Font + 'static) {
+		self.fallback_fonts.push(Box::new(f));
+	}
+
+	fn fallback_fonts(&self) -> impl Iterator<Item = &dyn Font> {
+		return self.fallback_fonts.iter().map(|f| f.as_ref());
+	}
+
+	/// fonts added with [`add_fallback_font`](#method.add_fallback_font), e.g. to [`cache_str`](trait.Font.html#method.cache_str) chars after they're added
+	pub fn fallback_fonts_mut(&mut self) -> impl Iterator<Item = &mut dyn Font> {
+		return self.fallback_fonts.iter_mut().map(|f| f.as_mut() as &mut dyn Font);
+	}
+
+	/// draw everything inside sorted by render state (texture, shader, blend etc.), so sprites from different textures that are drawn interleaved only take one draw call per texture
+	///
+	/// draws are grouped in [`use_layer`](#method.use_layer) order, the order between draws with the same state is kept, anything that flushes (canvases, masks, meshes) ends the sorted range so far
+	pub fn draw_sorted(
+		&mut self,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		self.renderer.begin_sort()?;
+		let res = f(self);
+		self.renderer.end_sort();
+
+		return res;
+
+	}
+
+	/// set the layer for draws inside, lower layers are drawn first in [`draw_sorted`](#method.draw_sorted), has no effect outside
+	pub fn use_layer(
+		&mut self,
+		layer: i32,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		let prev = self.renderer.set_layer(layer);
+		let res = f(self);
+		self.renderer.set_layer(prev);
+
+		return res;
+
+	}
+
+	/// draw everything queued in the batch
 	pub fn flush(&mut self) {
-		self.renderer.flush();
+		self.renderer.flush(FlushReason::Explicit);
 	}
 
 	pub(crate) fn set_dpi(&mut self, dpi: f32) {
@@ -726,8 +918,10 @@ impl Gfx {
 
 	pub(crate) fn begin_frame(&mut self) {
 
-		self.draw_calls_last = self.draw_calls;
-		self.draw_calls = 0;
+		self.renderer.apply(&GLState {
+			depth_write: true,
+			..self.gl_state
+		});
 
 		unsafe {
 
@@ -748,8 +942,7 @@ impl Gfx {
 	pub(crate) fn end_frame(&mut self) {
 		self.flush();
 		self.transform = mat4!();
-		self.draw_calls += self.renderer.draw_count();
-		self.renderer.clear_draw_count();
+		self.stats = self.renderer.take_stats();
 	}
 
 	pub fn width(&self) -> i32 {
@@ -764,8 +957,14 @@ impl Gfx {
 		return self.dpi;
 	}
 
+	/// draw calls of last frame
 	pub fn draw_calls(&self) -> usize {
-		return self.draw_calls_last;
+		return self.stats.draw_calls;
+	}
+
+	/// draw statistics of last frame
+	pub fn stats(&self) -> DrawStats {
+		return self.stats;
 	}
 
 }
@@ -789,6 +988,148 @@ pub(self) fn draw<V: VertexLayout, U: UniformLayout>(
 		bind_attrs::<V>(&gl);
 		ibuf.bind();
 
+		let tex_slots = set_uniform(ctx, pip, uniform);
+
+		match prim {
+			Primitive::Line(w) => gl.line_width(w),
+			_ => {},
+		}
+
+		gl.draw_elements(prim.as_glow(), count as i32, glow::UNSIGNED_INT, 0);
+
+		ibuf.unbind();
+		vbuf.unbind();
+		gl.use_program(None);
+		unbind_textures(ctx, tex_slots);
+
+	}
+
+}
+
+// draw with per vertex skinning & morph target data from other buffers
+pub(self) fn draw_deformed<V: VertexLayout, U: UniformLayout>(
+	ctx: &impl GLCtx,
+	prim: Primitive,
+	pip: &Pipeline<V, U>,
+	vbuf: &VertexBuffer<V>,
+	skin_buf: Option<&VertexBuffer<SkinVertex>>,
+	morph_bufs: &[&VertexBuffer<MorphVertex>],
+	ibuf: &IndexBuffer,
+	count: usize,
+	uniform: &U,
+) {
+
+	unsafe {
+
+		let gl = ctx.gl();
+		let start = V::attrs().len();
+		let skin_attrs = SkinVertex::attrs().len();
+		let morph_attrs = MorphVertex::attrs().len();
+
+		pip.bind();
+
+		if let Some(skin_buf) = skin_buf {
+			skin_buf.bind();
+			bind_attrs_at::<SkinVertex>(&gl, start);
+		}
+
+		for (i, buf) in morph_bufs.iter().take(MAX_MORPH_TARGETS).enumerate() {
+			buf.bind();
+			bind_attrs_at::<MorphVertex>(&gl, start + skin_attrs + i * morph_attrs);
+		}
+
+		vbuf.bind();
+		bind_attrs::<V>(&gl);
+		ibuf.bind();
+
+		let tex_slots = set_uniform(ctx, pip, uniform);
+
+		match prim {
+			Primitive::Line(w) => gl.line_width(w),
+			_ => {},
+		}
+
+		gl.draw_elements(prim.as_glow(), count as i32, glow::UNSIGNED_INT, 0);
+
+		// other draws don't use these
+		for i in start..start + DEFORM_ATTRS.len() {
+			gl.disable_vertex_attrib_array(i as u32);
+		}
+
+		ibuf.unbind();
+		vbuf.unbind();
+		gl.use_program(None);
+		unbind_textures(ctx, tex_slots);
+
+	}
+
+}
+
+// draw a mesh once for each instance in the instance buffer
+pub(self) fn draw_instanced<V: VertexLayout, I: VertexLayout, U: UniformLayout>(
+	ctx: &impl GLCtx,
+	prim: Primitive,
+	pip: &Pipeline<V, U>,
+	vbuf: &VertexBuffer<V>,
+	ibuf: &IndexBuffer,
+	count: usize,
+	inst_buf: &VertexBuffer<I>,
+	inst_count: usize,
+	uniform: &U,
+) {
+
+	unsafe {
+
+		let gl = ctx.gl();
+		let start = V::attrs().len();
+		let inst_attrs = I::attrs().len();
+
+		pip.bind();
+		vbuf.bind();
+		bind_attrs::<V>(&gl);
+		inst_buf.bind();
+		bind_attrs_at::<I>(&gl, start);
+
+		for i in start..start + inst_attrs {
+			gl.vertex_attrib_divisor(i as u32, 1);
+		}
+
+		ibuf.bind();
+
+		let tex_slots = set_uniform(ctx, pip, uniform);
+
+		match prim {
+			Primitive::Line(w) => gl.line_width(w),
+			_ => {},
+		}
+
+		gl.draw_elements_instanced(prim.as_glow(), count as i32, glow::UNSIGNED_INT, 0, inst_count as i32);
+
+		// other draws don't use these
+		for i in start..start + inst_attrs {
+			gl.vertex_attrib_divisor(i as u32, 0);
+			gl.disable_vertex_attrib_array(i as u32);
+		}
+
+		ibuf.unbind();
+		inst_buf.unbind();
+		gl.use_program(None);
+		unbind_textures(ctx, tex_slots);
+
+	}
+
+}
+
+// returns the texture target bound to each slot
+fn set_uniform<V: VertexLayout, U: UniformLayout>(
+	ctx: &impl GLCtx,
+	pip: &Pipeline<V, U>,
+	uniform: &U,
+) -> Vec<u32> {
+
+	unsafe {
+
+		let gl = ctx.gl();
 		let mut tex_slots = vec![];
 
 		for (name, data) in uniform.data() {
@@ -803,35 +1144,43 @@ pub(self) fn draw<V: VertexLayout, U: UniformLayout>(
 					UniformData::Vec4(f) => gl.uniform_4_f32(loc.as_ref(), f.x, f.y, f.z, f.w),
 					UniformData::Int(i) => gl.uniform_1_i32(loc.as_ref(), i),
 					UniformData::Mat4(m) => gl.uniform_matrix_4_f32_slice(loc.as_ref(), false, &m.as_arr()),
+					UniformData::Mat4Array(ms) => {
+						let data = ms
+							.iter()
+							.flat_map(|m| m.as_arr().to_vec())
+							.collect::<Vec<f32>>();
+						gl.uniform_matrix_4_f32_slice(loc.as_ref(), false, &data);
+					},
 					UniformData::Texture(tex) => {
 						gl.uniform_1_i32(loc.as_ref(), tex_slots.len() as i32);
 						gl.active_texture(glow::TEXTURE0 + tex_slots.len() as u32);
 						tex.bind();
-						tex_slots.push(tex.clone());
+						tex_slots.push(glow::TEXTURE_2D);
+					},
+					UniformData::CubeTexture(tex) => {
+						gl.uniform_1_i32(loc.as_ref(), tex_slots.len() as i32);
+						gl.active_texture(glow::TEXTURE0 + tex_slots.len() as u32);
+						tex.bind();
+						tex_slots.push(glow::TEXTURE_CUBE_MAP);
 					},
 				}
 			}
 
 		}
 
-		match prim {
-			Primitive::Line(w) => gl.line_width(w),
-			_ => {},
-		}
+		return tex_slots;
 
-		gl.draw_elements(prim.as_glow(), count as i32, glow::UNSIGNED_INT, 0);
+	}
 
-		ibuf.unbind();
-		vbuf.unbind();
-		gl.use_program(None);
+}
 
-		for (i, tex) in tex_slots.into_iter().enumerate() {
-			gl.active_texture(glow::TEXTURE0 + i as u32);
-			tex.unbind();
+fn unbind_textures(ctx: &impl GLCtx, tex_slots: Vec<u32>) {
+	unsafe {
+		for (i, target) in tex_slots.into_iter().enumerate() {
+			ctx.gl().active_texture(glow::TEXTURE0 + i as u32);
+			ctx.gl().bind_texture(target, None);
 		}
-
 	}
-
 }
 
 pub trait Drawable {
@@ -846,7 +1195,7 @@ struct BlendState {
 	a_dest: BlendFac,
 }
 
-// TODO
+// gl states that are part of the batch key
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct GLState {
 	blend: BlendState,
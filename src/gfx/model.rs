@@ -2,35 +2,69 @@
 
 use std::io::Cursor;
 use std::path::Path;
+use std::cell::RefCell;
 
 use super::*;
 
 type NodeID = usize;
 
-// TODO: rework anim system
-
 #[derive(Clone, Serialize, Deserialize)]
 pub(super) struct NodeData {
 	pub id: NodeID,
 	pub children: Vec<NodeID>,
 	pub transform: Transform,
-	pub meshes: Vec<MeshData>,
+	pub meshes: Vec<PrimData>,
+	pub skin: Option<usize>,
+	// default morph target weights
+	pub weights: Vec<f32>,
 	pub name: Option<String>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub(super) struct PrimData {
+	pub mesh: MeshData,
+	pub material: Option<usize>,
+	// joints & weights of each vertex
+	pub skin: Option<Vec<SkinVertex>>,
+	// offsets of each vertex for each morph target
+	pub morphs: Vec<Vec<MorphVertex>>,
+}
+
+impl PrimData {
+	fn new(mesh: MeshData) -> Self {
+		return Self {
+			mesh: mesh,
+			material: None,
+			skin: None,
+			morphs: vec![],
+		};
+	}
+}
+
+/// Joints of a Skinned Mesh
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Skin {
+	pub joints: Vec<NodeID>,
+	pub inverse_binds: Vec<Mat4>,
+}
+
 /// Data for Creating [`Model`](`struct.Model.html`)
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ModelData {
-	nodes: HashMap<NodeID, NodeData>,
-	root_nodes: Vec<NodeID>,
-	img: Option<img::Image>,
-	anims: HashMap<NodeID, Anim>,
-	anim_len: f32,
+	pub(super) nodes: HashMap<NodeID, NodeData>,
+	pub(super) root_nodes: Vec<NodeID>,
+	pub(super) img: Option<img::Image>,
+	pub(super) materials: Vec<MaterialData>,
+	pub(super) skins: Vec<Skin>,
+	pub(super) clips: Vec<AnimationClip>,
 }
 
 #[derive(Clone)]
 pub(super) struct Node {
 	meshes: Vec<Mesh>,
+	mesh_materials: Vec<Option<usize>>,
+	skin: Option<usize>,
+	weights: Vec<f32>,
 	id: NodeID,
 	name: Option<String>,
 	children: Vec<NodeID>,
@@ -44,102 +78,30 @@ impl Node {
 	pub fn meshes(&self) -> &[Mesh] {
 		return &self.meshes;
 	}
+	pub fn mesh_material(&self, i: usize) -> Option<usize> {
+		return self.mesh_materials.get(i).cloned().flatten();
+	}
 	pub fn children(&self) -> &[NodeID] {
 		return &self.children;
 	}
-}
-
-type Track<T> = Vec<(f32, T)>;
-
-/// 3D Animation Data
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Anim {
-	pos: Track<Vec3>,
-	rot: Track<Vec4>,
-	scale: Track<Vec3>,
-}
-
-fn get_track_val<T: Lerp>(track: &Track<T>, t: f32) -> Option<T> {
-
-	if track.is_empty() {
-		return None;
-	}
-
-	if let Some((k, _)) = track.first() {
-		if *k > t {
-			return None;
-		}
-	}
-
-	if let Some((k, val)) = track.last() {
-		if *k <= t {
-			return Some(*val);
-		}
-	}
-
-	for i in 0..track.len() - 1 {
-
-		let (k1, pos1) = track[i];
-		let (k2, pos2) = track[i + 1];
-
-		if t >= k1 && t <= k2 {
-			let dt = t - k1;
-			return Some(pos1.lerp(pos2, dt));
-		}
-
-	}
-
-	return None;
-
-}
-
-impl Anim {
-
-	pub fn len(&self) -> f32 {
-
-		let t1 = self.pos
-			.last()
-			.map(|(t, _)| *t)
-			.unwrap_or(0.0);
-
-		let t2 = self.rot
-			.last()
-			.map(|(t, _)| *t)
-			.unwrap_or(0.0);
-
-		let t3 = self.scale
-			.last()
-			.map(|(t, _)| *t)
-			.unwrap_or(0.0);
-
-		return t1
-			.max(t2)
-			.max(t3)
-			;
-
+	pub fn skin(&self) -> Option<usize> {
+		return self.skin;
 	}
-
-	pub fn get_transform(&self, t: f32) -> (Option<Vec3>, Option<Vec4>, Option<Vec3>) {
-
-		return (
-			get_track_val(&self.pos, t),
-			get_track_val(&self.rot, t),
-			get_track_val(&self.scale, t),
-		);
-
+	pub fn weights(&self) -> &[f32] {
+		return &self.weights;
 	}
-
 }
 
 /// 3D Model
 #[derive(Clone)]
 pub struct Model {
 	nodes: HashMap<NodeID, Node>,
-	anims: HashMap<NodeID, Anim>,
-	anim_len: f32,
+	clips: Vec<AnimationClip>,
 	root_nodes: Vec<NodeID>,
 	bbox: BBox,
 	texture: Option<Texture>,
+	materials: Vec<Material>,
+	skins: Vec<Skin>,
 }
 
 fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf::Node) {
@@ -154,7 +116,7 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 		scale: vec3!(scale[0], scale[1], scale[2]),
 	};
 
-	let meshes = node.mesh().map(|mesh| {
+	let meshes: Vec<PrimData> = node.mesh().map(|mesh| {
 
 		return mesh
 			.primitives()
@@ -211,6 +173,55 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 						.collect::<Vec<Vec2>>();
 				}).unwrap_or_default();
 
+			let joints = reader
+				.read_joints(0)
+				.map(|joints| {
+					return joints
+						.into_u16()
+						.map(|j| vec4!(j[0], j[1], j[2], j[3]))
+						.collect::<Vec<Vec4>>();
+				}).unwrap_or_default();
+
+			let weights = reader
+				.read_weights(0)
+				.map(|weights| {
+					return weights
+						.into_f32()
+						.map(|w| vec4!(w[0], w[1], w[2], w[3]))
+						.collect::<Vec<Vec4>>();
+				}).unwrap_or_default();
+
+			let skin = if joints.len() == positions.len() && weights.len() == positions.len() {
+				Some(joints
+					.into_iter()
+					.zip(weights)
+					.map(|(joints, weights)| SkinVertex {
+						joints: joints,
+						weights: weights,
+					})
+					.collect::<Vec<SkinVertex>>())
+			} else {
+				None
+			};
+
+			let morphs = reader
+				.read_morph_targets()
+				.map(|(pos, normals, _)| {
+					let pos = pos
+						.map(|p| p.map(|v| vec3!(v[0], v[1], v[2])).collect::<Vec<Vec3>>())
+						.unwrap_or_default();
+					let normals = normals
+						.map(|n| n.map(|v| vec3!(v[0], v[1], v[2])).collect::<Vec<Vec3>>())
+						.unwrap_or_default();
+					return (0..positions.len())
+						.map(|i| MorphVertex {
+							pos: pos.get(i).cloned().unwrap_or(vec3!(0)),
+							normal: normals.get(i).cloned().unwrap_or(vec3!(0)),
+						})
+						.collect::<Vec<MorphVertex>>();
+				})
+				.collect::<Vec<Vec<MorphVertex>>>();
+
 			let mut verts = Vec::with_capacity(positions.len());
 
 			for i in 0..positions.len() {
@@ -226,21 +237,42 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 
 			}
 
-			return MeshData {
-				vertices: verts,
-				indices: indices,
+			return PrimData {
+				mesh: MeshData {
+					vertices: verts,
+					indices: indices,
+				},
+				material: prim.material().index(),
+				skin: skin,
+				morphs: morphs,
 			};
 
 		}).collect();
 
 	}).unwrap_or_default();
 
+	let morph_count = meshes
+		.iter()
+		.map(|p| p.morphs.len())
+		.max()
+		.unwrap_or(0);
+
+	let mut weights = node
+		.weights()
+		.or_else(|| node.mesh().and_then(|m| m.weights()))
+		.map(|w| w.to_vec())
+		.unwrap_or_default();
+
+	weights.resize(morph_count, 0.0);
+
 	nodes.insert(id, NodeData {
 		id: id,
 		name: name.map(String::from),
 		children: node.children().map(|c| c.index()).collect(),
 		transform: transform,
 		meshes: meshes,
+		skin: node.skin().map(|s| s.index()),
+		weights: weights,
 	});
 
 	for c in node.children() {
@@ -249,6 +281,245 @@ fn read_gltf_node(bin: &[u8], nodes: &mut HashMap<NodeID, NodeData>, node: gltf:
 
 }
 
+// names of the materials in an mtl file that set Ks
+fn mtl_with_specular(mtl: &[u8]) -> Vec<String> {
+
+	let mut names = vec![];
+	let mut cur = None;
+
+	for line in String::from_utf8_lossy(mtl).lines() {
+		let mut words = line.split_whitespace();
+		match words.next() {
+			Some("newmtl") => cur = Some(words.collect::<Vec<&str>>().join(" ")),
+			Some("Ks") => names.extend(cur.clone()),
+			_ => {},
+		}
+	}
+
+	return names;
+
+}
+
+fn read_gltf(document: &gltf::Document, bin: &[u8]) -> Result<ModelData> {
+
+	// morph target weights are passed in a vec4
+	for m in document.meshes() {
+		for p in m.primitives() {
+			let count = p.morph_targets().count();
+			if count > MAX_MORPH_TARGETS {
+				return Err(format!("too many morph targets in mesh: {}, max {}", count, MAX_MORPH_TARGETS));
+			}
+		}
+	}
+
+	// image
+	use gltf::image::Source;
+
+	let mut imgs = Vec::with_capacity(document.images().len());
+
+	for i in document.images() {
+
+		match i.source() {
+
+			Source::View { view, .. } => {
+
+				let offset = view.offset();
+				let len = view.length();
+				let buf = &bin[offset..offset + len];
+
+				imgs.push(Some(img::Image::from_bytes(buf)?));
+
+			},
+
+			// external images are not supported in glb
+			_ => imgs.push(None),
+
+		}
+
+	}
+
+	// materials
+	let get_img = |tex: gltf::texture::Texture| {
+		return imgs.get(tex.source().index()).cloned().flatten();
+	};
+
+	let materials = document.materials().map(|m| {
+
+		let pbr = m.pbr_metallic_roughness();
+		let c = pbr.base_color_factor();
+		let e = m.emissive_factor();
+
+		return MaterialData {
+			name: m.name().map(String::from),
+			// factors are in linear color space like vertex colors
+			base_color: rgba!(c[0], c[1], c[2], c[3]).to_srgb(),
+			base_color_img: pbr.base_color_texture().and_then(|t| get_img(t.texture())),
+			metallic: pbr.metallic_factor(),
+			roughness: pbr.roughness_factor(),
+			metallic_roughness_img: pbr.metallic_roughness_texture().and_then(|t| get_img(t.texture())),
+			normal_img: m.normal_texture().and_then(|t| get_img(t.texture())),
+			normal_scale: m.normal_texture().map(|t| t.scale()).unwrap_or(1.0),
+			emissive: rgba!(e[0], e[1], e[2], 1).to_srgb(),
+			emissive_img: m.emissive_texture().and_then(|t| get_img(t.texture())),
+			occlusion_img: m.occlusion_texture().and_then(|t| get_img(t.texture())),
+			occlusion_strength: m.occlusion_texture().map(|t| t.strength()).unwrap_or(1.0),
+			..MaterialData::default()
+		};
+
+	}).collect::<Vec<MaterialData>>();
+
+	// skins
+	let mut skins = Vec::with_capacity(document.skins().len());
+
+	for s in document.skins() {
+
+		let joints = s.joints().map(|j| j.index()).collect::<Vec<NodeID>>();
+
+		let inverse_binds = s
+			.reader(|_| Some(&bin))
+			.read_inverse_bind_matrices()
+			.map(|mats| {
+				return mats
+					.map(|m| Mat4::new([
+						m[0][0], m[0][1], m[0][2], m[0][3],
+						m[1][0], m[1][1], m[1][2], m[1][3],
+						m[2][0], m[2][1], m[2][2], m[2][3],
+						m[3][0], m[3][1], m[3][2], m[3][3],
+					]))
+					.collect::<Vec<Mat4>>();
+			})
+			.unwrap_or_else(|| vec![mat4!(); joints.len()]);
+
+		skins.push(Skin {
+			joints: joints,
+			inverse_binds: inverse_binds,
+		});
+
+	}
+
+	// anims
+	let mut clips = Vec::with_capacity(document.animations().len());
+
+	for a in document.animations() {
+
+		let mut anims: HashMap<NodeID, Anim> = hmap![];
+
+		for c in a.channels() {
+
+			let reader = c.reader(|_| Some(&bin));
+			let node_id = c.target().node().index();
+			let anim = anims.entry(node_id).or_insert(Anim::default());
+
+			use gltf::animation::Interpolation as GLTFInterpolation;
+
+			let interpolation = match c.sampler().interpolation() {
+				GLTFInterpolation::Linear => Interpolation::Linear,
+				GLTFInterpolation::Step => Interpolation::Step,
+				GLTFInterpolation::CubicSpline => Interpolation::CubicSpline,
+			};
+
+			let times: Vec<f32> = reader
+				.read_inputs()
+				.ok_or_else(|| format!("failed to read anim"))?
+				.collect();
+
+			let per_key = match interpolation {
+				Interpolation::CubicSpline => 3,
+				_ => 1,
+			};
+
+			use gltf::animation::util::ReadOutputs;
+
+			match reader
+				.read_outputs()
+				.ok_or_else(|| format!("failed to read anim"))? {
+
+				ReadOutputs::Translations(translations) => {
+					anim.pos = Some(Track {
+						interpolation: interpolation,
+						times: times.clone(),
+						values: translations.map(|v| vec3!(v[0], v[1], v[2])).collect(),
+					});
+				}
+
+				ReadOutputs::Rotations(rotations) => {
+					anim.rot = Some(Track {
+						interpolation: interpolation,
+						times: times.clone(),
+						values: rotations.into_f32().map(|v| vec4!(v[0], v[1], v[2], v[3])).collect(),
+					});
+				}
+
+				ReadOutputs::Scales(scales) => {
+					anim.scale = Some(Track {
+						interpolation: interpolation,
+						times: times.clone(),
+						values: scales.map(|v| vec3!(v[0], v[1], v[2])).collect(),
+					});
+				}
+
+				// all targets of each keyframe are packed together
+				ReadOutputs::MorphTargetWeights(weights) => {
+
+					let weights = weights.into_f32().collect::<Vec<f32>>();
+					let keys = times.len() * per_key;
+
+					if keys == 0 || weights.len() % keys != 0 {
+						return Err(format!("failed to read anim from glb"));
+					}
+
+					let targets = weights.len() / keys;
+
+					anim.weights = (0..targets)
+						.map(|j| Track {
+							interpolation: interpolation,
+							times: times.clone(),
+							values: (0..keys).map(|k| weights[k * targets + j]).collect(),
+						})
+						.collect();
+
+				}
+
+			};
+
+			let count = [
+				anim.pos.as_ref().map(|t| t.values.len()),
+				anim.rot.as_ref().map(|t| t.values.len()),
+				anim.scale.as_ref().map(|t| t.values.len()),
+			];
+
+			if count.iter().flatten().any(|c| *c < times.len() * per_key) {
+				return Err(format!("failed to read anim from glb"));
+			}
+
+		}
+
+		clips.push(AnimationClip::new(a.name().map(String::from), anims));
+
+	}
+
+	// mesh
+	let mut nodes = HashMap::with_capacity(document.nodes().len());
+	let mut root_nodes = vec![];
+
+	for s in document.scenes() {
+		for n in s.nodes() {
+			root_nodes.push(n.index());
+			read_gltf_node(&bin, &mut nodes, n);
+		}
+	}
+
+	return Ok(ModelData {
+		nodes,
+		root_nodes,
+		img: None,
+		materials,
+		skins,
+		clips,
+	});
+
+}
+
 impl Model {
 
 	/// load [`ModelData`](struct.ModelData.html) from a file
@@ -261,18 +532,21 @@ impl Model {
 			"obj" => {
 
 				let obj_src = fs::read_str(&path)?;
+				let dir = path.parent().map(|p| p.to_owned()).unwrap_or_default();
+				let mtl_path = path.with_extension("mtl");
 
-				path.set_extension("mtl");
-
-				let mtl_src = fs::read_str(&path).ok();
-				let mtl_src = mtl_src.as_deref();
+				// referenced files are relative to the obj, fallback to the mtl with the same name
+				let mut data = gfx::Model::load_obj_with(&obj_src, |p| {
+					return fs::read(dir.join(p))
+						.ok()
+						.or_else(|| if p.ends_with(".mtl") { fs::read(&mtl_path).ok() } else { None });
+				})?;
 
 				path.set_extension("png");
 
-				let img_src = fs::read(&path).ok();
-				let img_src = img_src.as_deref();
-
-				let data = gfx::Model::load_obj(&obj_src, mtl_src, img_src)?;
+				if let Ok(bytes) = fs::read(&path) {
+					data.img = Some(img::Image::from_bytes(&bytes)?);
+				}
 
 				return Ok(data);
 
@@ -287,6 +561,15 @@ impl Model {
 
 			},
 
+			"gltf" => {
+
+				let src = fs::read_str(&path)?;
+				let data = gfx::Model::load_gltf(&src)?;
+
+				return Ok(data);
+
+			},
+
 			_ => {
 				return Err(format!("unsupported 3d format"));
 			},
@@ -308,10 +591,12 @@ impl Model {
 			name: None,
 			children: vec![],
 			transform: Transform::new(),
-			meshes: vec![MeshData {
+			meshes: vec![PrimData::new(MeshData {
 				vertices: verts,
 				indices: indices,
-			}],
+			})],
+			skin: None,
+			weights: vec![],
 		};
 
 		return ModelData {
@@ -320,8 +605,9 @@ impl Model {
 			],
 			root_nodes: vec![0],
 			img: None,
-			anims: hmap![],
-			anim_len: 0.0,
+			materials: vec![],
+			skins: vec![],
+			clips: vec![],
 		};
 
 	}
@@ -340,144 +626,124 @@ impl Model {
 		let bin = glb.bin
 			.ok_or_else(|| format!("failed to parse bin from glb"))?;
 
-		// image
-		use gltf::image::Source;
-
-		let mut img = None;
-
-		for i in document.images() {
+		return read_gltf(&document, &bin);
 
-			match i.source() {
+	}
 
-				Source::View { view, .. } => {
+	/// load [`ModelData`](struct.ModelData.html) from gltf text, only embedded buffers are supported
+	pub fn load_gltf(src: &str) -> Result<ModelData> {
 
-					let offset = view.offset();
-					let len = view.length();
-					let buf = &bin[offset..offset + len];
+		use gltf::Gltf;
+		use gltf::buffer::Source;
+
+		let document = Gltf::from_slice(src.as_bytes())
+			.map_err(|_| format!("failed to parse gltf"))?;
+
+		let bin = match document.buffers().next().map(|b| b.source()) {
+			Some(Source::Uri(uri)) => {
+				let data = uri
+					.splitn(2, ";base64,")
+					.nth(1)
+					.ok_or_else(|| format!("external buffers are not supported in gltf"))?;
+				base64::decode(data)
+					.map_err(|_| format!("failed to decode gltf buffer"))?
+			},
+			Some(_) => return Err(format!("failed to read gltf buffer")),
+			None => vec![],
+		};
 
-					img = Some(img::Image::from_bytes(buf)?);
+		return read_gltf(&document, &bin);
 
-				},
+	}
 
-				_ => {},
+	/// load [`ModelData`](struct.ModelData.html) from obj file, `img` is used for meshes without a textured material
+	pub fn load_obj(obj: &str, mtl: Option<&str>, img: Option<&[u8]>) -> Result<ModelData> {
 
+		let mut data = Self::load_obj_with(obj, |path| {
+			if path.ends_with(".mtl") {
+				return mtl.map(|m| m.as_bytes().to_vec());
 			}
+			return None;
+		})?;
 
+		if let Some(bytes) = img {
+			data.img = Some(img::Image::from_bytes(bytes)?);
 		}
 
-		// anims
-		let mut anims: HashMap<NodeID, Anim> = hmap![];
-
-		for a in document.animations() {
-
-			for c in a.channels() {
+		return Ok(data);
 
-				let reader = c.reader(|_| Some(&bin));
-				let node_id = c.target().node().index();
-				let sampler = c.sampler();
-
-				let mut anim = anims.entry(node_id).or_insert(Anim {
-					pos: vec![],
-					rot: vec![],
-					scale: vec![],
-				});
-
-				// TODO
-				use gltf::animation::Interpolation;
-
-				match sampler.interpolation() {
-					Interpolation::Linear => {},
-					Interpolation::Step => {},
-					Interpolation::CubicSpline => {},
-				};
-
-				let frames: Vec<f32> = reader
-					.read_inputs()
-					.ok_or_else(|| format!("failed to read anim"))?
-					.collect();
+	}
 
-				use gltf::animation::util::ReadOutputs;
+	/// load [`ModelData`](struct.ModelData.html) from obj file, `read` gets the content of mtl & texture files referenced by path
+	pub fn load_obj_with(obj: &str, read: impl Fn(&str) -> Option<Vec<u8>>) -> Result<ModelData> {
 
-				match reader
-					.read_outputs()
-					.ok_or_else(|| format!("failed to read anim"))? {
+		// tobj reads a missing Ks as black, so keep track of which materials set it
+		let with_specular = RefCell::new(vec![]);
 
-					ReadOutputs::Translations(translations) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in translations.enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec3!(v[0], v[1], v[2])));
-						}
-						anim.pos = values;
-					}
+		let (models, mtls) = tobj::load_obj_buf(&mut Cursor::new(obj), true, |path| {
+			return path
+				.to_str()
+				.and_then(|p| read(p))
+				.map(|m| {
+					with_specular.borrow_mut().extend(mtl_with_specular(&m));
+					return tobj::load_mtl_buf(&mut Cursor::new(m));
+				})
+				.unwrap_or(Ok((vec![], hmap![])));
+		}).map_err(|_| format!("failed to parse obj"))?;
 
-					ReadOutputs::Rotations(rotations) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in rotations.into_f32().enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec4!(v[0], v[1], v[2], v[3])));
-						}
-						anim.rot = values;
-					}
+		let with_specular = with_specular.into_inner();
 
-					ReadOutputs::Scales(scales) => {
-						let mut values = Vec::with_capacity(frames.len());
-						for (i, v) in scales.enumerate() {
-							let t = frames
-								.get(i)
-								.ok_or_else(|| format!("failed to read anim from glb"))?;
-							values.push((*t, vec3!(v[0], v[1], v[2])));
-						}
-						anim.scale = values;
-					}
+		// atlases are often shared by materials
+		let mut imgs: HashMap<String, Option<img::Image>> = hmap![];
 
-					_ => {}
+		let mut get_img = |name: &str| -> Result<Option<img::Image>> {
 
-				};
+			// ignore options like "-bm 1.0", windows paths are common
+			let name = match name.split_whitespace().last() {
+				Some(n) => n.replace('\\', "/"),
+				None => return Ok(None),
+			};
 
+			if let Some(img) = imgs.get(&name) {
+				return Ok(img.clone());
 			}
 
-		}
-
-		let mut anim_len = 0.0f32;
-
-		for a in anims.values() {
-			anim_len = anim_len.max(a.len());
-		}
-
-		// mesh
-		let mut nodes = HashMap::with_capacity(document.nodes().len());
-		let mut root_nodes = vec![];
+			let img = match read(&name) {
+				Some(bytes) => Some(img::Image::from_bytes(&bytes)?),
+				None => None,
+			};
 
-		for s in document.scenes() {
-			for n in s.nodes() {
-				root_nodes.push(n.index());
-				read_gltf_node(&bin, &mut nodes, n);
-			}
-		}
+			imgs.insert(name, img.clone());
 
-		return Ok(ModelData {
-			nodes,
-			root_nodes,
-			img,
-			anims,
-			anim_len,
-		});
+			return Ok(img);
 
-	}
+		};
 
-	/// load [`ModelData`](struct.ModelData.html) from obj file
-	pub fn load_obj(obj: &str, mtl: Option<&str>, img: Option<&[u8]>) -> Result<ModelData> {
+		let mut materials = Vec::with_capacity(mtls.len());
+
+		for m in &mtls {
+
+			let color = |c: [f32; 3], a: f32| rgba!(c[0], c[1], c[2], a);
+			let bump = m.unknown_param
+				.get("map_Bump")
+				.or(m.unknown_param.get("bump"))
+				.map(|s| s.as_str())
+				.unwrap_or(&m.normal_texture);
+
+			materials.push(MaterialData {
+				name: Some(m.name.clone()),
+				base_color: color(m.diffuse, m.dissolve),
+				base_color_img: get_img(&m.diffuse_texture)?,
+				normal_img: get_img(bump)?,
+				// missing Ka is read as black, which would hide the model in ambient light
+				ambient: if m.ambient == [0.0; 3] { rgba!(1) } else { color(m.ambient, 1.0) },
+				specular: if with_specular.contains(&m.name) { Some(color(m.specular, 1.0)) } else { None },
+				// inverse of the shininess mapping in the lit shader
+				roughness: (2.0 / (m.shininess.max(0.0) + 2.0)).powf(0.25),
+				..MaterialData::default()
+			});
 
-		let (models, materials) = tobj::load_obj_buf(&mut Cursor::new(obj), true, |_| {
-			return mtl
-				.map(|m| tobj::load_mtl_buf(&mut Cursor::new(m)))
-				.unwrap_or(Ok((vec![], hmap![])));
-		}).map_err(|_| format!("failed to parse obj"))?;
+		}
 
 		let mut root_nodes = Vec::with_capacity(models.len());
 		let mut nodes = HashMap::with_capacity(models.len());
@@ -486,6 +752,7 @@ impl Model {
 
 			root_nodes.push(i);
 
+			let name = m.name;
 			let m = m.mesh;
 			let positions = m.positions
 				.chunks(3)
@@ -504,57 +771,46 @@ impl Model {
 					.collect()
 			};
 
-			let mcolor = m.material_id
-				.map(|id| materials.get(id))
-				.flatten()
-				.map(|m| m.diffuse)
-				.map(|c| rgba!(c[0], c[1], c[2], 1))
-				.unwrap_or(rgba!(1))
-				;
-
 			for i in 0..vert_count {
 
-				let vx = m.positions[i * 3 + 0];
-				let vy = m.positions[i * 3 + 1];
-				let vz = m.positions[i * 3 + 2];
-
 				let tx = m.texcoords.get(i * 2 + 0).cloned().unwrap_or(0.0);
 				let ty = m.texcoords.get(i * 2 + 1).cloned().unwrap_or(0.0);
 
 				verts.push(Vertex {
-					pos: vec3!(vx, vy, vz),
+					pos: positions[i],
 					normal: normals[i],
 					uv: vec2!(tx, 1.0 - ty),
-					color: mcolor,
+					// colors come from the material
+					color: rgba!(1),
 				});
 
 			}
 
 			nodes.insert(i, NodeData {
 				id: i,
-				name: None,
+				name: if name.is_empty() { None } else { Some(name) },
 				children: vec![],
 				transform: gfx::Transform::new(),
-				meshes: vec![MeshData {
-					vertices: verts,
-					indices: m.indices,
+				meshes: vec![PrimData {
+					material: m.material_id.filter(|id| *id < materials.len()),
+					..PrimData::new(MeshData {
+						vertices: verts,
+						indices: m.indices,
+					})
 				}],
+				skin: None,
+				weights: vec![],
 			});
 
 		}
 
-		let img = if let Some(bytes) = img {
-			Some(img::Image::from_bytes(bytes)?)
-		} else {
-			None
-		};
-
 		return Ok(ModelData {
 			nodes,
 			root_nodes,
-			img,
-			anims: hmap![],
-			anim_len: 0.0,
+			img: None,
+			materials,
+			skins: vec![],
+			clips: vec![],
 		});
 
 	}
@@ -570,38 +826,60 @@ impl Model {
 			None
 		};
 
-		let anims = data.anims;
-		let anim_len = data.anim_len;
+		let materials = data.materials
+			.into_iter()
+			.map(|m| Material::from_data(ctx, m))
+			.collect::<Result<Vec<Material>>>()?;
+
+		let skins = data.skins;
+		let clips = data.clips;
 		let root_nodes = data.root_nodes;
 
 		let nodes = data.nodes
 			.into_iter()
 			.map(|(id, node)| {
 
+				let mesh_materials = node.meshes
+					.iter()
+					.map(|p| p.material)
+					.collect::<Vec<Option<usize>>>();
+
 				let meshes = node.meshes
 					.into_iter()
-					// TODO: don't unwrap here
-					.map(|m| Mesh::from_meshdata(ctx, &m).unwrap())
-					.collect::<Vec<Mesh>>();
+					.map(|p| {
+						let mut mesh = match p.skin {
+							Some(skin) => Mesh::with_skin(ctx, &p.mesh.vertices, &p.mesh.indices, &skin)?,
+							None => Mesh::from_meshdata(ctx, &p.mesh)?,
+						};
+						for m in &p.morphs {
+							mesh.add_morph_target(ctx, m)?;
+						}
+						return Ok(mesh);
+					})
+					.collect::<Result<Vec<Mesh>>>()?;
 
-				return (id, Node {
+				return Ok((id, Node {
 					id: node.id,
 					name: node.name,
 					children: node.children,
 					transform: node.transform,
 					meshes: meshes,
-				});
+					mesh_materials: mesh_materials,
+					skin: node.skin,
+					weights: node.weights,
+				}));
 
 			})
-			.collect::<HashMap<NodeID, Node>>();
+			.collect::<Result<HashMap<NodeID, Node>>>()?;
 
 		return Ok(Self {
 			bbox: bbox,
 			nodes,
-			anim_len,
-			anims,
+			clips,
 			root_nodes,
 			texture: tex,
+			materials,
+			skins,
 		});
 
 	}
@@ -635,12 +913,63 @@ impl Model {
 		return self.nodes.get(&id);
 	}
 
-	pub fn get_anim(&self, id: NodeID) -> Option<&Anim> {
-		return self.anims.get(&id);
+	// if any mesh is skinned or has morph targets
+	pub(super) fn deformed(&self) -> bool {
+		return self.nodes.values().any(|n| {
+			return n.skin.is_some() || n.meshes.iter().any(|m| m.morph_targets() > 0);
+		});
 	}
 
+	/// get the name of a node
+	pub fn node_name(&self, id: NodeID) -> Option<&str> {
+		return self.nodes.get(&id).and_then(|n| n.name.as_deref());
+	}
+
+	/// length of the first animation clip
 	pub fn anim_len(&self) -> f32 {
-		return self.anim_len;
+		return self.clips.first().map(|c| c.len()).unwrap_or(0.0);
+	}
+
+	/// get all animation clips
+	pub fn clips(&self) -> &[AnimationClip] {
+		return &self.clips;
+	}
+
+	/// get animation clip by index
+	pub fn clip(&self, i: usize) -> Option<&AnimationClip> {
+		return self.clips.get(i);
+	}
+
+	/// get animation clip by index, to add events
+	pub fn clip_mut(&mut self, i: usize) -> Option<&mut AnimationClip> {
+		return self.clips.get_mut(i);
+	}
+
+	/// add an animation clip, returns its index
+	pub fn add_clip(&mut self, clip: AnimationClip) -> usize {
+		self.clips.push(clip);
+		return self.clips.len() - 1;
+	}
+
+	/// find animation clip index by name
+	pub fn clip_id(&self, name: &str) -> Option<usize> {
+		return self.clips.iter().position(|c| c.name.as_deref() == Some(name));
+	}
+
+	/// get a node & all its descendants, useful for [`AnimLayer::mask`](struct.AnimLayer.html#method.mask)
+	pub fn subtree(&self, id: NodeID) -> Vec<NodeID> {
+
+		let mut nodes = vec![];
+
+		if let Some(node) = self.nodes.get(&id) {
+			nodes.push(id);
+			for c in node.children() {
+				nodes.extend(self.subtree(*c));
+			}
+		}
+
+		return nodes;
+
 	}
 
 	pub fn root_nodes(&self) -> &[NodeID] {
@@ -651,6 +980,143 @@ impl Model {
 		return self.texture.as_ref();
 	}
 
+	/// get all materials
+	pub fn materials(&self) -> &[Material] {
+		return &self.materials;
+	}
+
+	/// get material by index
+	pub fn material(&self, i: usize) -> Option<&Material> {
+		return self.materials.get(i);
+	}
+
+	/// get skin by index
+	pub fn skin(&self, i: usize) -> Option<&Skin> {
+		return self.skins.get(i);
+	}
+
+	/// sample the local transform & morph target weights of each node animated by a clip
+	pub fn sample_clip(&self, clip: usize, t: f32) -> Pose {
+
+		let mut pose = Pose::default();
+
+		let clip = match self.clips.get(clip) {
+			Some(c) => c,
+			None => return pose,
+		};
+
+		for (id, anim) in &clip.anims {
+			if let Some(node) = self.nodes.get(id) {
+				if anim.pos.is_some() || anim.rot.is_some() || anim.scale.is_some() {
+					pose.transforms.insert(*id, anim.apply(node.transform(), t));
+				}
+				if !anim.weights.is_empty() {
+					pose.weights.insert(*id, anim.apply_weights(node.weights(), t));
+				}
+			}
+		}
+
+		return pose;
+
+	}
+
+	/// blend 2 poses, nodes missing in one use their rest transform & weights
+	pub fn blend_pose(&self, p1: &Pose, p2: &Pose, t: f32) -> Pose {
+
+		let t = t.max(0.0).min(1.0);
+
+		let transforms = p1.transforms
+			.keys()
+			.chain(p2.transforms.keys())
+			.filter_map(|id| {
+				let rest = self.nodes.get(id)?.transform();
+				let a = p1.transforms.get(id).cloned().unwrap_or(rest);
+				let b = p2.transforms.get(id).cloned().unwrap_or(rest);
+				return Some((*id, Transform {
+					pos: a.pos.lerp(b.pos, t),
+					rot: slerp(a.rot, b.rot, t),
+					scale: a.scale.lerp(b.scale, t),
+				}));
+			})
+			.collect();
+
+		let weights = p1.weights
+			.keys()
+			.chain(p2.weights.keys())
+			.filter_map(|id| {
+				let rest = self.nodes.get(id)?.weights();
+				let a = p1.weights.get(id).map(|w| w.as_slice()).unwrap_or(rest);
+				let b = p2.weights.get(id).map(|w| w.as_slice()).unwrap_or(rest);
+				let len = a.len().max(b.len());
+				return Some((*id, (0..len)
+					.map(|i| {
+						let a = a.get(i).cloned().unwrap_or(0.0);
+						let b = b.get(i).cloned().unwrap_or(0.0);
+						return a.lerp(b, t);
+					})
+					.collect()));
+			})
+			.collect();
+
+		return Pose {
+			transforms: transforms,
+			weights: weights,
+		};
+
+	}
+
+	/// get the global transform of each node at a time of the first animation clip
+	pub fn node_transforms(&self, t: f32) -> HashMap<NodeID, Mat4> {
+		return self.pose_transforms(&self.sample_clip(0, t));
+	}
+
+	/// get the global transform of each node in a pose
+	pub fn pose_transforms(&self, pose: &Pose) -> HashMap<NodeID, Mat4> {
+
+		let mut transforms = HashMap::with_capacity(self.nodes.len());
+
+		for id in &self.root_nodes {
+			self.pose_transforms_inner(&mut transforms, pose, mat4!(), *id);
+		}
+
+		return transforms;
+
+	}
+
+	fn pose_transforms_inner(&self, transforms: &mut HashMap<NodeID, Mat4>, pose: &Pose, parent: Mat4, id: NodeID) {
+
+		if let Some(node) = self.nodes.get(&id) {
+
+			let tr = pose.transforms.get(&id).cloned().unwrap_or(node.transform());
+			let tr = parent * tr.as_mat4();
+
+			transforms.insert(id, tr);
+
+			for c in node.children() {
+				self.pose_transforms_inner(transforms, pose, tr, *c);
+			}
+
+		}
+
+	}
+
+	/// get the joint matrices of a skin, for a mesh on a node with global transform `node`
+	pub fn joint_matrices(&self, skin: usize, node: Mat4, transforms: &HashMap<NodeID, Mat4>) -> Option<Vec<Mat4>> {
+
+		let skin = self.skins.get(skin)?;
+		let inv = node.inverse();
+
+		return Some(skin.joints
+			.iter()
+			.zip(skin.inverse_binds.iter())
+			.map(|(j, ib)| {
+				let jt = transforms.get(j).cloned().unwrap_or(mat4!());
+				return inv * jt * *ib;
+			})
+			.collect());
+
+	}
+
 	/// get center position
 	pub fn center(&self) -> Vec3 {
 		return (self.bbox.min + self.bbox.max) / 2.0;
@@ -679,7 +1145,7 @@ fn get_bbox_inner(
 
 			for m in &node.meshes {
 
-				for v in &m.vertices {
+				for v in &m.mesh.vertices {
 
 					let pos = tr * v.pos;
 
@@ -713,3 +1179,96 @@ fn get_bbox(model: &ModelData) -> BBox {
 
 }
 
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn skinning() {
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 64, 64).unwrap();
+
+	let vert = |x: f32, y: f32| {
+		return Vertex {
+			pos: vec3!(x, y, 0),
+			normal: vec3!(0, 0, 1),
+			uv: vec2!(0),
+			color: rgba!(1),
+		};
+	};
+
+	// more joints than fit in uniforms, only the last one is used
+	let skin = SkinVertex {
+		joints: vec4!(99),
+		weights: vec4!(1, 0, 0, 0),
+	};
+
+	// a quad on node 0 following joint node 1, which moves right
+	let data = ModelData {
+		nodes: hmap![
+			0 => NodeData {
+				id: 0,
+				children: vec![],
+				transform: Transform::new(),
+				meshes: vec![PrimData {
+					mesh: MeshData {
+						vertices: vec![vert(-8.0, -8.0), vert(8.0, -8.0), vert(8.0, 8.0), vert(-8.0, 8.0)],
+						indices: vec![0, 1, 2, 0, 2, 3],
+					},
+					material: None,
+					skin: Some(vec![skin; 4]),
+					morphs: vec![],
+				}],
+				skin: Some(0),
+				weights: vec![],
+				name: None,
+			},
+			1 => NodeData {
+				id: 1,
+				children: vec![],
+				transform: Transform::new(),
+				meshes: vec![],
+				skin: None,
+				weights: vec![],
+				name: None,
+			},
+			2 => NodeData {
+				id: 2,
+				children: vec![],
+				transform: Transform::new(),
+				meshes: vec![],
+				skin: None,
+				weights: vec![],
+				name: None,
+			},
+		],
+		root_nodes: vec![0, 1, 2],
+		img: None,
+		materials: vec![],
+		skins: vec![Skin {
+			joints: [vec![2; 99], vec![1]].concat(),
+			inverse_binds: vec![mat4!(); 100],
+		}],
+		clips: vec![AnimationClip::new(None, hmap![
+			1 => Anim {
+				pos: Some(Track::linear(&[(0.0, vec3!(0)), (1.0, vec3!(16, 0, 0))])),
+				..Anim::default()
+			},
+		])],
+	};
+
+	let model = Model::from_data(gfx, data).unwrap();
+
+	for (t, x) in &[(0.0, 0.0), (1.0, 16.0)] {
+
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.draw(&shapes::model(&model).time(*t));
+		}).unwrap();
+
+		assert!(canvas.read(vec2!(32.0 + x, 32)).r > 0.9);
+		assert!(canvas.read(vec2!(48.0 - x, 32)).r < 0.1);
+
+	}
+
+}
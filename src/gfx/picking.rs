@@ -0,0 +1,330 @@
+// wengwengweng
+
+use super::*;
+
+// ids are stored + 1, 0 is for things drawn without an id
+const MAX_ID: u32 = 0xfffffffe;
+// without gl 3 ids are packed in the rgb of an rgba8 target
+const MAX_ID_RGB: u32 = 0xfffffe;
+
+fn encode(id: Option<u32>, int: bool) -> UniformData {
+
+	let id = id.map(|i| i + 1).unwrap_or(0);
+
+	if int {
+		// same bits, read back as uint in the shader
+		return UniformData::Int(id as i32);
+	}
+
+	return UniformData::Vec4(vec4!(
+		(id & 0xff) as f32 / 255.0,
+		((id >> 8) & 0xff) as f32 / 255.0,
+		((id >> 16) & 0xff) as f32 / 255.0,
+		1.0
+	));
+
+}
+
+fn decode(px: &[u8]) -> Option<u32> {
+
+	let id = px[0] as u32 | (px[1] as u32) << 8 | (px[2] as u32) << 16;
+
+	if px[3] == 0 || id == 0 {
+		return None;
+	}
+
+	return Some(id - 1);
+
+}
+
+fn decode_int(id: u32) -> Option<u32> {
+	return id.checked_sub(1);
+}
+
+/// GPU Picking
+///
+/// renders object IDs to an off-screen buffer, then the ID under a point or inside a rect can be queried, IDs are assigned with [`Gfx::pick_id`](struct.Gfx.html#method.pick_id)
+/// ```ignore
+/// // init
+/// let mut picker = Picker::new(d.gfx)?;
+///
+/// // event
+/// picker.event(d.gfx, e)?;
+///
+/// // frame, same drawing code as the normal pass
+/// picker.render(d.gfx, |gfx| {
+///     for (i, obj) in objs.iter().enumerate() {
+///         gfx.pick_id(i as u32, |gfx| {
+///             return gfx.draw_t(obj.transform, &shapes::model(&obj.model));
+///         })?;
+///     }
+///     return Ok(());
+/// })?;
+///
+/// if let Some(id) = picker.at(d.window.mouse_pos()) {
+///     // hovering objs[id]
+/// }
+/// ```
+///
+/// inside a pick pass, custom shaders from [`Gfx::draw_with`](struct.Gfx.html#method.draw_with) are not applied and instanced drawables are drawn one instance at a time, things drawn without an ID still hide what's behind them
+///
+/// IDs are rendered to an `R32UI` target and read back as integers, drivers without gl 3 fall back to packing them in an `RGBA8` target
+pub struct Picker {
+	canvas: Canvas,
+	pipeline: Pipeline<Vertex, Uniform>,
+	int: bool,
+	width: i32,
+	height: i32,
+}
+
+impl Picker {
+
+	/// create with the size of the screen
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Self::with_size(gfx, gfx.width(), gfx.height());
+	}
+
+	pub fn with_size(gfx: &Gfx, w: i32, h: i32) -> Result<Self> {
+
+		let int = gfx.caps.glsl3;
+
+		let pipeline = if int {
+			let vert_src = format!(
+				"{}{}",
+				gfx.caps.glsl3_header(ShaderType::Vertex),
+				shaders::TEMPLATE_VERT.replace("{{user}}", shaders::DEFAULT_VERT),
+			);
+			let frag_src = format!("{}{}", gfx.caps.glsl3_header(ShaderType::Fragment), shaders::PICK_INT_FRAG);
+			Pipeline::new(gfx, &vert_src, &frag_src)?
+		} else {
+			Shader::<()>::from_frag(gfx, shaders::PICK_FRAG)?.pipeline().clone()
+		};
+
+		return Ok(Self {
+			canvas: Self::make_canvas(gfx, w, h, int)?,
+			pipeline: pipeline,
+			int: int,
+			width: w,
+			height: h,
+		});
+
+	}
+
+	fn make_canvas(gfx: &Gfx, w: i32, h: i32, int: bool) -> Result<Canvas> {
+		if int {
+			return Canvas::new_with_conf(gfx, w, h, TextureConf {
+				format: TextureFormat::R32UI,
+				..TextureConf::default()
+			});
+		} else {
+			return Canvas::new(gfx, w, h);
+		}
+	}
+
+	/// recreate buffer with a new size
+	pub fn resize(&mut self, gfx: &Gfx, w: i32, h: i32) -> Result<()> {
+		self.canvas = Self::make_canvas(gfx, w, h, self.int)?;
+		self.width = w;
+		self.height = h;
+		return Ok(());
+	}
+
+	/// recreate buffer on window resize and DPI change
+	pub fn event(&mut self, gfx: &Gfx, e: &input::Event) -> Result<()> {
+
+		use input::Event::*;
+
+		match *e {
+			Resize(w, h) => self.resize(gfx, w, h)?,
+			DPIChange(_) => self.resize(gfx, self.width, self.height)?,
+			_ => {},
+		}
+
+		return Ok(());
+
+	}
+
+	/// run a pick pass, replacing the result of the last one
+	pub fn render(&mut self, gfx: &mut Gfx, f: impl FnOnce(&mut Gfx) -> Result<()>) -> Result<()> {
+
+		let pipeline = &self.pipeline;
+		let int = self.int;
+
+		return gfx.draw_on(&self.canvas, CanvasAction::clear(), |gfx| {
+			return gfx.use_blend(Blend::Replace, |gfx| {
+				return gfx.draw_with_data(pipeline, vec![("u_pick_id", encode(None, int))], |gfx| {
+					gfx.picking = true;
+					let res = f(gfx);
+					gfx.flush();
+					gfx.picking = false;
+					return res;
+				});
+			});
+		});
+
+	}
+
+	// screen position to buffer pixel
+	fn to_px(&self, pt: Vec2) -> (i32, i32) {
+
+		let tex = self.canvas.color_tex();
+		let sx = tex.width() as f32 / self.width as f32;
+		let sy = tex.height() as f32 / self.height as f32;
+
+		return (
+			((pt.x + self.width as f32 / 2.0) * sx) as i32,
+			((pt.y + self.height as f32 / 2.0) * sy) as i32,
+		);
+
+	}
+
+	/// get the ID at a screen position (same coords as drawing and mouse position)
+	pub fn at(&self, pt: Vec2) -> Option<u32> {
+
+		let (x, y) = self.to_px(pt);
+		let tex = self.canvas.color_tex();
+
+		if x < 0 || y < 0 || x >= tex.width() || y >= tex.height() {
+			return None;
+		}
+
+		if self.int {
+			return self.canvas.read_pixels_u32(x, y, 1, 1).first().and_then(|id| decode_int(*id));
+		}
+
+		return decode(&self.canvas.read_pixels(x, y, 1, 1));
+
+	}
+
+	/// get all IDs visible inside a rect, sorted
+	pub fn within(&self, p1: Vec2, p2: Vec2) -> Vec<u32> {
+
+		let tex = self.canvas.color_tex();
+		let (x1, y1) = self.to_px(vec2!(p1.x.min(p2.x), p1.y.min(p2.y)));
+		let (x2, y2) = self.to_px(vec2!(p1.x.max(p2.x), p1.y.max(p2.y)));
+		let (x1, y1) = (x1.max(0), y1.max(0));
+		let (x2, y2) = (x2.min(tex.width()), y2.min(tex.height()));
+
+		if x2 <= x1 || y2 <= y1 {
+			return vec![];
+		}
+
+		let mut ids = if self.int {
+			self.canvas
+				.read_pixels_u32(x1, y1, x2 - x1, y2 - y1)
+				.into_iter()
+				.filter_map(decode_int)
+				.collect::<Vec<u32>>()
+		} else {
+			self.canvas
+				.read_pixels(x1, y1, x2 - x1, y2 - y1)
+				.chunks(4)
+				.filter_map(decode)
+				.collect::<Vec<u32>>()
+		};
+
+		ids.sort();
+		ids.dedup();
+
+		return ids;
+
+	}
+
+	/// the canvas IDs are drawn to
+	pub fn canvas(&self) -> &Canvas {
+		return &self.canvas;
+	}
+
+}
+
+impl Gfx {
+
+	/// assign an ID to everything drawn inside for [`Picker`](struct.Picker.html), only has effect inside [`Picker::render`](struct.Picker.html#method.render)
+	///
+	/// IDs go up to 4294967294 (0xfffffffe), or 16777214 (0xfffffe) on drivers without gl 3
+	pub fn pick_id(
+		&mut self,
+		id: u32,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		if !self.picking {
+			return f(self);
+		}
+
+		let int = self.caps.glsl3;
+		let max = if int { MAX_ID } else { MAX_ID_RGB };
+
+		if id > max {
+			return Err(format!("pick id {} is over max {}", id, max));
+		}
+
+		let prev = self.cur_custom_uniform.clone();
+
+		self.cur_custom_uniform = Some(vec![("u_pick_id", encode(Some(id), int))]);
+		let res = f(self);
+		self.cur_custom_uniform = prev;
+
+		return res;
+
+	}
+
+	/// if currently in a pick pass
+	pub fn picking(&self) -> bool {
+		return self.picking;
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn picking() {
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let mut picker = Picker::new(gfx).unwrap();
+	// over 24 bits if ids are stored as integers
+	let max = if picker.int { MAX_ID } else { MAX_ID_RGB };
+
+	picker.render(gfx, |gfx| {
+		gfx.pick_id(0, |gfx| {
+			return gfx.draw(&shapes::rect(vec2!(-32, -32), vec2!(0, 0)));
+		})?;
+		gfx.pick_id(7, |gfx| {
+			return gfx.draw(&shapes::rect(vec2!(0, 0), vec2!(32, 32)));
+		})?;
+		// instances share the id
+		let tex = Texture::from_raw(gfx, 4, 4, &[255; 4 * 4 * 4])?;
+		let instances = [
+			Instance::new(mat4!().t2(vec2!(8, -24))),
+			Instance::new(mat4!().t2(vec2!(24, -24))),
+		];
+		gfx.pick_id(3, |gfx| {
+			return gfx.draw(&shapes::instanced_sprite(&tex, &instances));
+		})?;
+		// drawn on top without an id
+		gfx.draw(&shapes::rect(vec2!(20, 20), vec2!(32, 32)))?;
+		gfx.pick_id(max, |gfx| {
+			// custom shaders are ignored in pick pass
+			let shader = Shader::<()>::from_frag(gfx, "vec4 frag() { return vec4(0.0); }")?;
+			return gfx.draw_with(&shader, &(), |gfx| {
+				return gfx.draw(&shapes::rect(vec2!(-32, 16), vec2!(-16, 32)));
+			});
+		})?;
+		return Ok(());
+	}).unwrap();
+
+	assert_eq!(picker.at(vec2!(-16, -16)), Some(0));
+	assert_eq!(picker.at(vec2!(8, 8)), Some(7));
+	assert_eq!(picker.at(vec2!(24, 24)), None);
+	assert_eq!(picker.at(vec2!(-24, 24)), Some(max));
+	assert_eq!(picker.at(vec2!(8, -16)), None);
+	assert_eq!(picker.at(vec2!(8, -24)), Some(3));
+	assert_eq!(picker.at(vec2!(24, -24)), Some(3));
+	assert_eq!(picker.at(vec2!(100, 0)), None);
+	assert_eq!(picker.within(vec2!(-32, -32), vec2!(32, 32)), vec![0, 3, 7, max]);
+	assert_eq!(picker.within(vec2!(4, 4), vec2!(12, 12)), vec![7]);
+	assert!(gfx.pick_id(max + 1, |_| Ok(())).is_ok());
+
+}
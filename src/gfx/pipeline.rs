@@ -1,11 +1,15 @@
 // wengwengweng
 
+use std::cell::RefCell;
+
 use super::*;
 
-#[derive(Clone)]
 pub(super) struct Pipeline<V: VertexLayout, U: UniformLayout> {
 	gl: Rc<glow::Context>,
 	gl_prog: Rc<ProgramHandle>,
+	// skinning & morph targets are in a separate program compiled on first use, so other draws stay within the gles 2 attribute limit
+	deform_src: Option<Rc<(String, String)>>,
+	deform: Rc<RefCell<Option<Result<Self>>>>,
 	_vertex_layout: PhantomData<V>,
 	_uniform_layout: PhantomData<U>,
 }
@@ -13,6 +17,17 @@ pub(super) struct Pipeline<V: VertexLayout, U: UniformLayout> {
 impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 
 	pub fn new(ctx: &impl GLCtx, vert_src: &str, frag_src: &str) -> Result<Self> {
+		let mut pip = Self::with_attrs(ctx, vert_src, frag_src, &[])?;
+		pip.deform_src = Some(Rc::new((deform_vert(vert_src), frag_src.to_string())));
+		return Ok(pip);
+	}
+
+	// per instance attributes are bound after the vertex attributes
+	pub fn new_instanced<I: VertexLayout>(ctx: &impl GLCtx, vert_src: &str, frag_src: &str) -> Result<Self> {
+		return Self::with_attrs(ctx, vert_src, frag_src, I::attrs());
+	}
+
+	fn with_attrs(ctx: &impl GLCtx, vert_src: &str, frag_src: &str, extra_attrs: &[(&'static str, usize)]) -> Result<Self> {
 
 		unsafe {
 
@@ -40,7 +55,7 @@ impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 			gl.attach_shader(gl_prog.id(), vert_id);
 			gl.attach_shader(gl_prog.id(), frag_id);
 
-			for (i, (name, _)) in V::attrs().iter().enumerate() {
+			for (i, (name, _)) in V::attrs().iter().chain(extra_attrs).enumerate() {
 				gl.bind_attrib_location(gl_prog.id(), i as u32, name);
 			}
 
@@ -56,6 +71,8 @@ impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 			return Ok(Self {
 				gl: gl,
 				gl_prog: Rc::new(gl_prog),
+				deform_src: None,
+				deform: Rc::new(RefCell::new(None)),
 				_vertex_layout: PhantomData,
 				_uniform_layout: PhantomData,
 			});
@@ -64,6 +81,31 @@ impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 
 	}
 
+	// the variant for skinned & morphed meshes, skinning & morph target attributes are bound after the vertex attributes
+	pub(super) fn deformed(&self) -> Result<Self> {
+
+		let (vert_src, frag_src) = match &self.deform_src {
+			Some(src) => &**src,
+			None => return Err(format!("pipeline doesn't support skinning & morph targets")),
+		};
+
+		let mut deform = self.deform.borrow_mut();
+
+		if deform.is_none() {
+			*deform = Some(
+				Self::with_attrs(&self.gl, vert_src, frag_src, &DEFORM_ATTRS)
+					.map_err(|e| format!("failed to create skinning & morph target shader: {}", e))
+			);
+		}
+
+		return match &*deform {
+			Some(Ok(pip)) => Ok(pip.clone()),
+			Some(Err(e)) => Err(e.clone()),
+			None => Err(format!("failed to create skinning & morph target shader")),
+		};
+
+	}
+
 	pub(super) fn bind(&self) {
 		unsafe {
 			self.gl.use_program(Some(self.gl_prog.id()));
@@ -84,9 +126,32 @@ impl<V: VertexLayout, U: UniformLayout> Pipeline<V, U> {
 
 }
 
+// derive would require V & U to be Clone
+impl<V: VertexLayout, U: UniformLayout> Clone for Pipeline<V, U> {
+	fn clone(&self) -> Self {
+		return Self {
+			gl: self.gl.clone(),
+			gl_prog: self.gl_prog.clone(),
+			deform_src: self.deform_src.clone(),
+			deform: self.deform.clone(),
+			_vertex_layout: PhantomData,
+			_uniform_layout: PhantomData,
+		};
+	}
+}
+
 impl<V: VertexLayout, U: UniformLayout> PartialEq for Pipeline<V, U> {
 	fn eq(&self, other: &Self) -> bool {
 		return self.gl_prog == other.gl_prog;
 	}
 }
 
+// turn on the skinning & morph target parts of the vertex template, defines go after #version
+fn deform_vert(src: &str) -> String {
+	if src.starts_with("#version") {
+		if let Some(i) = src.find('\n') {
+			return format!("{}\n#define DEFORM\n{}", &src[..i], &src[i + 1..]);
+		}
+	}
+	return format!("#define DEFORM\n{}", src);
+}
@@ -0,0 +1,607 @@
+// wengwengweng
+
+use super::*;
+
+/// A Post Processing Pass
+pub trait Pass {
+	/// render `src` to `dest`, `scratch` are canvases of the same size for passes that need multiple steps
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, scratch: &[Canvas; 2]) -> Result<()>;
+}
+
+// uniform data built at draw time
+struct PassUniform;
+
+impl UniformLayout for PassUniform {}
+
+fn pass_shader(gfx: &Gfx, frag: &str) -> Result<Shader<PassUniform>> {
+	return Shader::from_frag(gfx, frag);
+}
+
+fn resolution(gfx: &Gfx, c: &Canvas) -> Vec2 {
+	return vec2!(c.width(), c.height()) * gfx.dpi();
+}
+
+fn blit_data(
+	gfx: &mut Gfx,
+	src: &Canvas,
+	dest: &Canvas,
+	pipeline: &Pipeline<Vertex, Uniform>,
+	mut data: Vec<(&'static str, UniformData)>,
+) -> Result<()> {
+
+	data.push(("u_resolution", UniformData::Vec2(resolution(gfx, src))));
+
+	return gfx.draw_on(dest, CanvasAction::clear(), |gfx| {
+		return gfx.use_blend(Blend::Replace, |gfx| {
+			return gfx.draw_with_data(pipeline, data, |gfx| {
+				return gfx.draw(&shapes::canvas(src));
+			});
+		});
+	});
+
+}
+
+/// draw a canvas onto another through a shader, useful for custom passes
+///
+/// `u_resolution` (vec2) is set to the pixel size of `src`
+pub fn blit<U: UniformLayout>(gfx: &mut Gfx, src: &Canvas, dest: &Canvas, shader: &Shader<U>, uniform: &U) -> Result<()> {
+	return blit_data(gfx, src, dest, shader.pipeline(), uniform.data());
+}
+
+/// Post Processing Stack
+///
+/// owns the intermediate canvases and runs a list of [`Pass`](trait.Pass.html)es over what's drawn
+/// ```ignore
+/// // init
+/// let pp = PostProcess::new(d.gfx)?;
+/// let bloom = Bloom::new(d.gfx)?;
+/// let vignette = Vignette::new(d.gfx)?;
+///
+/// // event
+/// pp.event(d.gfx, e)?;
+///
+/// // draw
+/// pp.draw(d.gfx, &[&bloom, &vignette], |gfx| {
+///     gfx.draw(&shapes::sprite(&tex))?;
+///     return Ok(());
+/// })?;
+/// ```
+pub struct PostProcess {
+	canvases: [Canvas; 2],
+	scratch: [Canvas; 2],
+	width: i32,
+	height: i32,
+	samples: u8,
+}
+
+impl PostProcess {
+
+	/// create with the size of the screen
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Self::with_size(gfx, gfx.width(), gfx.height());
+	}
+
+	pub fn with_size(gfx: &Gfx, w: i32, h: i32) -> Result<Self> {
+		return Self::with_samples(gfx, w, h, 0);
+	}
+
+	/// scene is drawn to a [multisampled](struct.Canvas.html#method.multisampled) canvas
+	pub fn with_samples(gfx: &Gfx, w: i32, h: i32, samples: u8) -> Result<Self> {
+
+		let conf = TextureConf {
+			filter: FilterMode::Linear,
+			wrap_x: WrapMode::ClampToEdge,
+			wrap_y: WrapMode::ClampToEdge,
+			..TextureConf::default()
+		};
+
+		let c = || Canvas::new_with_conf(gfx, w, h, conf);
+
+		return Ok(Self {
+			canvases: [Canvas::multisampled(gfx, w, h, samples, &[conf])?, c()?],
+			scratch: [c()?, c()?],
+			width: w,
+			height: h,
+			samples: samples,
+		});
+
+	}
+
+	/// recreate canvases with a new size
+	pub fn resize(&mut self, gfx: &Gfx, w: i32, h: i32) -> Result<()> {
+		*self = Self::with_samples(gfx, w, h, self.samples)?;
+		return Ok(());
+	}
+
+	/// recreate canvases on window resize and DPI change
+	pub fn event(&mut self, gfx: &Gfx, e: &input::Event) -> Result<()> {
+
+		use input::Event::*;
+
+		match *e {
+			Resize(w, h) => self.resize(gfx, w, h)?,
+			DPIChange(_) => self.resize(gfx, self.width, self.height)?,
+			_ => {},
+		}
+
+		return Ok(());
+
+	}
+
+	pub fn width(&self) -> i32 {
+		return self.width;
+	}
+
+	pub fn height(&self) -> i32 {
+		return self.height;
+	}
+
+	/// draw to a canvas and run passes in order, returning the canvas with the result
+	pub fn render(
+		&mut self,
+		gfx: &mut Gfx,
+		passes: &[&dyn Pass],
+		f: impl FnOnce(&mut Gfx) -> Result<()>,
+	) -> Result<&Canvas> {
+
+		gfx.draw_on(&self.canvases[0], CanvasAction::clear(), f)?;
+
+		let mut cur = 0;
+
+		for p in passes {
+			let (a, b) = self.canvases.split_at(1);
+			let (src, dest) = if cur == 0 { (&a[0], &b[0]) } else { (&b[0], &a[0]) };
+			p.apply(gfx, src, dest, &self.scratch)?;
+			cur = 1 - cur;
+		}
+
+		return Ok(&self.canvases[cur]);
+
+	}
+
+	/// draw to a canvas, run passes in order and draw the result
+	pub fn draw(
+		&mut self,
+		gfx: &mut Gfx,
+		passes: &[&dyn Pass],
+		f: impl FnOnce(&mut Gfx) -> Result<()>,
+	) -> Result<()> {
+		let c = self.render(gfx, passes, f)?;
+		return gfx.draw(&shapes::canvas(c));
+	}
+
+}
+
+/// Custom Pass with a [`Shader`](struct.Shader.html)
+///
+/// the shader reads the previous result from `u_tex`, and `u_resolution` (vec2) is set to its pixel size
+pub struct ShaderPass<U: UniformLayout> {
+	shader: Shader<U>,
+	pub uniform: U,
+}
+
+impl<U: UniformLayout> ShaderPass<U> {
+	pub fn new(shader: Shader<U>, uniform: U) -> Self {
+		return Self {
+			shader: shader,
+			uniform: uniform,
+		};
+	}
+}
+
+impl<U: UniformLayout> Pass for ShaderPass<U> {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit(gfx, src, dest, &self.shader, &self.uniform);
+	}
+}
+
+/// Gaussian Blur
+pub struct Blur {
+	/// spread in pixels
+	pub radius: f32,
+	shader: Shader<PassUniform>,
+}
+
+impl Blur {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			radius: 1.0,
+			shader: pass_shader(gfx, shaders::BLUR_FRAG)?,
+		});
+	}
+
+	pub fn radius(mut self, r: f32) -> Self {
+		self.radius = r;
+		return self;
+	}
+
+	// horizontal from src to tmp, vertical from tmp to dest
+	fn blur(&self, gfx: &mut Gfx, src: &Canvas, tmp: &Canvas, dest: &Canvas) -> Result<()> {
+		blit_data(gfx, src, tmp, self.shader.pipeline(), vec![
+			("u_dir", UniformData::Vec2(vec2!(self.radius, 0))),
+		])?;
+		blit_data(gfx, tmp, dest, self.shader.pipeline(), vec![
+			("u_dir", UniformData::Vec2(vec2!(0, self.radius))),
+		])?;
+		return Ok(());
+	}
+
+}
+
+impl Pass for Blur {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, scratch: &[Canvas; 2]) -> Result<()> {
+		return self.blur(gfx, src, &scratch[0], dest);
+	}
+}
+
+/// Bloom
+pub struct Bloom {
+	/// brightness where things start to glow (0.0 - 1.0)
+	pub threshold: f32,
+	pub intensity: f32,
+	pub blur: Blur,
+	threshold_shader: Shader<PassUniform>,
+	combine_shader: Shader<PassUniform>,
+}
+
+impl Bloom {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			threshold: 0.7,
+			intensity: 1.0,
+			blur: Blur::new(gfx)?.radius(2.0),
+			threshold_shader: pass_shader(gfx, shaders::BLOOM_THRESHOLD_FRAG)?,
+			combine_shader: pass_shader(gfx, shaders::BLOOM_COMBINE_FRAG)?,
+		});
+	}
+
+	pub fn threshold(mut self, t: f32) -> Self {
+		self.threshold = t;
+		return self;
+	}
+
+	pub fn intensity(mut self, i: f32) -> Self {
+		self.intensity = i;
+		return self;
+	}
+
+	pub fn radius(mut self, r: f32) -> Self {
+		self.blur.radius = r;
+		return self;
+	}
+
+}
+
+impl Pass for Bloom {
+
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, scratch: &[Canvas; 2]) -> Result<()> {
+
+		blit_data(gfx, src, &scratch[0], self.threshold_shader.pipeline(), vec![
+			("u_threshold", UniformData::Float(self.threshold)),
+		])?;
+
+		self.blur.blur(gfx, &scratch[0], &scratch[1], &scratch[0])?;
+
+		return blit_data(gfx, src, dest, self.combine_shader.pipeline(), vec![
+			("u_intensity", UniformData::Float(self.intensity)),
+			("u_bloom", UniformData::Texture(scratch[0].color_tex().clone())),
+		]);
+
+	}
+
+}
+
+/// Color Grading
+///
+/// adjusts exposure, contrast and saturation, then optionally maps colors through a LUT texture
+///
+/// a LUT of size n is a (n * n) x n texture, with n slices of blue laid out horizontally, red going right and green going down in each slice
+pub struct ColorGrade {
+	pub exposure: f32,
+	pub contrast: f32,
+	pub saturation: f32,
+	/// how much the LUT is applied (0.0 - 1.0)
+	pub lut_mix: f32,
+	lut: Option<Texture>,
+	shader: Shader<PassUniform>,
+}
+
+impl ColorGrade {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			exposure: 1.0,
+			contrast: 1.0,
+			saturation: 1.0,
+			lut_mix: 1.0,
+			lut: None,
+			shader: pass_shader(gfx, shaders::COLOR_GRADE_FRAG)?,
+		});
+	}
+
+	/// use a LUT texture, its height is the LUT size
+	pub fn lut(mut self, lut: Texture) -> Result<Self> {
+		self.set_lut(Some(lut))?;
+		return Ok(self);
+	}
+
+	pub fn set_lut(&mut self, lut: Option<Texture>) -> Result<()> {
+
+		if let Some(lut) = &lut {
+			if lut.width() != lut.height() * lut.height() {
+				return Err(format!("invalid lut size {}x{}, expected (n * n) x n", lut.width(), lut.height()));
+			}
+		}
+
+		self.lut = lut;
+
+		return Ok(());
+
+	}
+
+	/// an image of a LUT that maps every color to itself, edit it in an image editor to make your own
+	pub fn identity_lut(size: i32) -> img::Image {
+
+		let mut img = img::Image::new(size * size, size);
+		let max = (size - 1).max(1) as f32;
+
+		for b in 0..size {
+			for g in 0..size {
+				for r in 0..size {
+					img.set(b * size + r, g, rgba!(r as f32 / max, g as f32 / max, b as f32 / max, 1)).ok();
+				}
+			}
+		}
+
+		return img;
+
+	}
+
+}
+
+impl Pass for ColorGrade {
+
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+
+		let mut data = vec![
+			("u_exposure", UniformData::Float(self.exposure)),
+			("u_contrast", UniformData::Float(self.contrast)),
+			("u_saturation", UniformData::Float(self.saturation)),
+			("u_lut_mix", UniformData::Float(self.lut_mix)),
+		];
+
+		match &self.lut {
+			Some(lut) => {
+				data.push(("u_lut_size", UniformData::Float(lut.height() as f32)));
+				data.push(("u_lut", UniformData::Texture(lut.clone())));
+			},
+			None => data.push(("u_lut_size", UniformData::Float(0.0))),
+		}
+
+		return blit_data(gfx, src, dest, self.shader.pipeline(), data);
+
+	}
+
+}
+
+/// Darken the Edges
+pub struct Vignette {
+	pub intensity: f32,
+	/// where the darkening ends, 1.0 is the corners
+	pub radius: f32,
+	pub softness: f32,
+	pub color: Color,
+	shader: Shader<PassUniform>,
+}
+
+impl Vignette {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			intensity: 1.0,
+			radius: 1.0,
+			softness: 0.6,
+			color: rgba!(0, 0, 0, 1),
+			shader: pass_shader(gfx, shaders::VIGNETTE_FRAG)?,
+		});
+	}
+
+	pub fn intensity(mut self, i: f32) -> Self {
+		self.intensity = i;
+		return self;
+	}
+
+	pub fn radius(mut self, r: f32) -> Self {
+		self.radius = r;
+		return self;
+	}
+
+	pub fn softness(mut self, s: f32) -> Self {
+		self.softness = s;
+		return self;
+	}
+
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+
+}
+
+impl Pass for Vignette {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit_data(gfx, src, dest, self.shader.pipeline(), vec![
+			("u_intensity", UniformData::Float(self.intensity)),
+			("u_radius", UniformData::Float(self.radius)),
+			("u_softness", UniformData::Float(self.softness)),
+			("u_vignette_color", UniformData::Vec4(self.color.as_vec4())),
+		]);
+	}
+}
+
+/// Split Color Channels towards the Edges
+pub struct ChromaticAberration {
+	/// offset at the edges in pixels
+	pub offset: f32,
+	shader: Shader<PassUniform>,
+}
+
+impl ChromaticAberration {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			offset: 3.0,
+			shader: pass_shader(gfx, shaders::CHROMATIC_ABERRATION_FRAG)?,
+		});
+	}
+
+	pub fn offset(mut self, o: f32) -> Self {
+		self.offset = o;
+		return self;
+	}
+
+}
+
+impl Pass for ChromaticAberration {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit_data(gfx, src, dest, self.shader.pipeline(), vec![
+			("u_offset", UniformData::Float(self.offset)),
+		]);
+	}
+}
+
+/// CRT Screen Curvature & Scanlines
+pub struct Crt {
+	pub curvature: f32,
+	/// darkness of scanlines (0.0 - 1.0)
+	pub scanline: f32,
+	shader: Shader<PassUniform>,
+}
+
+impl Crt {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			curvature: 0.2,
+			scanline: 0.3,
+			shader: pass_shader(gfx, shaders::CRT_FRAG)?,
+		});
+	}
+
+	pub fn curvature(mut self, c: f32) -> Self {
+		self.curvature = c;
+		return self;
+	}
+
+	pub fn scanline(mut self, s: f32) -> Self {
+		self.scanline = s;
+		return self;
+	}
+
+}
+
+impl Pass for Crt {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit_data(gfx, src, dest, self.shader.pipeline(), vec![
+			("u_curvature", UniformData::Float(self.curvature)),
+			("u_scanline", UniformData::Float(self.scanline)),
+		]);
+	}
+}
+
+/// Pixelate
+pub struct Pixelate {
+	/// pixel size in screen pixels
+	pub size: f32,
+	shader: Shader<PassUniform>,
+}
+
+impl Pixelate {
+
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			size: 4.0,
+			shader: pass_shader(gfx, shaders::PIXELATE_FRAG)?,
+		});
+	}
+
+	pub fn size(mut self, s: f32) -> Self {
+		self.size = s;
+		return self;
+	}
+
+}
+
+impl Pass for Pixelate {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit_data(gfx, src, dest, self.shader.pipeline(), vec![
+			("u_size", UniformData::Float(self.size.max(1.0))),
+		]);
+	}
+}
+
+/// Fast Approximate Anti-Aliasing
+pub struct Fxaa {
+	shader: Shader<PassUniform>,
+}
+
+impl Fxaa {
+	pub fn new(gfx: &Gfx) -> Result<Self> {
+		return Ok(Self {
+			shader: pass_shader(gfx, shaders::FXAA_FRAG)?,
+		});
+	}
+}
+
+impl Pass for Fxaa {
+	fn apply(&self, gfx: &mut Gfx, src: &Canvas, dest: &Canvas, _: &[Canvas; 2]) -> Result<()> {
+		return blit_data(gfx, src, dest, self.shader.pipeline(), vec![]);
+	}
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn post_passes() {
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let mut pp = PostProcess::new(gfx).unwrap();
+	let bloom = Bloom::new(gfx).unwrap().threshold(0.5);
+	let pixelate = Pixelate::new(gfx).unwrap().size(8.0);
+	let vignette = Vignette::new(gfx).unwrap();
+	let grade = ColorGrade::new(gfx).unwrap()
+		.lut(Texture::from_img_with_conf(gfx, ColorGrade::identity_lut(16), TextureConf {
+			filter: FilterMode::Linear,
+			wrap_x: WrapMode::ClampToEdge,
+			wrap_y: WrapMode::ClampToEdge,
+			..TextureConf::default()
+		}).unwrap())
+		.unwrap();
+
+	let scene = |gfx: &mut Gfx| {
+		gfx.draw(&shapes::rect(vec2!(-32), vec2!(32)).fill(rgba!(0.2, 0.2, 0.4, 1)))?;
+		gfx.draw(&shapes::circle(vec2!(0), 12.0).fill(rgba!(1, 0.9, 0.5, 1)))?;
+		return Ok(());
+	};
+
+	gfx.begin_frame();
+	let plain = pp.render(gfx, &[], scene).unwrap().capture().unwrap();
+	let graded = pp.render(gfx, &[&grade], scene).unwrap().capture().unwrap();
+
+	// identity lut shouldn't change anything
+	assert!(plain.diff(&graded).unwrap() < 0.01);
+
+	let img = pp.render(gfx, &[&bloom, &pixelate, &vignette], scene).unwrap().capture().unwrap();
+
+	check_golden(&img, concat!(env!("CARGO_MANIFEST_DIR"), "/misc/golden/post.png"), 0.01).unwrap();
+
+	let crt = Crt::new(gfx).unwrap();
+	let ca = ChromaticAberration::new(gfx).unwrap();
+	let fxaa = Fxaa::new(gfx).unwrap();
+
+	assert!(pp.render(gfx, &[&crt, &ca, &fxaa], scene).is_ok());
+
+}
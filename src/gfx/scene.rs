@@ -0,0 +1,478 @@
+// wengwengweng
+
+use std::cell::Cell;
+
+use super::*;
+
+pub type SceneNodeID = usize;
+
+/// Layer Mask That Matches Every Layer
+pub const ALL_LAYERS: u32 = !0;
+
+/// Objects That Can be Attached to a [`SceneNode`](struct.SceneNode.html)
+pub trait SceneObject {
+	/// draw in node local space
+	fn draw(&self, ctx: &mut Gfx) -> Result<()>;
+	/// local bounding box for culling, objects without one are never culled
+	fn bbox(&self) -> Option<BBox> {
+		return None;
+	}
+}
+
+impl SceneObject for Model {
+	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+		return ctx.draw(&shapes::model(self));
+	}
+	fn bbox(&self) -> Option<BBox> {
+		return Some(Model::bbox(self));
+	}
+}
+
+impl<F: Fn(&mut Gfx) -> Result<()>> SceneObject for F {
+	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+		return self(ctx);
+	}
+}
+
+/// A Node in a [`Scene`](struct.Scene.html)
+pub struct SceneNode {
+	pub name: Option<String>,
+	pub transform: Transform,
+	/// hidden nodes hide their children too
+	pub visible: bool,
+	/// bit mask matched against the mask passed to [`draw_layers`](struct.Scene.html#method.draw_layers)
+	pub layers: u32,
+	/// overrides the object bounding box for culling
+	pub bbox: Option<BBox>,
+	pub object: Option<Box<dyn SceneObject>>,
+	parent: Option<SceneNodeID>,
+	children: Vec<SceneNodeID>,
+	// cached world transform, None when dirty
+	world: Cell<Option<Mat4>>,
+}
+
+impl SceneNode {
+
+	/// an empty node for grouping
+	pub fn new() -> Self {
+		return Self {
+			name: None,
+			transform: Transform::new(),
+			visible: true,
+			layers: 1,
+			bbox: None,
+			object: None,
+			parent: None,
+			children: vec![],
+			world: Cell::new(None),
+		};
+	}
+
+	/// a node with an object
+	pub fn with(obj: impl SceneObject + 'static) -> Self {
+		return Self {
+			object: Some(Box::new(obj)),
+			..Self::new()
+		};
+	}
+
+	pub fn name(mut self, name: &str) -> Self {
+		self.name = Some(String::from(name));
+		return self;
+	}
+
+	pub fn transform(mut self, t: Transform) -> Self {
+		self.transform = t;
+		return self;
+	}
+
+	pub fn pos(mut self, p: Vec3) -> Self {
+		self.transform.pos = p;
+		return self;
+	}
+
+	pub fn visible(mut self, b: bool) -> Self {
+		self.visible = b;
+		return self;
+	}
+
+	pub fn layers(mut self, l: u32) -> Self {
+		self.layers = l;
+		return self;
+	}
+
+	pub fn bbox(mut self, b: BBox) -> Self {
+		self.bbox = Some(b);
+		return self;
+	}
+
+	pub fn parent(&self) -> Option<SceneNodeID> {
+		return self.parent;
+	}
+
+	pub fn children(&self) -> &[SceneNodeID] {
+		return &self.children;
+	}
+
+	fn local_bbox(&self) -> Option<BBox> {
+		return self.bbox.or_else(|| self.object.as_ref().and_then(|o| o.bbox()));
+	}
+
+}
+
+/// Retained Node Hierarchy with Cached Transforms & Frustum Culling
+pub struct Scene {
+	nodes: HashMap<SceneNodeID, SceneNode>,
+	roots: Vec<SceneNodeID>,
+	last_id: SceneNodeID,
+}
+
+impl Scene {
+
+	pub fn new() -> Self {
+		return Self {
+			nodes: hmap![],
+			roots: vec![],
+			last_id: 0,
+		};
+	}
+
+	/// add a root node
+	pub fn add(&mut self, node: SceneNode) -> SceneNodeID {
+
+		let id = self.insert(node, None);
+
+		self.roots.push(id);
+
+		return id;
+
+	}
+
+	/// add a node under a parent
+	pub fn add_child(&mut self, parent: SceneNodeID, node: SceneNode) -> Result<SceneNodeID> {
+
+		if !self.nodes.contains_key(&parent) {
+			return Err(format!("scene node not found: {}", parent));
+		}
+
+		let id = self.insert(node, Some(parent));
+
+		if let Some(p) = self.nodes.get_mut(&parent) {
+			p.children.push(id);
+		}
+
+		return Ok(id);
+
+	}
+
+	fn insert(&mut self, mut node: SceneNode, parent: Option<SceneNodeID>) -> SceneNodeID {
+
+		let id = self.last_id;
+
+		self.last_id += 1;
+		node.parent = parent;
+		node.children = vec![];
+		node.world.set(None);
+		self.nodes.insert(id, node);
+
+		return id;
+
+	}
+
+	/// remove a node with all its children
+	pub fn remove(&mut self, id: SceneNodeID) -> bool {
+
+		let parent = match self.nodes.get(&id) {
+			Some(n) => n.parent,
+			None => return false,
+		};
+
+		self.detach(id, parent);
+
+		let mut stack = vec![id];
+
+		while let Some(id) = stack.pop() {
+			if let Some(node) = self.nodes.remove(&id) {
+				stack.extend(node.children);
+			}
+		}
+
+		return true;
+
+	}
+
+	fn detach(&mut self, id: SceneNodeID, parent: Option<SceneNodeID>) {
+		match parent {
+			Some(p) => {
+				if let Some(p) = self.nodes.get_mut(&p) {
+					p.children.retain(|c| *c != id);
+				}
+			},
+			None => self.roots.retain(|c| *c != id),
+		}
+	}
+
+	/// move a node under another parent, or to the root with None
+	pub fn set_parent(&mut self, id: SceneNodeID, parent: Option<SceneNodeID>) -> Result<()> {
+
+		let old = self.nodes
+			.get(&id)
+			.ok_or_else(|| format!("scene node not found: {}", id))?
+			.parent;
+
+		if let Some(p) = parent {
+
+			if !self.nodes.contains_key(&p) {
+				return Err(format!("scene node not found: {}", p));
+			}
+
+			// can't parent to itself or a descendant
+			let mut cur = Some(p);
+
+			while let Some(c) = cur {
+				if c == id {
+					return Err(format!("scene node {} can't be a child of itself", id));
+				}
+				cur = self.nodes.get(&c).and_then(|n| n.parent);
+			}
+
+		}
+
+		self.detach(id, old);
+
+		match parent {
+			Some(p) => {
+				if let Some(p) = self.nodes.get_mut(&p) {
+					p.children.push(id);
+				}
+			},
+			None => self.roots.push(id),
+		}
+
+		if let Some(node) = self.nodes.get_mut(&id) {
+			node.parent = parent;
+		}
+
+		self.invalidate(id);
+
+		return Ok(());
+
+	}
+
+	pub fn get(&self, id: SceneNodeID) -> Option<&SceneNode> {
+		return self.nodes.get(&id);
+	}
+
+	/// get a node for editing, this invalidates the cached transforms of its subtree
+	pub fn get_mut(&mut self, id: SceneNodeID) -> Option<&mut SceneNode> {
+		self.invalidate(id);
+		return self.nodes.get_mut(&id);
+	}
+
+	fn invalidate(&self, id: SceneNodeID) {
+
+		let mut stack = vec![id];
+
+		while let Some(id) = stack.pop() {
+			if let Some(node) = self.nodes.get(&id) {
+				// children of a dirty node are always dirty
+				if node.world.get().is_some() {
+					node.world.set(None);
+					stack.extend(&node.children);
+				}
+			}
+		}
+
+	}
+
+	/// find the first node with a name
+	pub fn find(&self, name: &str) -> Option<SceneNodeID> {
+		return self.nodes
+			.iter()
+			.filter(|(_, n)| n.name.as_deref() == Some(name))
+			.map(|(id, _)| *id)
+			.min();
+	}
+
+	pub fn roots(&self) -> &[SceneNodeID] {
+		return &self.roots;
+	}
+
+	pub fn len(&self) -> usize {
+		return self.nodes.len();
+	}
+
+	/// get world transform of a node
+	pub fn world_transform(&self, id: SceneNodeID) -> Option<Mat4> {
+
+		let node = self.nodes.get(&id)?;
+
+		if let Some(m) = node.world.get() {
+			return Some(m);
+		}
+
+		let parent = match node.parent {
+			Some(p) => self.world_transform(p)?,
+			None => mat4!(),
+		};
+
+		let m = parent * node.transform.as_mat4();
+
+		node.world.set(Some(m));
+
+		return Some(m);
+
+	}
+
+	/// get world space bounding box of a node, without its children
+	pub fn world_bbox(&self, id: SceneNodeID) -> Option<BBox> {
+		let bbox = self.nodes.get(&id)?.local_bbox()?;
+		return Some(bbox.transform(self.world_transform(id)?));
+	}
+
+	/// get visible nodes with objects on any of the layers inside a frustum, in draw order
+	pub fn visible_nodes(&self, frustum: Option<Frustum>, layers: u32) -> Vec<SceneNodeID> {
+
+		let mut list = vec![];
+		let mut stack = self.roots.iter().rev().cloned().collect::<Vec<SceneNodeID>>();
+
+		while let Some(id) = stack.pop() {
+
+			let node = match self.nodes.get(&id) {
+				Some(n) => n,
+				None => continue,
+			};
+
+			if !node.visible {
+				continue;
+			}
+
+			stack.extend(node.children.iter().rev());
+
+			if node.object.is_none() || node.layers & layers == 0 {
+				continue;
+			}
+
+			if let (Some(f), Some(bbox)) = (frustum, self.world_bbox(id)) {
+				if !col::frustum_box(f, bbox) {
+					continue;
+				}
+			}
+
+			list.push(id);
+
+		}
+
+		return list;
+
+	}
+
+	/// draw every layer with a camera, returns the number of drawn nodes
+	pub fn draw(&self, ctx: &mut Gfx, cam: &dyn Camera) -> Result<usize> {
+		return self.draw_layers(ctx, cam, ALL_LAYERS);
+	}
+
+	/// draw nodes on any of the layers with a camera, returns the number of drawn nodes
+	pub fn draw_layers(&self, ctx: &mut Gfx, cam: &dyn Camera, layers: u32) -> Result<usize> {
+
+		let mut count = 0;
+
+		ctx.use_cam(cam, |gfx| {
+
+			// nodes are drawn under the current transform
+			let frustum = Frustum::from_mat(cam.proj() * cam.view() * gfx.transform());
+
+			for id in self.visible_nodes(Some(frustum), layers) {
+
+				let tr = match self.world_transform(id) {
+					Some(t) => t,
+					None => continue,
+				};
+
+				if let Some(obj) = self.nodes.get(&id).and_then(|n| n.object.as_ref()) {
+					gfx.push_t(tr, |gfx| {
+						return obj.draw(gfx);
+					})?;
+					count += 1;
+				}
+
+			}
+
+			return Ok(());
+
+		})?;
+
+		return Ok(count);
+
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn scene() {
+
+	let unit = BBox::new(vec3!(-1), vec3!(1));
+	let noop = |_: &mut Gfx| Ok(());
+	let mut scene = Scene::new();
+
+	let root = scene.add(SceneNode::new().pos(vec3!(0, 0, -10)));
+	let a = scene.add_child(root, SceneNode::with(noop).bbox(unit).name("a")).unwrap();
+	let b = scene.add_child(a, SceneNode::with(noop).bbox(unit).pos(vec3!(2, 0, 0))).unwrap();
+	let behind = scene.add(SceneNode::with(noop).bbox(unit).pos(vec3!(0, 0, 10)));
+	let hidden = scene.add_child(root, SceneNode::new().visible(false)).unwrap();
+	let _ = scene.add_child(hidden, SceneNode::with(noop)).unwrap();
+	let ui = scene.add(SceneNode::with(noop).layers(2));
+
+	assert_eq!(scene.find("a"), Some(a));
+	assert_eq!(scene.world_transform(b).map(|m| m * vec3!(0)), Some(vec3!(2, 0, -10)));
+
+	// cached transforms follow parent changes
+	scene.get_mut(root).unwrap().transform.pos = vec3!(0, 0, -20);
+	assert_eq!(scene.world_transform(b).map(|m| m * vec3!(0)), Some(vec3!(2, 0, -20)));
+	assert_eq!(scene.world_bbox(b).map(|b| b.center()), Some(vec3!(2, 0, -20)));
+
+	scene.set_parent(b, None).unwrap();
+	assert_eq!(scene.world_transform(b).map(|m| m * vec3!(0)), Some(vec3!(2, 0, 0)));
+	scene.set_parent(b, Some(a)).unwrap();
+	assert!(scene.set_parent(root, Some(b)).is_err());
+
+	let cam = PerspectiveCam {
+		fov: f32::to_radians(60.0),
+		up: vec3!(0, 1, 0),
+		aspect: 1.0,
+		near: 0.1,
+		far: 100.0,
+		pos: vec3!(0),
+		dir: vec3!(0, 0, -1),
+	};
+
+	let frustum = Some(cam.frustum());
+
+	assert_eq!(scene.visible_nodes(frustum, ALL_LAYERS), vec![a, b, ui]);
+	assert_eq!(scene.visible_nodes(frustum, 1), vec![a, b]);
+	assert_eq!(scene.visible_nodes(None, 1), vec![a, b, behind]);
+
+	// out of the sides
+	scene.get_mut(a).unwrap().transform.pos = vec3!(-30, 0, 0);
+	assert!(scene.visible_nodes(frustum, 1).is_empty());
+
+	let ortho = OrthoCam {
+		width: 8.0,
+		height: 8.0,
+		near: -100.0,
+		far: 100.0,
+	};
+
+	assert_eq!(scene.visible_nodes(Some(ortho.frustum()), 1), vec![behind]);
+
+	assert!(scene.remove(root));
+	assert_eq!(scene.len(), 2);
+	assert_eq!(scene.roots(), &[behind, ui]);
+
+	let mut h = test_headless(8, 8);
+
+	assert_eq!(scene.draw(h.gfx(), &ortho).unwrap(), 2);
+	assert_eq!(scene.draw_layers(h.gfx(), &ortho, 2).unwrap(), 1);
+
+}
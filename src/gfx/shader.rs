@@ -33,7 +33,7 @@ impl<U: UniformLayout> Shader<U> {
 	pub fn from_vert_frag(ctx: &impl GLCtx, vert: &str, frag: &str) -> Result<Self> {
 
 		let vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", vert);
-		let frag_src = shaders::TEMPLATE_FRAG.replace("{{user}}", frag);
+		let frag_src = shaders::frag(shaders::TEMPLATE_FRAG, frag);
 		#[cfg(any(web, mobile))]
 		let frag_src = format!("{}{}", "precision mediump float;", frag_src);
 
@@ -44,6 +44,41 @@ impl<U: UniformLayout> Shader<U> {
 
 	}
 
+	/// create shader that writes to multiple targets of a [`Canvas`](struct.Canvas.html#method.with_targets) from fragment code
+	///
+	/// instead of returning a color, `frag()` writes each target with `frag_data[i]`, needs gl 3 / gles 3 / webgl 2 or `GL_EXT_draw_buffers` on gles 2
+	/// ```glsl
+	/// void frag() {
+	///     frag_data[0] = default_color();
+	///     frag_data[1] = vec4(normalize(v_normal) * 0.5 + 0.5, 1.0);
+	/// }
+	/// ```
+	pub fn from_frag_mrt(ctx: &Gfx, frag: &str) -> Result<Self> {
+
+		let caps = &ctx.caps;
+		let vert_src = shaders::TEMPLATE_VERT.replace("{{user}}", shaders::DEFAULT_VERT);
+		let frag_src = shaders::frag(shaders::TEMPLATE_MRT_FRAG, frag);
+
+		let (vert_src, frag_src) = if caps.glsl3 {
+			(
+				format!("{}{}", caps.glsl3_header(ShaderType::Vertex), vert_src),
+				format!("{}out vec4 frag_data[{}];\n{}", caps.glsl3_header(ShaderType::Fragment), MAX_CANVAS_TARGETS, frag_src),
+			)
+		} else if !caps.es {
+			(vert_src, format!("#define frag_data gl_FragData\n{}", frag_src))
+		} else if caps.ext_draw_buffers {
+			(vert_src, format!("#extension GL_EXT_draw_buffers : require\nprecision mediump float;\n#define frag_data gl_FragData\n{}", frag_src))
+		} else {
+			return Err(format!("multiple render targets are not supported by the driver"));
+		};
+
+		return Ok(Self {
+			pipeline: Pipeline::new(ctx, &vert_src, &frag_src)?,
+			_custom_uniform: PhantomData,
+		});
+
+	}
+
 	/// create default shader
 	pub fn default(ctx: &impl GLCtx) -> Result<Self> {
 		return Self::from_vert_frag(ctx, shaders::DEFAULT_VERT, shaders::DEFAULT_FRAG);
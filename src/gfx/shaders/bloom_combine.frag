@@ -0,0 +1,13 @@
+// wengwengweng
+
+uniform sampler2D u_bloom;
+uniform float u_intensity;
+
+vec4 frag() {
+
+	vec4 c = texture2D(u_tex, v_uv);
+	vec3 bloom = texture2D(u_bloom, v_uv).rgb * u_intensity;
+
+	return vec4(c.rgb + bloom, max(c.a, max(bloom.r, max(bloom.g, bloom.b))));
+
+}
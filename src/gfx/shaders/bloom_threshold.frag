@@ -0,0 +1,12 @@
+// wengwengweng
+
+uniform float u_threshold;
+
+vec4 frag() {
+
+	vec4 c = texture2D(u_tex, v_uv);
+	float l = dot(c.rgb, vec3(0.2126, 0.7152, 0.0722));
+
+	return vec4(c.rgb * smoothstep(u_threshold, u_threshold + 0.1, l), 1.0);
+
+}
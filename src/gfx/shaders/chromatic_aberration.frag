@@ -0,0 +1,19 @@
+// wengwengweng
+
+uniform float u_offset;
+uniform vec2 u_resolution;
+
+vec4 frag() {
+
+	// stronger towards the edges
+	vec2 d = (v_uv - vec2(0.5)) * 2.0 * u_offset / u_resolution;
+	vec4 c = texture2D(u_tex, v_uv);
+
+	return vec4(
+		texture2D(u_tex, v_uv + d).r,
+		c.g,
+		texture2D(u_tex, v_uv - d).b,
+		c.a
+	);
+
+}
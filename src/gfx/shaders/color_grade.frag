@@ -0,0 +1,40 @@
+// wengwengweng
+
+uniform sampler2D u_lut;
+uniform float u_lut_size;
+uniform float u_lut_mix;
+uniform float u_exposure;
+uniform float u_contrast;
+uniform float u_saturation;
+
+// blue slices are laid out horizontally, red goes right and green goes down in each slice
+vec3 lut(vec3 c) {
+
+	float n = u_lut_size;
+	float b = c.b * (n - 1.0);
+	float b0 = floor(b);
+	float b1 = min(b0 + 1.0, n - 1.0);
+	vec2 uv = vec2((c.r * (n - 1.0) + 0.5) / (n * n), (c.g * (n - 1.0) + 0.5) / n);
+	vec3 c0 = texture2D(u_lut, uv + vec2(b0 / n, 0.0)).rgb;
+	vec3 c1 = texture2D(u_lut, uv + vec2(b1 / n, 0.0)).rgb;
+
+	return mix(c0, c1, b - b0);
+
+}
+
+vec4 frag() {
+
+	vec4 c = texture2D(u_tex, v_uv);
+	vec3 col = c.rgb * u_exposure;
+
+	col = (col - 0.5) * u_contrast + 0.5;
+	col = mix(vec3(dot(col, vec3(0.2126, 0.7152, 0.0722))), col, u_saturation);
+	col = clamp(col, 0.0, 1.0);
+
+	if (u_lut_size > 0.0) {
+		col = mix(col, lut(col), u_lut_mix);
+	}
+
+	return vec4(col, c.a);
+
+}
@@ -0,0 +1,23 @@
+// wengwengweng
+
+uniform float u_curvature;
+uniform float u_scanline;
+uniform vec2 u_resolution;
+
+vec4 frag() {
+
+	vec2 uv = v_uv * 2.0 - 1.0;
+	vec2 offset = uv.yx * u_curvature;
+
+	uv = (uv + uv * offset * offset) * 0.5 + 0.5;
+
+	if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+		return vec4(0.0, 0.0, 0.0, 1.0);
+	}
+
+	vec4 c = texture2D(u_tex, uv);
+	float s = sin(uv.y * u_resolution.y * 3.14159) * 0.5 + 0.5;
+
+	return vec4(c.rgb * mix(1.0, s, u_scanline), c.a);
+
+}
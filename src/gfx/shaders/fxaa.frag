@@ -0,0 +1,57 @@
+// wengwengweng
+
+// https://github.com/mattdesl/glsl-fxaa
+
+uniform vec2 u_resolution;
+
+#define FXAA_REDUCE_MIN (1.0 / 128.0)
+#define FXAA_REDUCE_MUL (1.0 / 8.0)
+#define FXAA_SPAN_MAX 8.0
+
+vec4 frag() {
+
+	vec2 px = 1.0 / u_resolution;
+	vec3 nw = texture2D(u_tex, v_uv + vec2(-1.0, -1.0) * px).rgb;
+	vec3 ne = texture2D(u_tex, v_uv + vec2(1.0, -1.0) * px).rgb;
+	vec3 sw = texture2D(u_tex, v_uv + vec2(-1.0, 1.0) * px).rgb;
+	vec3 se = texture2D(u_tex, v_uv + vec2(1.0, 1.0) * px).rgb;
+	vec4 m = texture2D(u_tex, v_uv);
+
+	vec3 luma = vec3(0.299, 0.587, 0.114);
+	float l_nw = dot(nw, luma);
+	float l_ne = dot(ne, luma);
+	float l_sw = dot(sw, luma);
+	float l_se = dot(se, luma);
+	float l_m = dot(m.rgb, luma);
+	float l_min = min(l_m, min(min(l_nw, l_ne), min(l_sw, l_se)));
+	float l_max = max(l_m, max(max(l_nw, l_ne), max(l_sw, l_se)));
+
+	vec2 dir = vec2(
+		-((l_nw + l_ne) - (l_sw + l_se)),
+		((l_nw + l_sw) - (l_ne + l_se))
+	);
+
+	float reduce = max((l_nw + l_ne + l_sw + l_se) * (0.25 * FXAA_REDUCE_MUL), FXAA_REDUCE_MIN);
+	float rcp_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+
+	dir = clamp(dir * rcp_min, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * px;
+
+	vec3 a = 0.5 * (
+		texture2D(u_tex, v_uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+		texture2D(u_tex, v_uv + dir * (2.0 / 3.0 - 0.5)).rgb
+	);
+
+	vec3 b = a * 0.5 + 0.25 * (
+		texture2D(u_tex, v_uv + dir * -0.5).rgb +
+		texture2D(u_tex, v_uv + dir * 0.5).rgb
+	);
+
+	float l_b = dot(b, luma);
+
+	if (l_b < l_min || l_b > l_max) {
+		return vec4(a, m.a);
+	}
+
+	return vec4(b, m.a);
+
+}
@@ -0,0 +1,217 @@
+// wengwengweng
+
+#define MAX_LIGHTS 8
+#define PI 3.14159265
+
+struct Light {
+	int kind;
+	vec3 pos;
+	vec3 dir;
+	vec3 color;
+	float intensity;
+	float range;
+	float inner;
+	float outer;
+};
+
+varying vec3 v_world_normal;
+varying vec3 v_view_dir;
+
+uniform Light u_lights[MAX_LIGHTS];
+uniform int u_light_count;
+uniform int u_shading;
+uniform vec3 u_ambient;
+
+uniform float u_mat_metallic;
+uniform float u_mat_roughness;
+uniform float u_mat_normal_scale;
+uniform float u_mat_occlusion_strength;
+uniform vec3 u_mat_emissive;
+uniform vec3 u_mat_ambient;
+uniform int u_mat_has_specular;
+uniform vec3 u_mat_specular;
+uniform int u_mat_has_mr_tex;
+uniform int u_mat_has_normal_tex;
+uniform int u_mat_has_emissive_tex;
+uniform int u_mat_has_occlusion_tex;
+uniform sampler2D u_mat_mr_tex;
+uniform sampler2D u_mat_normal_tex;
+uniform sampler2D u_mat_emissive_tex;
+uniform sampler2D u_mat_occlusion_tex;
+
+vec3 to_linear(vec3 c) {
+	return pow(c, vec3(2.2));
+}
+
+vec3 to_srgb(vec3 c) {
+	return pow(c, vec3(1.0 / 2.2));
+}
+
+vec3 get_normal() {
+
+	vec3 n = normalize(v_world_normal);
+
+	if (!gl_FrontFacing) {
+		n = -n;
+	}
+
+	if (u_mat_has_normal_tex == 1) {
+
+		// cotangent frame from screen space derivatives, no vertex tangents needed
+		vec3 dp1 = dFdx(v_world_pos);
+		vec3 dp2 = dFdy(v_world_pos);
+		vec2 duv1 = dFdx(v_uv);
+		vec2 duv2 = dFdy(v_uv);
+		vec3 dp2perp = cross(dp2, n);
+		vec3 dp1perp = cross(n, dp1);
+		vec3 t = dp2perp * duv1.x + dp1perp * duv2.x;
+		vec3 b = dp2perp * duv1.y + dp1perp * duv2.y;
+		float invmax = inversesqrt(max(max(dot(t, t), dot(b, b)), 0.000001));
+		// uv v goes down, tangent space y goes up
+		mat3 tbn = mat3(t * invmax, -b * invmax, n);
+		vec3 m = texture2D(u_mat_normal_tex, v_uv).xyz * 2.0 - 1.0;
+
+		m.xy *= u_mat_normal_scale;
+		n = normalize(tbn * m);
+
+	}
+
+	return n;
+
+}
+
+// direction to light and attenuation
+float light_dir(Light light, out vec3 l) {
+
+	if (light.kind == 0) {
+		l = normalize(-light.dir);
+		return 1.0;
+	}
+
+	vec3 d = light.pos - v_world_pos;
+	float dist = length(d);
+	float att = 1.0;
+
+	l = d / max(dist, 0.0001);
+
+	if (light.range > 0.0) {
+		float f = clamp(1.0 - pow(dist / light.range, 4.0), 0.0, 1.0);
+		att = f * f;
+	}
+
+	if (light.kind == 2) {
+		att *= smoothstep(light.outer, light.inner, dot(-l, normalize(light.dir)));
+	}
+
+	return att;
+
+}
+
+vec3 blinn_phong(vec3 n, vec3 v, vec3 l, vec3 albedo, float metallic, float roughness) {
+
+	vec3 h = normalize(l + v);
+	float a = roughness * roughness;
+	float shininess = clamp(2.0 / (a * a) - 2.0, 1.0, 512.0);
+	vec3 spec_color = mix(vec3(0.04), albedo, metallic);
+
+	if (u_mat_has_specular == 1) {
+		spec_color = to_linear(u_mat_specular);
+	}
+
+	float spec = pow(max(dot(n, h), 0.0), shininess) * (shininess + 8.0) / 8.0;
+
+	return albedo * (1.0 - metallic) + spec_color * spec;
+
+}
+
+vec3 cook_torrance(vec3 n, vec3 v, vec3 l, vec3 albedo, float metallic, float roughness) {
+
+	vec3 h = normalize(l + v);
+	float nl = max(dot(n, l), 0.0);
+	float nv = max(dot(n, v), 0.0001);
+	float nh = max(dot(n, h), 0.0);
+	float vh = max(dot(v, h), 0.0);
+
+	// ggx distribution
+	float a = roughness * roughness;
+	float a2 = a * a;
+	float df = nh * nh * (a2 - 1.0) + 1.0;
+	float d = a2 / (PI * df * df);
+
+	// schlick-ggx geometry
+	float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+	float g = (nv / (nv * (1.0 - k) + k)) * (nl / (nl * (1.0 - k) + k));
+
+	// schlick fresnel
+	vec3 f0 = mix(vec3(0.04), albedo, metallic);
+	vec3 f = f0 + (1.0 - f0) * pow(1.0 - vh, 5.0);
+
+	vec3 spec = d * g * f / max(4.0 * nl * nv, 0.0001);
+	vec3 kd = (vec3(1.0) - f) * (1.0 - metallic);
+
+	// light intensity is in pi units so both models are equally bright
+	return kd * albedo + spec * PI;
+
+}
+
+vec4 frag() {
+
+	vec4 base = default_color();
+	vec3 albedo = to_linear(base.rgb);
+	float metallic = u_mat_metallic;
+	float roughness = u_mat_roughness;
+	float ao = 1.0;
+	vec3 emissive = to_linear(u_mat_emissive);
+
+	if (u_mat_has_mr_tex == 1) {
+		vec4 mr = texture2D(u_mat_mr_tex, v_uv);
+		roughness *= mr.g;
+		metallic *= mr.b;
+	}
+
+	if (u_mat_has_occlusion_tex == 1) {
+		ao = 1.0 + u_mat_occlusion_strength * (texture2D(u_mat_occlusion_tex, v_uv).r - 1.0);
+	}
+
+	if (u_mat_has_emissive_tex == 1) {
+		emissive *= to_linear(texture2D(u_mat_emissive_tex, v_uv).rgb);
+	}
+
+	roughness = clamp(roughness, 0.04, 1.0);
+	metallic = clamp(metallic, 0.0, 1.0);
+
+	vec3 n = get_normal();
+	vec3 v = normalize(v_view_dir);
+	vec3 color = to_linear(u_ambient) * to_linear(u_mat_ambient) * albedo * ao;
+
+	for (int i = 0; i < MAX_LIGHTS; i++) {
+
+		if (i >= u_light_count) {
+			break;
+		}
+
+		vec3 l;
+		float att = light_dir(u_lights[i], l) * shadow_of(i, v_world_pos);
+		float nl = max(dot(n, l), 0.0);
+
+		if (att <= 0.0 || nl <= 0.0) {
+			continue;
+		}
+
+		vec3 radiance = to_linear(u_lights[i].color) * u_lights[i].intensity * att;
+		vec3 brdf;
+
+		if (u_shading == 0) {
+			brdf = blinn_phong(n, v, l, albedo, metallic, roughness);
+		} else {
+			brdf = cook_torrance(n, v, l, albedo, metallic, roughness);
+		}
+
+		color += brdf * radiance * nl;
+
+	}
+
+	return vec4(to_srgb(color + emissive), base.a);
+
+}
+
@@ -0,0 +1,27 @@
+// wengwengweng
+
+varying vec3 v_world_normal;
+varying vec3 v_view_dir;
+
+uniform mat4 u_normal_mat;
+
+vec4 vert() {
+
+	vec4 pos = u_model * vec4(v_pos, 1.0);
+
+	v_world_normal = (u_normal_mat * vec4(v_normal, 0.0)).xyz;
+
+	if (u_proj[3][3] == 1.0) {
+		// orthographic, every pixel looks the same direction
+		v_view_dir = vec3(u_view[0].z, u_view[1].z, u_view[2].z);
+	} else {
+		// camera position from a rigid view matrix
+		vec3 t = u_view[3].xyz;
+		vec3 cam_pos = -vec3(dot(u_view[0].xyz, t), dot(u_view[1].xyz, t), dot(u_view[2].xyz, t));
+		v_view_dir = cam_pos - pos.xyz;
+	}
+
+	return u_proj * u_view * pos;
+
+}
+
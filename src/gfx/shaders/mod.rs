@@ -2,6 +2,34 @@
 
 pub const TEMPLATE_VERT: &str = include_str!("template.vert");
 pub const DEFAULT_VERT: &str = include_str!("default.vert");
+pub const TEMPLATE_INSTANCED_VERT: &str = include_str!("template_instanced.vert");
 pub const TEMPLATE_FRAG: &str = include_str!("template.frag");
+pub const TEMPLATE_MRT_FRAG: &str = include_str!("template_mrt.frag");
 pub const DEFAULT_FRAG: &str = include_str!("default.frag");
+pub const LIT_VERT: &str = include_str!("lit.vert");
+pub const LIT_FRAG: &str = include_str!("lit.frag");
+pub const SKYBOX_VERT: &str = include_str!("skybox.vert");
+pub const SKYBOX_FRAG: &str = include_str!("skybox.frag");
+pub const SDF_FRAG: &str = include_str!("sdf.frag");
 
+pub const BLUR_FRAG: &str = include_str!("blur.frag");
+pub const BLOOM_THRESHOLD_FRAG: &str = include_str!("bloom_threshold.frag");
+pub const BLOOM_COMBINE_FRAG: &str = include_str!("bloom_combine.frag");
+pub const COLOR_GRADE_FRAG: &str = include_str!("color_grade.frag");
+pub const VIGNETTE_FRAG: &str = include_str!("vignette.frag");
+pub const CHROMATIC_ABERRATION_FRAG: &str = include_str!("chromatic_aberration.frag");
+pub const CRT_FRAG: &str = include_str!("crt.frag");
+pub const PIXELATE_FRAG: &str = include_str!("pixelate.frag");
+pub const FXAA_FRAG: &str = include_str!("fxaa.frag");
+pub const PICK_FRAG: &str = include_str!("pick.frag");
+pub const PICK_INT_FRAG: &str = include_str!("pick_int.frag");
+pub const SHADOW_FRAG: &str = include_str!("shadow.frag");
+pub const SHADOW_LIB: &str = include_str!("shadow_lib.glsl");
+
+// fill a fragment template with user code, shadow functions are only added when the user code calls them so other pipelines don't pay for the extra uniforms & sampler
+pub fn frag(template: &str, user: &str) -> String {
+	if user.contains("shadow_of") || user.contains("default_shadow") {
+		return template.replace("{{user}}", &format!("{}\n{}", SHADOW_LIB, user));
+	}
+	return template.replace("{{user}}", user);
+}
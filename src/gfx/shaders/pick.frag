@@ -0,0 +1,11 @@
+// wengwengweng
+
+uniform vec4 u_pick_id;
+
+vec4 frag() {
+	if (default_color().a < 0.1) {
+		discard;
+	}
+	return u_pick_id;
+}
+
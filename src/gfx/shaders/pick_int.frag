@@ -0,0 +1,23 @@
+// wengwengweng
+
+varying vec3 v_pos;
+varying vec3 v_normal;
+varying vec2 v_uv;
+varying vec4 v_color;
+
+uniform sampler2D u_tex;
+uniform vec4 u_color;
+uniform int u_pick_id;
+
+out uvec4 o_pick_id;
+
+void main() {
+
+	if ((v_color * u_color * texture2D(u_tex, v_uv)).a < 0.1) {
+		discard;
+	}
+
+	// keeps the bits of the unsigned id
+	o_pick_id = uvec4(uint(u_pick_id), 0u, 0u, 0u);
+
+}
@@ -0,0 +1,9 @@
+// wengwengweng
+
+uniform float u_size;
+uniform vec2 u_resolution;
+
+vec4 frag() {
+	vec2 cell = vec2(u_size) / u_resolution;
+	return texture2D(u_tex, (floor(v_uv / cell) + 0.5) * cell);
+}
@@ -0,0 +1,32 @@
+// wengwengweng
+
+// distance field glyphs, alpha 0.5 is the edge
+uniform float u_sdf_outline;
+uniform vec4 u_sdf_outline_color;
+uniform float u_sdf_glow;
+uniform vec4 u_sdf_glow_color;
+
+vec4 frag() {
+
+	float d = texture2D(u_tex, v_uv).a;
+	float w = max(fwidth(d) * 0.75, 0.001);
+	vec4 fill = v_color * u_color;
+	vec4 col = vec4(fill.rgb, fill.a * smoothstep(0.5 - w, 0.5 + w, d));
+
+	if (u_sdf_outline > 0.0) {
+		float a = smoothstep(0.5 - u_sdf_outline - w, 0.5 - u_sdf_outline + w, d);
+		vec4 outline = vec4(u_sdf_outline_color.rgb, u_sdf_outline_color.a * a);
+		col = mix(outline, fill, smoothstep(0.5 - w, 0.5 + w, d));
+	}
+
+	if (u_sdf_glow > 0.0) {
+		float a = u_sdf_glow_color.a * smoothstep(0.5 - u_sdf_glow, 0.5, d);
+		float out_a = col.a + a * (1.0 - col.a);
+		if (out_a > 0.0) {
+			col = vec4((col.rgb * col.a + u_sdf_glow_color.rgb * a * (1.0 - col.a)) / out_a, out_a);
+		}
+	}
+
+	return col;
+
+}
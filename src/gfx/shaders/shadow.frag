@@ -0,0 +1,9 @@
+// wengwengweng
+
+vec4 frag() {
+	if (default_color().a < 0.1) {
+		discard;
+	}
+	return vec4(1.0);
+}
+
@@ -0,0 +1,79 @@
+// wengwengweng
+
+#define MAX_SHADOW_MAPS 4
+
+varying float v_view_depth;
+
+uniform int u_shadow_count;
+uniform int u_no_shadow;
+uniform int u_shadow_pcf;
+uniform float u_shadow_bias;
+uniform vec2 u_shadow_texel;
+uniform mat4 u_shadow_mats[MAX_SHADOW_MAPS];
+uniform int u_shadow_lights[MAX_SHADOW_MAPS];
+uniform float u_shadow_splits[MAX_SHADOW_MAPS];
+// uv rect (x, y, w, h) of each map in the atlas
+uniform vec4 u_shadow_rects[MAX_SHADOW_MAPS];
+uniform sampler2D u_shadow_map;
+
+float shadow_pcf(int i, vec3 world_pos) {
+
+	vec4 p = u_shadow_mats[i] * vec4(world_pos, 1.0);
+	vec3 c = p.xyz / p.w * 0.5 + 0.5;
+
+	if (c.x < 0.0 || c.x > 1.0 || c.y < 0.0 || c.y > 1.0 || c.z > 1.0) {
+		return 1.0;
+	}
+
+	// keep filter taps inside this map's tile
+	vec4 rect = u_shadow_rects[i];
+	vec2 uv = rect.xy + c.xy * rect.zw;
+	vec2 lo = rect.xy + u_shadow_texel * 0.5;
+	vec2 hi = rect.xy + rect.zw - u_shadow_texel * 0.5;
+
+	float lit = 0.0;
+	float count = 0.0;
+
+	for (int x = -2; x <= 2; x++) {
+		for (int y = -2; y <= 2; y++) {
+			if (x < -u_shadow_pcf || x > u_shadow_pcf || y < -u_shadow_pcf || y > u_shadow_pcf) {
+				continue;
+			}
+			float d = texture2D(u_shadow_map, clamp(uv + vec2(float(x), float(y)) * u_shadow_texel, lo, hi)).r;
+			lit += c.z - u_shadow_bias > d ? 0.0 : 1.0;
+			count += 1.0;
+		}
+	}
+
+	return lit / count;
+
+}
+
+// how much a world position is lit by a light, 0 is fully in shadow
+// cascades are picked by the view depth of the current fragment, ordered from near to far
+float shadow_of(int light, vec3 world_pos) {
+
+	if (u_no_shadow == 1) {
+		return 1.0;
+	}
+
+	for (int i = 0; i < MAX_SHADOW_MAPS; i++) {
+		if (i >= u_shadow_count) {
+			break;
+		}
+		if (u_shadow_lights[i] == light && v_view_depth <= u_shadow_splits[i]) {
+			return shadow_pcf(i, world_pos);
+		}
+	}
+
+	return 1.0;
+
+}
+
+// shadow term of the first shadow casting light at the current fragment
+float default_shadow() {
+	if (u_shadow_count == 0) {
+		return 1.0;
+	}
+	return shadow_of(u_shadow_lights[0], v_world_pos);
+}
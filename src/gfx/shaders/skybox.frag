@@ -0,0 +1,9 @@
+// wengwengweng
+
+uniform samplerCube u_skybox;
+
+varying vec4 v_dir;
+
+vec4 frag() {
+	return u_color * textureCube(u_skybox, v_dir.xyz / v_dir.w);
+}
@@ -0,0 +1,17 @@
+// wengwengweng
+
+uniform mat4 u_skybox_inv;
+
+varying vec4 v_dir;
+
+vec4 vert() {
+
+	// fullscreen quad on the far plane
+	vec4 pos = vec4(v_pos.xy * 2.0, 1.0, 1.0);
+
+	// divided by w per fragment
+	v_dir = u_skybox_inv * pos;
+
+	return pos;
+
+}
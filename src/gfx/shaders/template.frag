@@ -4,6 +4,7 @@ varying vec3 v_pos;
 varying vec3 v_normal;
 varying vec2 v_uv;
 varying vec4 v_color;
+varying vec3 v_world_pos;
 
 uniform sampler2D u_tex;
 uniform vec4 u_color;
@@ -5,15 +5,52 @@ attribute vec3 a_normal;
 attribute vec2 a_uv;
 attribute vec4 a_color;
 
+#ifdef DEFORM
+attribute vec4 a_joints;
+attribute vec4 a_weights;
+attribute vec3 a_morph_pos0;
+attribute vec3 a_morph_normal0;
+attribute vec3 a_morph_pos1;
+attribute vec3 a_morph_normal1;
+attribute vec3 a_morph_pos2;
+attribute vec3 a_morph_normal2;
+attribute vec3 a_morph_pos3;
+attribute vec3 a_morph_normal3;
+#endif
+
 varying vec3 v_pos;
 varying vec3 v_normal;
 varying vec2 v_uv;
 varying vec4 v_color;
+// for shadows & lighting
+varying vec3 v_world_pos;
+varying float v_view_depth;
 
 uniform mat4 u_model;
 uniform mat4 u_view;
 uniform mat4 u_proj;
 
+#ifdef DEFORM
+
+uniform int u_skinned;
+// 4 texels per joint, 1 for each matrix column
+uniform sampler2D u_joint_tex;
+uniform float u_joint_rows;
+uniform int u_morphed;
+uniform vec4 u_morph_weights;
+
+mat4 joint(float i) {
+	float y = (i + 0.5) / u_joint_rows;
+	return mat4(
+		texture2D(u_joint_tex, vec2(0.125, y)),
+		texture2D(u_joint_tex, vec2(0.375, y)),
+		texture2D(u_joint_tex, vec2(0.625, y)),
+		texture2D(u_joint_tex, vec2(0.875, y))
+	);
+}
+
+#endif
+
 vec4 default_pos() {
 	return u_proj * u_view * u_model * vec4(v_pos, 1.0);
 }
@@ -26,6 +63,47 @@ void main() {
 	v_uv = a_uv;
 	v_color = a_color;
 	v_normal = normalize(a_normal);
+
+#ifdef DEFORM
+
+	if (u_morphed == 1) {
+
+		v_pos +=
+			u_morph_weights.x * a_morph_pos0 +
+			u_morph_weights.y * a_morph_pos1 +
+			u_morph_weights.z * a_morph_pos2 +
+			u_morph_weights.w * a_morph_pos3;
+
+		v_normal = normalize(
+			a_normal +
+			u_morph_weights.x * a_morph_normal0 +
+			u_morph_weights.y * a_morph_normal1 +
+			u_morph_weights.z * a_morph_normal2 +
+			u_morph_weights.w * a_morph_normal3
+		);
+
+	}
+
+	if (u_skinned == 1) {
+
+		mat4 skin =
+			a_weights.x * joint(a_joints.x) +
+			a_weights.y * joint(a_joints.y) +
+			a_weights.z * joint(a_joints.z) +
+			a_weights.w * joint(a_joints.w);
+
+		v_pos = (skin * vec4(v_pos, 1.0)).xyz;
+		v_normal = normalize((skin * vec4(v_normal, 0.0)).xyz);
+
+	}
+
+#endif
+
+	vec4 world_pos = u_model * vec4(v_pos, 1.0);
+
+	v_world_pos = world_pos.xyz;
+	v_view_depth = (u_proj * u_view * world_pos).w;
+
 	gl_Position = vert();
 
 }
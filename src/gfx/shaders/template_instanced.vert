@@ -0,0 +1,56 @@
+// wengwengweng
+
+attribute vec3 a_pos;
+attribute vec3 a_normal;
+attribute vec2 a_uv;
+attribute vec4 a_color;
+
+attribute vec4 a_inst_model0;
+attribute vec4 a_inst_model1;
+attribute vec4 a_inst_model2;
+attribute vec4 a_inst_model3;
+attribute vec4 a_inst_color;
+attribute vec4 a_inst_quad;
+
+varying vec3 v_pos;
+varying vec3 v_normal;
+varying vec2 v_uv;
+varying vec4 v_color;
+// for shadows & lighting
+varying vec3 v_world_pos;
+varying float v_view_depth;
+
+uniform mat4 u_model;
+uniform mat4 u_view;
+uniform mat4 u_proj;
+uniform mat4 u_local;
+// texture size for sprites, 0 for meshes
+uniform vec2 u_sprite_size;
+
+mat4 inst_model() {
+	return mat4(a_inst_model0, a_inst_model1, a_inst_model2, a_inst_model3);
+}
+
+vec4 default_pos() {
+	return u_proj * u_view * u_model * inst_model() * u_local * vec4(v_pos, 1.0);
+}
+
+{{user}}
+
+void main() {
+
+	vec2 scale = u_sprite_size.x > 0.0 ? u_sprite_size * a_inst_quad.zw : vec2(1.0);
+
+	v_pos = vec3(a_pos.xy * scale, a_pos.z);
+	v_uv = a_inst_quad.xy + a_uv * a_inst_quad.zw;
+	v_color = a_color * a_inst_color;
+	v_normal = normalize(a_normal);
+
+	vec4 world_pos = u_model * inst_model() * u_local * vec4(v_pos, 1.0);
+
+	v_world_pos = world_pos.xyz;
+	v_view_depth = (u_proj * u_view * world_pos).w;
+
+	gl_Position = vert();
+
+}
@@ -0,0 +1,17 @@
+// wengwengweng
+
+uniform float u_intensity;
+uniform float u_radius;
+uniform float u_softness;
+uniform vec4 u_vignette_color;
+
+vec4 frag() {
+
+	vec4 c = texture2D(u_tex, v_uv);
+	float d = distance(v_uv, vec2(0.5)) * 1.414;
+	float v = 1.0 - smoothstep(u_radius - u_softness, u_radius, d);
+	float t = (1.0 - v) * u_intensity * u_vignette_color.a;
+
+	return vec4(mix(c.rgb, u_vignette_color.rgb, t), max(c.a, t));
+
+}
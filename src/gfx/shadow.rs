@@ -0,0 +1,526 @@
+// wengwengweng
+
+use super::*;
+
+/// Max Number of Shadow Maps, a directional light uses one per cascade
+pub const MAX_SHADOW_MAPS: usize = 4;
+/// Max Number of Cascades for a Directional Light, leaves room for a spot light with the default 3
+pub const MAX_CASCADES: usize = 3;
+
+// maps are tiles in one atlas so they only take 1 texture unit
+const ATLAS_COLS: i32 = 2;
+
+const MAT_NAMES: [&str; MAX_SHADOW_MAPS] = ["u_shadow_mats[0]", "u_shadow_mats[1]", "u_shadow_mats[2]", "u_shadow_mats[3]"];
+const LIGHT_NAMES: [&str; MAX_SHADOW_MAPS] = ["u_shadow_lights[0]", "u_shadow_lights[1]", "u_shadow_lights[2]", "u_shadow_lights[3]"];
+const SPLIT_NAMES: [&str; MAX_SHADOW_MAPS] = ["u_shadow_splits[0]", "u_shadow_splits[1]", "u_shadow_splits[2]", "u_shadow_splits[3]"];
+const RECT_NAMES: [&str; MAX_SHADOW_MAPS] = ["u_shadow_rects[0]", "u_shadow_rects[1]", "u_shadow_rects[2]", "u_shadow_rects[3]"];
+
+/// Shadow Map Settings
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowConf {
+	/// width & height of each shadow map
+	pub size: i32,
+	/// number of cascades for directional lights, 1 to [`MAX_CASCADES`](constant.MAX_CASCADES.html)
+	pub cascades: usize,
+	/// PCF kernel radius in texels, 0 to 2
+	pub pcf: i32,
+	/// depth bias to avoid shadow acne
+	pub bias: f32,
+	/// how far from the camera directional light shadows reach
+	pub distance: f32,
+	/// how far behind the view casters are still rendered for directional lights
+	pub caster_distance: f32,
+	/// far plane for spot lights without a range
+	pub spot_far: f32,
+}
+
+impl Default for ShadowConf {
+	fn default() -> Self {
+		return Self {
+			size: 1024,
+			cascades: 3,
+			pcf: 1,
+			bias: 0.002,
+			distance: 64.0,
+			caster_distance: 64.0,
+			spot_far: 64.0,
+		};
+	}
+}
+
+struct ShadowMap {
+	light: usize,
+	proj: Mat4,
+	view: Mat4,
+	split: f32,
+}
+
+/// Shadow Maps for [`Lighting`](struct.Lighting.html)
+///
+/// renders depth from the view of each light with `shadow` on, only directional and spot lights cast shadows, all maps share one depth texture atlas
+/// ```ignore
+/// // init
+/// let mut shadows = Shadows::new(d.gfx, ShadowConf::default())?;
+/// let lighting = Lighting::default()
+///     .light(Light::directional(vec3!(-1, -2, -1)).shadow(true));
+///
+/// // frame, render shadow maps first, then draw lit & shadowed
+/// shadows.render(d.gfx, &cam, &lighting, |gfx| draw_scene(gfx))?;
+///
+/// d.gfx.use_cam(&cam, |gfx| {
+///     return gfx.use_lighting(&lighting, |gfx| {
+///         return gfx.use_shadows(&shadows, |gfx| draw_scene(gfx));
+///     });
+/// })?;
+/// ```
+///
+/// custom shaders can get the shadow term with `default_shadow()` or `shadow_of(light_index, v_world_pos)` inside [`Gfx::use_shadows`](struct.Gfx.html#method.use_shadows), the shadow functions are only added to fragment shaders that call them
+pub struct Shadows {
+	conf: ShadowConf,
+	shader: Shader<()>,
+	maps: Vec<ShadowMap>,
+	atlas: Option<Canvas>,
+}
+
+impl Shadows {
+
+	pub fn new(gfx: &Gfx, conf: ShadowConf) -> Result<Self> {
+
+		if conf.cascades == 0 || conf.cascades > MAX_CASCADES {
+			return Err(format!("shadow cascades must be 1 to {}", MAX_CASCADES));
+		}
+
+		return Ok(Self {
+			conf: conf,
+			shader: Shader::from_frag(gfx, shaders::SHADOW_FRAG)?,
+			maps: vec![],
+			atlas: None,
+		});
+
+	}
+
+	pub fn conf(&self) -> &ShadowConf {
+		return &self.conf;
+	}
+
+	/// number of shadow maps from the last render
+	pub fn count(&self) -> usize {
+		return self.maps.len();
+	}
+
+	/// get the depth texture atlas holding every shadow map, after the first render
+	pub fn depth_tex(&self) -> Option<&Texture> {
+		return self.atlas.as_ref().map(|c| c.depth_stencil_tex());
+	}
+
+	/// get the uv rect of a shadow map from the last render in the [`depth_tex`](#method.depth_tex) atlas
+	pub fn map_rect(&self, i: usize) -> Option<Quad> {
+		if i < self.maps.len() {
+			return Some(tile_rect(i));
+		}
+		return None;
+	}
+
+	fn atlas(&mut self, gfx: &Gfx) -> Result<Canvas> {
+
+		if let Some(c) = &self.atlas {
+			return Ok(c.clone());
+		}
+
+		let size = self.conf.size * ATLAS_COLS;
+		let canvas = Canvas::depth_only(gfx, size, size)?;
+
+		self.atlas = Some(canvas.clone());
+
+		return Ok(canvas);
+
+	}
+
+	/// render shadow maps for lights with shadows in `lighting`, seen from `cam`, `f` draws the shadow casters and is called once per map
+	pub fn render(
+		&mut self,
+		gfx: &mut Gfx,
+		cam: &dyn Camera,
+		lighting: &Lighting,
+		mut f: impl FnMut(&mut Gfx) -> Result<()>,
+	) -> Result<()> {
+
+		let mut maps = vec![];
+
+		for (i, l) in lighting.lights.iter().enumerate() {
+
+			if !l.shadow {
+				continue;
+			}
+
+			match l.kind {
+				LightKind::Directional => {
+					for (proj, view, split) in self.cascades(cam, l) {
+						maps.push((i, proj, view, split));
+					}
+				},
+				LightKind::Spot => {
+					let (proj, view) = self.spot(l);
+					maps.push((i, proj, view, f32::MAX));
+				},
+				LightKind::Point => {},
+			}
+
+		}
+
+		if maps.len() > MAX_SHADOW_MAPS {
+			return Err(format!("too many shadow maps: {}, max {}", maps.len(), MAX_SHADOW_MAPS));
+		}
+
+		self.maps.clear();
+
+		let shader = self.shader.clone();
+		let atlas = self.atlas(gfx)?;
+		let size = self.conf.size;
+
+		gfx.draw_on(&atlas, CanvasAction::clear(), |gfx| {
+
+			for (i, (_, proj, view, _)) in maps.iter().enumerate() {
+
+				let i = i as i32;
+
+				unsafe {
+					gfx.gl.viewport(i % ATLAS_COLS * size, i / ATLAS_COLS * size, size, size);
+				}
+
+				gfx.use_cam(&RawCam { proj: *proj, view: *view }, |gfx| {
+					return gfx.draw_with_data(shader.pipeline(), vec![], |gfx| {
+						gfx.shadow_pass = true;
+						let res = f(gfx);
+						gfx.flush();
+						gfx.shadow_pass = false;
+						return res;
+					});
+				})?;
+
+			}
+
+			return Ok(());
+
+		})?;
+
+		self.maps = maps
+			.into_iter()
+			.map(|(light, proj, view, split)| ShadowMap {
+				light: light,
+				proj: proj,
+				view: view,
+				split: split,
+			})
+			.collect();
+
+		return Ok(());
+
+	}
+
+	fn spot(&self, l: &Light) -> (Mat4, Mat4) {
+
+		let far = if l.range > 0.0 { l.range } else { self.conf.spot_far };
+
+		let cam = PerspectiveCam {
+			up: up_for(l.dir),
+			dir: l.dir,
+			pos: l.pos,
+			fov: (l.outer * 2.0).min(f32::to_radians(170.0)),
+			aspect: 1.0,
+			near: far * 0.001,
+			far: far,
+		};
+
+		return (cam.proj(), cam.view());
+
+	}
+
+	// split the camera frustum and fit a light space box around each part
+	fn cascades(&self, cam: &dyn Camera, l: &Light) -> Vec<(Mat4, Mat4, f32)> {
+
+		let proj = cam.proj();
+		let inv = (proj * cam.view()).inverse();
+
+		let corner = |x: f32, y: f32, z: f32| {
+			let p = inv * vec4!(x, y, z, 1.0);
+			return p.xyz() / p.w;
+		};
+
+		let xy = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+		let near_pts = xy.iter().map(|(x, y)| corner(*x, *y, -1.0)).collect::<Vec<Vec3>>();
+		let far_pts = xy.iter().map(|(x, y)| corner(*x, *y, 1.0)).collect::<Vec<Vec3>>();
+
+		let is_ortho = proj.get(3, 3).cloned() == Some(1.0);
+		let a = proj.get(2, 2).cloned().unwrap_or(0.0);
+		let b = proj.get(3, 2).cloned().unwrap_or(0.0);
+
+		let (near, far, count) = if is_ortho {
+			(0.0, 1.0, 1)
+		} else {
+			(-b / (a + 1.0), b / (1.0 - a), self.conf.cascades)
+		};
+
+		let max = if is_ortho { far } else { far.min(self.conf.distance) };
+
+		// practical split scheme, half log half uniform
+		let split = |i: usize| {
+			let t = i as f32 / count as f32;
+			let log = near * (max / near).powf(t);
+			let uni = near + (max - near) * t;
+			return log * 0.5 + uni * 0.5;
+		};
+
+		return (0..count).map(|i| {
+
+			let (d1, d2) = (split(i), split(i + 1));
+			let (t1, t2) = ((d1 - near) / (far - near), (d2 - near) / (far - near));
+
+			let pts = near_pts
+				.iter()
+				.zip(far_pts.iter())
+				.flat_map(|(n, f)| vec![*n + (*f - *n) * t1, *n + (*f - *n) * t2])
+				.collect::<Vec<Vec3>>();
+
+			let center = pts.iter().fold(vec3!(0), |c, p| c + *p) / pts.len() as f32;
+			let radius = pts
+				.iter()
+				.map(|p| (*p - center).len())
+				.fold(0.0f32, f32::max)
+				.max(0.001);
+
+			let view = PerspectiveCam {
+				up: up_for(l.dir),
+				dir: l.dir,
+				pos: vec3!(0),
+				fov: 0.0,
+				aspect: 1.0,
+				near: 0.0,
+				far: 0.0,
+			}.view();
+
+			// snap to texels so shadows don't shimmer when the camera moves
+			let texel = radius * 2.0 / self.conf.size as f32;
+			let c = view * center;
+			let c = vec3!((c.x / texel).floor() * texel, (c.y / texel).floor() * texel, c.z);
+
+			let proj = ortho(
+				c.x - radius,
+				c.x + radius,
+				c.y - radius,
+				c.y + radius,
+				c.z - radius - self.conf.caster_distance,
+				c.z + radius,
+			);
+
+			let split = if is_ortho { f32::MAX } else { d2 };
+
+			return (proj, view, split);
+
+		}).collect();
+
+	}
+
+}
+
+// uv rect of the ith tile in the atlas
+fn tile_rect(i: usize) -> Quad {
+	let s = 1.0 / ATLAS_COLS as f32;
+	let i = i as i32;
+	return quad!((i % ATLAS_COLS) as f32 * s, (i / ATLAS_COLS) as f32 * s, s, s);
+}
+
+// an up vector that's not parallel to dir
+fn up_for(dir: Vec3) -> Vec3 {
+	if dir.unit().y.abs() > 0.99 {
+		return vec3!(0, 0, 1);
+	} else {
+		return vec3!(0, 1, 0);
+	}
+}
+
+// orthographic projection for views looking at +z
+fn ortho(l: f32, r: f32, b: f32, t: f32, n: f32, f: f32) -> Mat4 {
+	return Mat4::new([
+		-2.0 / (r - l), 0.0, 0.0, 0.0,
+		0.0, 2.0 / (t - b), 0.0, 0.0,
+		0.0, 0.0, 2.0 / (f - n), 0.0,
+		(r + l) / (r - l), -(t + b) / (t - b), -(f + n) / (f - n), 1.0,
+	]);
+}
+
+impl UniformLayout for Shadows {
+
+	fn data(&self) -> Vec<(&'static str, UniformData)> {
+
+		let size = self.conf.size * ATLAS_COLS;
+
+		let mut values = vec![
+			("u_shadow_count", UniformData::Int(self.maps.len() as i32)),
+			("u_no_shadow", UniformData::Int(0)),
+			("u_shadow_pcf", UniformData::Int(self.conf.pcf.max(0).min(2))),
+			("u_shadow_bias", UniformData::Float(self.conf.bias)),
+			("u_shadow_texel", UniformData::Vec2(vec2!(1.0 / size as f32))),
+		];
+
+		if let Some(tex) = self.depth_tex() {
+			values.push(("u_shadow_map", UniformData::Texture(tex.clone())));
+		}
+
+		for (i, m) in self.maps.iter().enumerate() {
+			let r = tile_rect(i);
+			values.extend(vec![
+				(MAT_NAMES[i], UniformData::Mat4(m.proj * m.view)),
+				(LIGHT_NAMES[i], UniformData::Int(m.light as i32)),
+				(SPLIT_NAMES[i], UniformData::Float(m.split)),
+				(RECT_NAMES[i], UniformData::Vec4(vec4!(r.x, r.y, r.w, r.h))),
+			]);
+		}
+
+		return values;
+
+	}
+
+}
+
+impl Gfx {
+
+	/// draw everything inside with shadows from [`Shadows`](struct.Shadows.html), works with [`use_lighting`](struct.Gfx.html#method.use_lighting) and custom shaders
+	pub fn use_shadows(
+		&mut self,
+		shadows: &Shadows,
+		f: impl FnOnce(&mut Self) -> Result<()>,
+	) -> Result<()> {
+
+		if self.picking || self.shadow_pass {
+			return f(self);
+		}
+
+		let prev = self.cur_custom_uniform.clone();
+		let mut data = prev.clone().unwrap_or_default();
+
+		data.extend(shadows.data());
+		self.cur_custom_uniform = Some(data);
+		let res = f(self);
+		self.cur_custom_uniform = prev;
+
+		return res;
+
+	}
+
+	/// if currently in a shadow map pass
+	pub fn shadow_pass(&self) -> bool {
+		return self.shadow_pass;
+	}
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn shadows() {
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 64, 64).unwrap();
+	let cube = Mesh::from_meshdata(gfx, &geom::meshgen::cube()).unwrap();
+
+	let cam = PerspectiveCam {
+		fov: f32::to_radians(60.0),
+		up: vec3!(0, 1, 0),
+		aspect: 1.0,
+		near: 0.1,
+		far: 100.0,
+		pos: vec3!(0, 10, 0.01),
+		dir: vec3!(0, -1, -0.001).unit(),
+	};
+
+	// a floor with a box above the center
+	let scene = |gfx: &mut Gfx, cast: bool| {
+		gfx.draw_t(mat4!().t3(vec3!(0, -0.5, 0)).s3(vec3!(20, 1, 20)), &shapes::mesh(&cube))?;
+		gfx.draw_t(mat4!().t3(vec3!(0, 2, 0)), &shapes::mesh(&cube).cast_shadow(cast))?;
+		return Ok(());
+	};
+
+	for light in &[
+		Light::directional(vec3!(0, -1, 0)),
+		Light::spot(vec3!(0, 8, 0), vec3!(0, -1, 0), f32::to_radians(60.0)),
+	] {
+
+		let lighting = Lighting::default()
+			.ambient(rgba!(0, 0, 0, 1))
+			.light(light.shadow(true));
+
+		for cast in &[true, false] {
+
+			let mut shadows = Shadows::new(gfx, ShadowConf {
+				distance: 20.0,
+				..ShadowConf::default()
+			}).unwrap();
+
+			shadows.render(gfx, &cam, &lighting, |gfx| scene(gfx, *cast)).unwrap();
+
+			if let LightKind::Directional = light.kind {
+				assert_eq!(shadows.count(), 3);
+			} else {
+				assert_eq!(shadows.count(), 1);
+			}
+
+			gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+				return gfx.use_cam(&cam, |gfx| {
+					return gfx.use_lighting(&lighting, |gfx| {
+						return gfx.use_shadows(&shadows, |gfx| {
+							// only the floor, so the box doesn't cover the shadow
+							return gfx.draw_t(mat4!().t3(vec3!(0, -0.5, 0)).s3(vec3!(20, 1, 20)), &shapes::mesh(&cube));
+						});
+					});
+				});
+			}).unwrap();
+
+			let center = canvas.read(vec2!(32, 32));
+			let edge = canvas.read(vec2!(4, 4));
+
+			assert!(edge.r > 0.3);
+			assert_eq!(center.r < 0.1, *cast);
+
+		}
+
+	}
+
+	// a sun with all cascades and a spot light fit in the atlas together
+	let both = Lighting::default()
+		.ambient(rgba!(0, 0, 0, 1))
+		.light(Light::directional(vec3!(0, -1, 0)).shadow(true))
+		.light(Light::spot(vec3!(0, 8, 0), vec3!(0, -1, 0), f32::to_radians(60.0)).shadow(true));
+
+	let mut shadows = Shadows::new(gfx, ShadowConf {
+		distance: 20.0,
+		..ShadowConf::default()
+	}).unwrap();
+
+	shadows.render(gfx, &cam, &both, |gfx| scene(gfx, true)).unwrap();
+	assert_eq!(shadows.count(), 4);
+	assert_eq!(shadows.map_rect(3), Some(quad!(0.5, 0.5, 0.5, 0.5)));
+
+	gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+		return gfx.use_cam(&cam, |gfx| {
+			return gfx.use_lighting(&both, |gfx| {
+				return gfx.use_shadows(&shadows, |gfx| {
+					return gfx.draw_t(mat4!().t3(vec3!(0, -0.5, 0)).s3(vec3!(20, 1, 20)), &shapes::mesh(&cube));
+				});
+			});
+		});
+	}).unwrap();
+
+	assert!(canvas.read(vec2!(4, 4)).r > 0.3);
+	assert!(canvas.read(vec2!(32, 32)).r < 0.1);
+
+	let too_many = Lighting::default()
+		.light(Light::directional(vec3!(0, -1, 0)).shadow(true))
+		.light(Light::directional(vec3!(1, -1, 0)).shadow(true));
+
+	let mut shadows = Shadows::new(gfx, ShadowConf::default()).unwrap();
+
+	assert!(shadows.render(gfx, &cam, &too_many, |_| Ok(())).is_err());
+
+}
@@ -0,0 +1,342 @@
+// wengwengweng
+
+use std::collections::HashMap;
+
+use super::*;
+use gfx::Instance;
+use gfx::InstanceData;
+
+#[derive(Clone, Copy)]
+enum Target<'a> {
+	Mesh(&'a gfx::Mesh),
+	Model(&'a gfx::Model),
+	Sprite(&'a gfx::Texture),
+}
+
+/// Draw Many Copies in One Draw Call
+///
+/// instancing needs OpenGL 3.3 / GLES 3.0 / WebGL 2, custom shaders from [`draw_with`](../struct.Gfx.html#method.draw_with) are not applied
+///
+/// without instancing, in a [`Picker`](../struct.Picker.html) pass, and for models with skinned or morphed meshes (each draw needs its own joints & weights), instances are drawn one by one with the normal shapes, instance quads are ignored for models
+#[derive(Clone)]
+pub struct Instanced<'a> {
+	target: Target<'a>,
+	instances: &'a [Instance],
+	prim: Primitive,
+	tex: Option<&'a gfx::Texture>,
+	color: Color,
+	time: f32,
+}
+
+/// instanced [`Mesh`](../struct.Mesh.html)
+pub fn instanced_mesh<'a>(m: &'a gfx::Mesh, instances: &'a [Instance]) -> Instanced<'a> {
+	return Instanced::new(Target::Mesh(m), instances);
+}
+
+/// instanced [`Model`](../struct.Model.html)
+pub fn instanced_model<'a>(m: &'a gfx::Model, instances: &'a [Instance]) -> Instanced<'a> {
+	return Instanced::new(Target::Model(m), instances);
+}
+
+/// instanced sprite, each instance's quad selects the texture region like [`Sprite::quad`](struct.Sprite.html#method.quad)
+pub fn instanced_sprite<'a>(tex: &'a gfx::Texture, instances: &'a [Instance]) -> Instanced<'a> {
+	return Instanced::new(Target::Sprite(tex), instances);
+}
+
+impl<'a> Instanced<'a> {
+	fn new(target: Target<'a>, instances: &'a [Instance]) -> Self {
+		return Self {
+			target: target,
+			instances: instances,
+			prim: Primitive::Triangle,
+			tex: None,
+			color: rgba!(1),
+			time: 0.0,
+		};
+	}
+	/// texture for meshes
+	pub fn texture(mut self, tex: &'a gfx::Texture) -> Self {
+		self.tex = Some(tex);
+		return self;
+	}
+	pub fn prim(mut self, prim: gfx::Primitive) -> Self {
+		self.prim = prim;
+		return self;
+	}
+	/// color multiplied with each instance's color
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+	pub fn opacity(mut self, a: f32) -> Self {
+		self.color.a = a;
+		return self;
+	}
+	/// animation time for models
+	pub fn time(mut self, t: f32) -> Self {
+		self.time = t;
+		return self;
+	}
+}
+
+impl<'a> Drawable for Instanced<'a> {
+
+	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+
+		if self.instances.is_empty() {
+			return Ok(());
+		}
+
+		let deformed = match self.target {
+			Target::Model(m) => m.deformed(),
+			_ => false,
+		};
+
+		// the pick pipeline doesn't take instance attributes
+		if ctx.picking || deformed || !ctx.caps.instancing {
+			return self.draw_each(ctx);
+		}
+
+		match self.target {
+			Target::Mesh(m) => {
+				let tex = self.tex.unwrap_or(&ctx.empty_tex).clone();
+				let custom = ctx.cur_custom_uniform.clone();
+				draw_mesh(ctx, self, m, &tex, self.color, custom, Mat4::identity(), vec2!(0))?;
+			},
+			Target::Sprite(tex) => {
+				let size = vec2!(tex.width(), tex.height());
+				let quad = ctx.quad_mesh.clone();
+				let custom = ctx.cur_custom_uniform.clone();
+				draw_mesh(ctx, self, &quad, tex, self.color, custom, Mat4::identity(), size)?;
+			},
+			Target::Model(m) => {
+				let transforms = m.node_transforms(self.time);
+				for id in m.root_nodes() {
+					draw_node(ctx, self, m, &transforms, *id)?;
+				}
+			},
+		}
+
+		return Ok(());
+
+	}
+
+}
+
+impl<'a> Instanced<'a> {
+
+	// one draw per instance
+	fn draw_each(&self, ctx: &mut Gfx) -> Result<()> {
+
+		for i in self.instances {
+
+			let color = self.color * i.color;
+
+			match self.target {
+				Target::Mesh(m) => {
+					let mut shape = mesh(m).prim(self.prim).color(color);
+					if let Some(tex) = self.tex {
+						shape = shape.texture(tex);
+					}
+					ctx.draw_t(i.transform, &shape)?;
+				},
+				Target::Sprite(tex) => {
+					ctx.draw_t(i.transform, &sprite(tex).quad(i.quad).color(color))?;
+				},
+				Target::Model(m) => {
+					ctx.draw_t(i.transform, &model(m).prim(self.prim).time(self.time).color(color))?;
+				},
+			}
+
+		}
+
+		return Ok(());
+
+	}
+
+}
+
+fn draw_node(ctx: &mut Gfx, dctx: &Instanced, model: &gfx::Model, transforms: &HashMap<usize, Mat4>, id: usize) -> Result<()> {
+
+	if let Some(node) = model.get_node(id) {
+
+		let tr = transforms.get(&id).cloned().unwrap_or(mat4!());
+
+		for (i, m) in node.meshes().iter().enumerate() {
+
+			let mat = node.mesh_material(i).and_then(|i| model.material(i));
+			let mut color = dctx.color;
+			let mut custom = ctx.cur_custom_uniform.clone();
+
+			// per-material textures take priority over the model-wide one
+			let tex = mat
+				.and_then(|m| m.base_color_tex.as_ref())
+				.or(model.texture())
+				.unwrap_or(&ctx.empty_tex)
+				.clone();
+
+			if let Some(mat) = mat {
+				let mut data = custom.unwrap_or_default();
+				data.extend(mat.data());
+				custom = Some(data);
+				color = color * mat.base_color;
+			}
+
+			draw_mesh(ctx, dctx, m, &tex, color, custom, tr, vec2!(0))?;
+
+		}
+
+		for c in node.children() {
+			draw_node(ctx, dctx, model, transforms, *c)?;
+		}
+
+	}
+
+	return Ok(());
+
+}
+
+fn draw_mesh(
+	ctx: &mut Gfx,
+	dctx: &Instanced,
+	mesh: &gfx::Mesh,
+	tex: &gfx::Texture,
+	color: Color,
+	custom: Option<Vec<(&'static str, gfx::UniformData)>>,
+	local: Mat4,
+	sprite_size: Vec2,
+) -> Result<()> {
+
+	let uniform = gfx::InstancedUniform {
+		base: gfx::Uniform {
+			proj: ctx.proj,
+			view: ctx.view,
+			model: ctx.transform,
+			color: color,
+			tex: tex.clone(),
+			custom: custom,
+		},
+		local: local,
+		sprite_size: sprite_size,
+	};
+
+	for chunk in dctx.instances.chunks(gfx::INSTANCE_COUNT) {
+
+		let data = chunk
+			.iter()
+			.map(InstanceData::from)
+			.collect::<Vec<InstanceData>>();
+
+		ctx.renderer.begin_direct(&ctx.gl_state);
+		ctx.instance_buf.data(0, &data);
+
+		gfx::draw_instanced(
+			&ctx.gl,
+			dctx.prim,
+			&ctx.instanced_pipeline,
+			mesh.vbuf(),
+			mesh.ibuf(),
+			mesh.count(),
+			&ctx.instance_buf,
+			chunk.len(),
+			&uniform,
+		);
+
+	}
+
+	return Ok(());
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn instanced_matches() {
+
+	let mut h = gfx::test_headless(64, 64);
+
+	let img = crate::img::Image::from_raw(2, 2, vec![
+		255, 0, 0, 255,
+		0, 255, 0, 255,
+		0, 0, 255, 255,
+		255, 255, 0, 255,
+	]).unwrap();
+
+	let tex = gfx::Texture::from_img(h.gfx(), img.clone()).unwrap();
+	let cube = gfx::Mesh::from_meshdata(h.gfx(), &geom::meshgen::cube()).unwrap();
+
+	let instances = (0..16)
+		.map(|i| {
+			let x = (i % 4) as f32 * 16.0 - 24.0;
+			let y = (i / 4) as f32 * 16.0 - 24.0;
+			return Instance::new(mat4!().t2(vec2!(x, y)).s2(vec2!(3)))
+				.color(rgba!(1, 1, 1, 1.0 - i as f32 / 32.0))
+				.quad(quad!(0.5 * (i % 2) as f32, 0, 0.5, 1));
+		})
+		.collect::<Vec<Instance>>();
+
+	let a = h.render(|gfx| {
+		for i in &instances {
+			gfx.draw_t(i.transform, &sprite(&tex).quad(i.quad).color(i.color))?;
+		}
+		for i in &instances {
+			gfx.draw_t(mat4!().t2(vec2!(4)) * i.transform, &mesh(&cube).color(i.color))?;
+		}
+		return Ok(());
+	}).unwrap();
+
+	let b = h.render(|gfx| {
+		gfx.draw(&instanced_sprite(&tex, &instances))?;
+		gfx.draw_t(mat4!().t2(vec2!(4)), &instanced_mesh(&cube, &instances))?;
+		return Ok(());
+	}).unwrap();
+
+	assert!(a.diff(&b).unwrap() < 0.001);
+
+	// drawn one by one without instancing
+	h.gfx().caps.instancing = false;
+
+	let c = h.render(|gfx| {
+		gfx.draw(&instanced_sprite(&tex, &instances))?;
+		gfx.draw_t(mat4!().t2(vec2!(4)), &instanced_mesh(&cube, &instances))?;
+		return Ok(());
+	}).unwrap();
+
+	h.gfx().caps.instancing = true;
+	assert!(a.diff(&c).unwrap() < 0.001);
+
+	// textures & colors come from each mesh's material, like in glb models
+	let mut data = gfx::Model::load_meshdata(geom::meshgen::cube());
+
+	data.materials.push(gfx::MaterialData {
+		base_color: rgba!(1, 0.5, 1, 1),
+		base_color_img: Some(img),
+		..gfx::MaterialData::default()
+	});
+
+	for n in data.nodes.values_mut() {
+		for p in &mut n.meshes {
+			p.material = Some(0);
+		}
+	}
+
+	let m = gfx::Model::from_data(h.gfx(), data).unwrap();
+	let instances = instances
+		.iter()
+		.map(|i| Instance::new(i.transform.s3(vec3!(2))).color(i.color))
+		.collect::<Vec<Instance>>();
+
+	let a = h.render(|gfx| {
+		for i in &instances {
+			gfx.draw_t(i.transform, &model(&m).color(i.color))?;
+		}
+		return Ok(());
+	}).unwrap();
+
+	let b = h.render(|gfx| {
+		return gfx.draw(&instanced_model(&m, &instances));
+	}).unwrap();
+
+	assert!(a.diff(&b).unwrap() < 0.001);
+	assert!(b.get(4, 52).map(|c| c.a > 0.9 && c.g < 0.6).unwrap_or(false));
+
+}
@@ -8,6 +8,11 @@ pub struct Mesh<'a> {
 	prim: Primitive,
 	tex: Option<&'a gfx::Texture>,
 	color: Color,
+	material: Option<&'a gfx::Material>,
+	cast_shadow: bool,
+	receive_shadow: bool,
+	joints: Option<&'a [Mat4]>,
+	morph_weights: Option<&'a [f32]>,
 }
 
 impl<'a> Mesh<'a> {
@@ -17,6 +22,11 @@ impl<'a> Mesh<'a> {
 			prim: Primitive::Triangle,
 			tex: None,
 			color: rgba!(1),
+			material: None,
+			cast_shadow: true,
+			receive_shadow: true,
+			joints: None,
+			morph_weights: None,
 		};
 	}
 	pub fn texture(mut self, tex: &'a gfx::Texture) -> Self {
@@ -31,6 +41,31 @@ impl<'a> Mesh<'a> {
 		self.color = c;
 		return self;
 	}
+	/// surface properties for [`Gfx::use_lighting`](../struct.Gfx.html#method.use_lighting), base color is also applied when unlit
+	pub fn material(mut self, m: &'a gfx::Material) -> Self {
+		self.material = Some(m);
+		return self;
+	}
+	/// if drawn in [`Shadows::render`](../struct.Shadows.html#method.render)
+	pub fn cast_shadow(mut self, b: bool) -> Self {
+		self.cast_shadow = b;
+		return self;
+	}
+	/// if shadowed inside [`Gfx::use_shadows`](../struct.Gfx.html#method.use_shadows)
+	pub fn receive_shadow(mut self, b: bool) -> Self {
+		self.receive_shadow = b;
+		return self;
+	}
+	/// joint matrices for a [skinned mesh](../struct.Mesh.html#method.with_skin), ignored for other meshes
+	pub fn joints(mut self, j: &'a [Mat4]) -> Self {
+		self.joints = Some(j);
+		return self;
+	}
+	/// weight of each [morph target](../struct.Mesh.html#method.add_morph_target)
+	pub fn morph_weights(mut self, w: &'a [f32]) -> Self {
+		self.morph_weights = Some(w);
+		return self;
+	}
 }
 
 pub fn mesh<'a>(m: &'a gfx::Mesh) -> Mesh<'a> {
@@ -41,26 +76,105 @@ impl<'a> Drawable for Mesh<'a> {
 
 	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
 
-		let tex = self.tex.unwrap_or(&ctx.empty_tex);
-
-		gfx::draw(
-			&ctx.gl,
-			self.prim,
-			&ctx.cur_pipeline,
-			self.mesh.vbuf(),
-			self.mesh.ibuf(),
-			self.mesh.count(),
-			&gfx::Uniform {
-				proj: ctx.proj,
-				view: ctx.view,
-				model: ctx.transform,
-				color: self.color,
-				tex: tex.clone(),
-				custom: ctx.cur_custom_uniform.clone(),
+		if ctx.shadow_pass && !self.cast_shadow {
+			return Ok(());
+		}
+
+		let mut tex = self.tex;
+		let mut color = self.color;
+		let mut custom = ctx.cur_custom_uniform.clone();
+
+		if let Some(mat) = self.material {
+
+			tex = tex.or(mat.base_color_tex.as_ref());
+			color = color * mat.base_color;
+
+			let mut data = custom.unwrap_or_default();
+
+			data.extend(mat.data());
+			custom = Some(data);
+
+		}
+
+		if !self.receive_shadow {
+			let mut data = custom.unwrap_or_default();
+			data.push(("u_no_shadow", gfx::UniformData::Int(1)));
+			custom = Some(data);
+		}
+
+		let skin = match (self.mesh.skin_buf(), self.joints) {
+			(Some(buf), Some(joints)) => {
+				let tex = ctx.joint_tex(joints)?;
+				let mut data = custom.unwrap_or_default();
+				data.push(("u_skinned", gfx::UniformData::Int(1)));
+				data.push(("u_joint_rows", gfx::UniformData::Float(tex.height() as f32)));
+				data.push(("u_joint_tex", gfx::UniformData::Texture(tex)));
+				custom = Some(data);
+				Some(buf)
 			},
-		);
+			_ => None,
+		};
+
+		let mut morphs = vec![];
+
+		if let Some(weights) = self.morph_weights {
+
+			let count = self.mesh.morph_targets().min(gfx::MAX_MORPH_TARGETS);
+
+			if weights.iter().take(count).any(|w| *w != 0.0) {
+
+				let mut w = [0.0; gfx::MAX_MORPH_TARGETS];
+
+				for i in 0..count {
+					w[i] = weights.get(i).cloned().unwrap_or(0.0);
+					morphs.extend(self.mesh.morph_buf(i));
+				}
+
+				let mut data = custom.unwrap_or_default();
+				data.push(("u_morphed", gfx::UniformData::Int(1)));
+				data.push(("u_morph_weights", gfx::UniformData::Vec4(vec4!(w[0], w[1], w[2], w[3]))));
+				custom = Some(data);
+
+			}
+
+		}
+
+		let tex = tex.unwrap_or(&ctx.empty_tex);
+
+		ctx.renderer.begin_direct(&ctx.gl_state);
+
+		let uniform = gfx::Uniform {
+			proj: ctx.proj,
+			view: ctx.view,
+			model: ctx.transform,
+			color: color,
+			tex: tex.clone(),
+			custom: custom,
+		};
 
-		ctx.draw_calls += 1;
+		if skin.is_some() || !morphs.is_empty() {
+			gfx::draw_deformed(
+				&ctx.gl,
+				self.prim,
+				&ctx.cur_pipeline.deformed()?,
+				self.mesh.vbuf(),
+				skin,
+				&morphs,
+				self.mesh.ibuf(),
+				self.mesh.count(),
+				&uniform,
+			);
+		} else {
+			gfx::draw(
+				&ctx.gl,
+				self.prim,
+				&ctx.cur_pipeline,
+				self.mesh.vbuf(),
+				self.mesh.ibuf(),
+				self.mesh.count(),
+				&uniform,
+			);
+		}
 
 		return Ok(());
 
@@ -10,6 +10,7 @@ use gfx::Gfx;
 use gfx::Drawable;
 use gfx::Vertex;
 use gfx::Primitive;
+use gfx::UniformLayout;
 
 export!(raw);
 export!(mesh);
@@ -26,6 +27,8 @@ export!(text);
 export!(model);
 export!(line3d);
 export!(rect3d);
+export!(instanced);
+export!(skybox);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LineDash {
@@ -1,5 +1,7 @@
 // wengwengweng
 
+use std::collections::HashMap;
+
 use super::*;
 
 #[derive(Clone)]
@@ -8,6 +10,12 @@ pub struct Model<'a> {
 	color: Color,
 	prim: Primitive,
 	time: f32,
+	clip: usize,
+	pose: Option<&'a gfx::Pose>,
+	morph_weights: Vec<(usize, &'a [f32])>,
+	pick_base: Option<u32>,
+	cast_shadow: bool,
+	receive_shadow: bool,
 }
 
 pub fn model<'a>(m: &'a gfx::Model) -> Model<'a> {
@@ -21,6 +29,12 @@ impl<'a> Model<'a> {
 			color: rgba!(1),
 			prim: Primitive::Triangle,
 			time: 0.0,
+			clip: 0,
+			pose: None,
+			morph_weights: vec![],
+			pick_base: None,
+			cast_shadow: true,
+			receive_shadow: true,
 		};
 	}
 	pub fn color(mut self, color: Color) -> Self {
@@ -31,22 +45,65 @@ impl<'a> Model<'a> {
 		self.color.a = a;
 		return self;
 	}
+	/// time in the animation clip set by [`clip`](#method.clip)
 	pub fn time(mut self, t: f32) -> Self {
 		self.time = t;
 		return self;
 	}
+	/// animation clip to play with [`time`](#method.time), defaults to the first
+	pub fn clip(mut self, clip: usize) -> Self {
+		self.clip = clip;
+		return self;
+	}
+	/// draw with a pose, e.g. from [`Animator::pose`](../struct.Animator.html#method.pose), overrides time & clip
+	pub fn pose(mut self, pose: &'a gfx::Pose) -> Self {
+		self.pose = Some(pose);
+		return self;
+	}
+	/// override the morph target weights of a node
+	pub fn morph_weights(mut self, node: usize, w: &'a [f32]) -> Self {
+		self.morph_weights.push((node, w));
+		return self;
+	}
 	pub fn prim(mut self, prim: gfx::Primitive) -> Self {
 		self.prim = prim;
 		return self;
 	}
+	/// give each node pick id `base + node id` in pick passes
+	pub fn pick_nodes(mut self, base: u32) -> Self {
+		self.pick_base = Some(base);
+		return self;
+	}
+	/// if drawn in [`Shadows::render`](../struct.Shadows.html#method.render)
+	pub fn cast_shadow(mut self, b: bool) -> Self {
+		self.cast_shadow = b;
+		return self;
+	}
+	/// if shadowed inside [`Gfx::use_shadows`](../struct.Gfx.html#method.use_shadows)
+	pub fn receive_shadow(mut self, b: bool) -> Self {
+		self.receive_shadow = b;
+		return self;
+	}
 }
 
 impl<'a> Drawable for Model<'a> {
 
 	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
 
+		let sampled;
+
+		let pose = match self.pose {
+			Some(pose) => pose,
+			None => {
+				sampled = self.model.sample_clip(self.clip, self.time);
+				&sampled
+			},
+		};
+
+		let transforms = self.model.pose_transforms(pose);
+
 		for t in self.model.root_nodes() {
-			draw_mesh(ctx, &self, Mat4::identity(), *t)?;
+			draw_mesh(ctx, &self, pose, &transforms, *t)?;
 		}
 
 		return Ok(());
@@ -55,43 +112,65 @@ impl<'a> Drawable for Model<'a> {
 
 }
 
-fn draw_mesh(ctx: &mut Gfx, dctx: &Model, ptr: Mat4, id: usize) -> Result<()> {
+fn draw_mesh(ctx: &mut Gfx, dctx: &Model, pose: &gfx::Pose, transforms: &HashMap<usize, Mat4>, id: usize) -> Result<()> {
 
 	let model = &dctx.model;
 
 	if let Some(node) = model.get_node(id) {
 
-		let mut tr = node.transform();
-
-		if let Some(anim) = model.get_anim(id) {
-
-			let (pos, rot, scale) = anim.get_transform(dctx.time);
+		let tr = transforms.get(&id).cloned().unwrap_or(mat4!());
+		let joints = node.skin().and_then(|s| model.joint_matrices(s, tr, transforms));
 
-			tr.pos = pos.unwrap_or(tr.pos);
-			tr.rot = rot.unwrap_or(tr.rot);
-			tr.scale = scale.unwrap_or(tr.scale);
+		let weights = dctx.morph_weights
+			.iter()
+			.rev()
+			.find(|(n, _)| *n == id)
+			.map(|(_, w)| *w)
+			.or(pose.weights.get(&id).map(|w| w.as_slice()))
+			.unwrap_or(node.weights());
 
-		}
-
-		let tr = ptr * tr.as_mat4();
-
-		for m in node.meshes() {
+		for (i, m) in node.meshes().iter().enumerate() {
 
 			let mut shape = mesh(m)
 				.prim(dctx.prim)
 				.color(dctx.color)
+				.cast_shadow(dctx.cast_shadow)
+				.receive_shadow(dctx.receive_shadow)
 				;
 
+			let mat = node.mesh_material(i).and_then(|i| model.material(i));
+
+			// per-material textures take priority over the model-wide one
 			if let Some(tex) = model.texture() {
-				shape = shape.texture(tex);
+				if mat.map(|m| m.base_color_tex.is_none()).unwrap_or(true) {
+					shape = shape.texture(tex);
+				}
 			}
 
-			ctx.draw_t(tr, &shape)?;
+			if let Some(mat) = mat {
+				shape = shape.material(mat);
+			}
+
+			if let Some(joints) = &joints {
+				shape = shape.joints(joints);
+			}
+
+			if !weights.is_empty() {
+				shape = shape.morph_weights(weights);
+			}
+
+			if let Some(base) = dctx.pick_base {
+				ctx.pick_id(base + id as u32, |ctx| {
+					return ctx.draw_t(tr, &shape);
+				})?;
+			} else {
+				ctx.draw_t(tr, &shape)?;
+			}
 
 		}
 
 		for c in node.children() {
-			draw_mesh(ctx, dctx, tr, *c)?;
+			draw_mesh(ctx, dctx, pose, transforms, *c)?;
 		}
 
 	}
@@ -72,6 +72,7 @@ impl<'a> Drawable for Raw<'a> {
 				tex: tex.clone(),
 				custom: ctx.cur_custom_uniform.clone(),
 			},
+			&ctx.gl_state,
 		)?;
 
 		return Ok(());
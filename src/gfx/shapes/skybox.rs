@@ -0,0 +1,72 @@
+// wengwengweng
+
+use super::*;
+
+/// Cube Texture Drawn Behind Everything, Following the Camera Rotation
+///
+/// draw it before or after the scene, it doesn't write depth and only shows where nothing else is drawn
+#[derive(Clone)]
+pub struct Skybox<'a> {
+	tex: &'a gfx::CubeTexture,
+	color: Color,
+}
+
+impl<'a> Skybox<'a> {
+	pub fn new(tex: &'a gfx::CubeTexture) -> Self {
+		return Self {
+			tex: tex,
+			color: rgba!(1),
+		};
+	}
+	pub fn color(mut self, c: Color) -> Self {
+		self.color = c;
+		return self;
+	}
+}
+
+pub fn skybox<'a>(tex: &'a gfx::CubeTexture) -> Skybox<'a> {
+	return Skybox::new(tex);
+}
+
+impl<'a> Drawable for Skybox<'a> {
+
+	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+
+		if ctx.picking || ctx.shadow_pass {
+			return Ok(());
+		}
+
+		// infinitely far away, only rotation matters
+		let inv = (ctx.proj * ctx.view.remove_translation()).inverse();
+		let mut state = ctx.gl_state;
+
+		state.depth_write = false;
+		ctx.renderer.begin_direct(&state);
+
+		let uniform = gfx::Uniform {
+			proj: ctx.proj,
+			view: ctx.view,
+			model: mat4!(),
+			color: self.color,
+			tex: ctx.empty_tex.clone(),
+			custom: Some(vec![
+				("u_skybox", gfx::UniformData::CubeTexture(self.tex.clone())),
+				("u_skybox_inv", gfx::UniformData::Mat4(inv)),
+			]),
+		};
+
+		gfx::draw(
+			&ctx.gl,
+			Primitive::Triangle,
+			&ctx.skybox_pipeline,
+			ctx.quad_mesh.vbuf(),
+			ctx.quad_mesh.ibuf(),
+			ctx.quad_mesh.count(),
+			&uniform,
+		);
+
+		return Ok(());
+
+	}
+
+}
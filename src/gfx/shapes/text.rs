@@ -5,29 +5,20 @@ use super::*;
 #[derive(Clone)]
 struct FormattedChar {
 	ch: char,
+	// top left of the glyph cell, relative to the top left of the text
 	pos: Vec2,
 	tex: gfx::Texture,
 	quad: Quad,
+	// glyph bearing, scaled
+	offset: Vec2,
 	width: f32,
 	height: f32,
+	scale: f32,
+	sdf: Option<f32>,
+	rtl: bool,
 	color: Option<Color>,
 }
 
-#[derive(Clone)]
-struct FormattedLine {
-	chars: Vec<FormattedChar>,
-	width: f32,
-}
-
-impl FormattedLine {
-	fn new() -> Self {
-		return Self {
-			chars: vec![],
-			width: 0.0,
-		};
-	}
-}
-
 #[derive(Clone)]
 pub struct TextChunk<'a> {
 	text: &'a str,
@@ -51,14 +42,19 @@ impl<'a> TextChunk<'a> {
 
 #[derive(Clone)]
 pub struct FormattedText {
+	// in logical order
 	chars: Vec<FormattedChar>,
-	scale: f32,
+	// align offset
+	offset: Vec2,
 	width: f32,
 	height: f32,
 	color: Color,
 	italic: bool,
 	bold: bool,
 	align: gfx::Origin,
+	outline: Option<(f32, Color)>,
+	shadow: Option<(Vec2, Color)>,
+	glow: Option<(f32, Color)>,
 }
 
 impl FormattedText {
@@ -70,35 +66,43 @@ impl FormattedText {
 		return self.height;
 	}
 
+	/// top of the cursor before the ith char
 	pub fn cursor_pos(&self, i: usize) -> Option<Vec2> {
+
 		if self.chars.is_empty() {
-			return Some(vec2!(0));
-		} else if i == 0 {
-			return Some(vec2!(0))
-		} else {
-			return self.chars.get(i - 1).map(|ch| {
-				return ch.pos + vec2!(ch.width, 0) + vec2!(0, ch.height) * (self.align.as_pt() - gfx::Origin::TopLeft.as_pt()) * 0.5;
-			});
+			return Some(self.offset);
 		}
+
+		// leading edge of the first char, trailing edge of others
+		let (ch, trailing) = if i == 0 {
+			(self.chars.first(), false)
+		} else {
+			(self.chars.get(i - 1), true)
+		};
+
+		return ch.map(|ch| {
+			let x = if ch.rtl != trailing { ch.width } else { 0.0 };
+			return self.offset + ch.pos + vec2!(x, 0);
+		});
+
 	}
 
+	/// closest cursor to a position
 	pub fn pos_cursor(&self, pos: Vec2) -> Option<usize> {
 
 		let mut closest_ch = None;
 		let mut closest_dis = None;
 
-		for (i, ch) in self.chars.iter().enumerate() {
+		for i in 0..=self.chars.len() {
 
-			let p = ch.pos + vec2!(ch.width, 0);
-			let dis = Vec2::dist(pos, p);
+			let dis = match self.cursor_pos(i) {
+				Some(p) => Vec2::dist(pos, p),
+				None => continue,
+			};
 
-			if let Some(cdis) = closest_dis {
-				if dis < cdis {
-					closest_dis = Some(dis);
-					closest_ch = Some(i);
-				}
-			} else {
+			if closest_dis.map(|cdis| dis < cdis).unwrap_or(true) {
 				closest_dis = Some(dis);
+				closest_ch = Some(i);
 			}
 
 		}
@@ -107,11 +111,13 @@ impl FormattedText {
 
 	}
 
-}
-
-impl gfx::Drawable for FormattedText {
-
-	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+	fn draw_chars(
+		&self,
+		ctx: &mut Gfx,
+		offset: Vec2,
+		color: Option<Color>,
+		filter: impl Fn(&FormattedChar) -> bool,
+	) -> Result<()> {
 
 		let italic = if self.italic {
 			0.3
@@ -119,20 +125,103 @@ impl gfx::Drawable for FormattedText {
 			0.0
 		};
 
-		for fch in &self.chars {
+		for fch in self.chars.iter().filter(|c| filter(c)) {
 			ctx.draw_t(mat4!()
-				.t2(fch.pos)
+				.t2(self.offset + fch.pos + fch.offset + offset)
 				.skx(italic)
 				.tx(italic * fch.width)
-				.s2(vec2!(self.scale))
+				.s2(vec2!(fch.scale))
 			, &sprite(&fch.tex)
 				.offset(gfx::Origin::TopLeft.as_pt())
 				.quad(fch.quad)
-				.color(fch.color.unwrap_or(self.color))
+				.color(color.or(fch.color).unwrap_or(self.color))
 			)?;
 		}
 
 		return Ok(());
+
+	}
+
+	fn sdf_data(&self, spread: f32, scale: f32, shadow: bool) -> Vec<(&'static str, gfx::UniformData)> {
+
+		// text size units to distance field units, where 0.5 is the glyph edge
+		let to_dis = |w: f32| {
+			return (w / scale / (spread * 2.0)).max(0.0).min(0.5);
+		};
+
+		let (outline, outline_color) = self.outline
+			.map(|(w, c)| (to_dis(w), c))
+			.unwrap_or((0.0, rgba!(0)));
+
+		let (glow, glow_color) = self.glow
+			.filter(|_| !shadow)
+			.map(|(r, c)| (to_dis(r), c))
+			.unwrap_or((0.0, rgba!(0)));
+
+		// shadows take the shape of the outline
+		let outline_color = match self.shadow {
+			Some((_, c)) if shadow => c,
+			_ => outline_color,
+		};
+
+		return vec![
+			("u_sdf_outline", gfx::UniformData::Float(outline)),
+			("u_sdf_outline_color", gfx::UniformData::Vec4(outline_color.as_vec4())),
+			("u_sdf_glow", gfx::UniformData::Float(glow)),
+			("u_sdf_glow_color", gfx::UniformData::Vec4(glow_color.as_vec4())),
+		];
+
+	}
+
+	fn draw_pass(&self, ctx: &mut Gfx, shadow: bool) -> Result<()> {
+
+		let (offset, color) = match self.shadow {
+			Some((offset, color)) if shadow => (offset, Some(color)),
+			_ => (vec2!(), None),
+		};
+
+		// distance fields are drawn as plain sprites in pick & shadow passes
+		if ctx.picking || ctx.shadow_pass {
+			return self.draw_chars(ctx, offset, color, |_| true);
+		}
+
+		self.draw_chars(ctx, offset, color, |c| c.sdf.is_none())?;
+
+		// glyphs from fallback fonts can have different spreads & scales
+		let mut groups: Vec<(f32, f32)> = vec![];
+
+		for ch in &self.chars {
+			if let Some(spread) = ch.sdf {
+				if !groups.contains(&(spread, ch.scale)) {
+					groups.push((spread, ch.scale));
+				}
+			}
+		}
+
+		let pipeline = ctx.sdf_pipeline.clone();
+
+		for (spread, scale) in groups {
+			ctx.draw_with_data(&pipeline, self.sdf_data(spread, scale, shadow), |ctx| {
+				return self.draw_chars(ctx, offset, color, |c| c.sdf == Some(spread) && c.scale == scale);
+			})?;
+		}
+
+		return Ok(());
+
+	}
+
+}
+
+impl gfx::Drawable for FormattedText {
+
+	fn draw(&self, ctx: &mut Gfx) -> Result<()> {
+
+		if self.shadow.is_some() {
+			self.draw_pass(ctx, true)?;
+		}
+
+		return self.draw_pass(ctx, false);
+
 	}
 
 }
@@ -145,8 +234,11 @@ pub struct TextWrap {
 
 #[derive(Clone, Copy)]
 pub enum TextWrapBreak {
+	/// break at any char
 	None,
+	/// break at line break opportunities, e.g. spaces & between cjk chars
 	Word,
+	/// like Word, but words longer than a line are broken with a hyphen
 	Hyphonate,
 }
 
@@ -162,6 +254,9 @@ struct FormatConf {
 	pub bold: bool,
 	pub tab_width: usize,
 	pub fallback_char: char,
+	pub outline: Option<(f32, Color)>,
+	pub shadow: Option<(Vec2, Color)>,
+	pub glow: Option<(f32, Color)>,
 }
 
 impl Default for FormatConf {
@@ -177,127 +272,302 @@ impl Default for FormatConf {
 			bold: false,
 			tab_width: 1,
 			fallback_char: ' ',
+			outline: None,
+			shadow: None,
+			glow: None,
 		};
 	}
 }
 
-// TODO: rework
-fn format(chunks: &[TextChunk], font: &dyn gfx::Font, conf: &FormatConf) -> FormattedText {
+// a char with the glyph from the first font in the chain that has it
+struct Glyph<'a> {
+	ch: char,
+	font: usize,
+	fch: &'a gfx::Char,
+	scale: f32,
+	width: f32,
+	color: Option<Color>,
+}
+
+fn resolve_glyph<'a>(fonts: &[&'a dyn gfx::Font], ch: char) -> Option<(usize, &'a gfx::Char)> {
+	return fonts
+		.iter()
+		.enumerate()
+		.find_map(|(i, f)| f.get(ch).map(|c| (i, c)));
+}
 
-	let mut lines = vec![];
-	let mut cur_line = FormattedLine::new();
-	let scale = conf.size.map(|s| s / font.height()).unwrap_or(1.0);
-	let gh = font.height() * scale + conf.line_spacing;
-	let mut w = 0.0;
-	let mut break_pt: Option<FormattedLine> = None;
+// a line as a range of logical chars in a paragraph
+struct LineRange {
+	start: usize,
+	end: usize,
+	hyphen: bool,
+}
 
-	for chunk in chunks {
+// fonts are tried in order for each char, the first one decides line height
+fn format(chunks: &[TextChunk], fonts: &[&dyn gfx::Font], conf: &FormatConf) -> FormattedText {
 
-		for ch in chunk.text.chars() {
+	let primary = fonts[0];
+	let size = conf.size.unwrap_or(primary.height());
+	let gh = size + conf.line_spacing;
+
+	// fallback glyphs are scaled to the line height of the primary font
+	let glyph = |ch: char, color: Option<Color>| {
+
+		let (font, fch) = resolve_glyph(fonts, ch).or(resolve_glyph(fonts, conf.fallback_char))?;
+		let scale = size / fonts[font].height();
+
+		let w = if fch.advance > 0.0 {
+			fch.advance
+		} else {
+			fch.tex.width() as f32 * fch.quad.w
+		};
+
+		let mut width = w * scale;
+
+		if ch == '\t' {
+			width *= conf.tab_width as f32;
+		}
+
+		return Some(Glyph {
+			ch: fch.ch,
+			font: font,
+			fch: fch,
+			scale: scale,
+			width: width,
+			color: color,
+		});
+
+	};
+
+	let kerning = |a: &Glyph, b: &Glyph| {
+		if a.font == b.font {
+			return fonts[a.font].kerning(a.ch, b.ch) * a.scale;
+		} else {
+			return 0.0;
+		}
+	};
 
+	let has_glyph = |ch| resolve_glyph(fonts, ch).is_some();
+	let hyphen = glyph('-', None).filter(|g| g.ch == '-');
+	let mut paragraphs = vec![vec![]];
+
+	for chunk in chunks {
+		for ch in chunk.text.chars() {
 			if ch == '\n' {
+				paragraphs.push(vec![]);
+			} else if let Some(p) = paragraphs.last_mut() {
+				p.push((ch, chunk.color));
+			}
+		}
+	}
 
-				lines.push(std::mem::replace(&mut cur_line, FormattedLine::new()));
-
-			} else {
-
-				if let Some(fch) = font.get(ch).or(font.get(conf.fallback_char)) {
-
-					let mut gw = fch.tex.width() as f32 * fch.quad.w * scale + conf.char_spacing;
-
-					if ch == '\t' {
-						gw *= conf.tab_width as f32;
-					}
-
-					if let Some(wrap) = &conf.wrap {
-						if cur_line.width + gw > wrap.width {
-							if let Some(line) = break_pt.take() {
-								cur_line.width -= line.width;
-								cur_line.chars.drain(0..line.chars.len());
-								lines.push(line);
-							} else {
-								lines.push(std::mem::replace(&mut cur_line, FormattedLine::new()));
-							}
-						}
-					}
-
-					cur_line.chars.push(FormattedChar {
-						ch: ch,
-						pos: vec2!(),
-						tex: fch.tex.clone(),
-						quad: fch.quad,
-						color: chunk.color,
-						width: gw - conf.char_spacing,
-						height: gh,
-					});
+	let mut lines = vec![];
 
-					cur_line.width += gw;
-					w = f32::max(cur_line.width, w);
+	for para in paragraphs {
+
+		let text = para.iter().map(|(ch, _)| *ch).collect::<Vec<char>>();
+		let shaped = gfx::shape(&text, has_glyph);
+		let chars = shaped.iter().map(|(_, ch)| *ch).collect::<Vec<char>>();
+		let breaks = gfx::line_breaks(&chars);
+		let levels = gfx::bidi_levels(&chars);
+		let rtl = gfx::is_rtl(&chars);
+
+		let glyphs = shaped
+			.iter()
+			.enumerate()
+			.map(|(i, (src, ch))| {
+				let ch = if levels[i] % 2 == 1 { gfx::bidi_mirror(*ch) } else { *ch };
+				return glyph(ch, para[*src].1);
+			})
+			.collect::<Vec<Option<Glyph>>>();
+
+		// advance of the ith char measured in logical order
+		let advance = |i: usize| {
+			let g = match &glyphs[i] {
+				Some(g) => g,
+				None => return 0.0,
+			};
+			let kern = match i.checked_sub(1).and_then(|p| glyphs[p].as_ref()) {
+				Some(prev) if levels[i] % 2 == 1 => kerning(g, prev),
+				Some(prev) => kerning(prev, g),
+				None => 0.0,
+			};
+			return g.width + kern + conf.char_spacing;
+		};
 
-				}
+		let mut ranges = vec![];
+		let mut start = 0;
+		let mut last_break = None;
+		let mut line_w = 0.0;
+
+		for i in 0..chars.len() {
+
+			let adv = advance(i);
+
+			if i > start && breaks[i] {
+				last_break = Some(i);
+			}
+
+			if let Some(wrap) = &conf.wrap {
+
+				let split_word = match wrap.break_type {
+					TextWrapBreak::Hyphonate => last_break.is_none(),
+					_ => false,
+				};
+
+				let reserve = match &hyphen {
+					Some(h) if split_word => h.width,
+					_ => 0.0,
+				};
+
+				// trailing spaces can hang outside
+				if i > start && line_w + adv + reserve > wrap.width && !chars[i].is_whitespace() {
+
+					let at = match wrap.break_type {
+						TextWrapBreak::None => i,
+						_ => last_break.unwrap_or(i),
+					};
+
+					ranges.push(LineRange {
+						start: start,
+						end: at,
+						hyphen: split_word && hyphen.is_some(),
+					});
+
+					start = at;
+					last_break = None;
+					line_w = (start..i).map(|j| advance(j)).sum();
 
-				if ch == ' ' {
-					if let Some(wrap) = &conf.wrap {
-						if let TextWrapBreak::Word = wrap.break_type {
-							break_pt = Some(cur_line.clone());
-						}
-					}
 				}
 
 			}
 
+			line_w += adv;
+
 		}
 
-	}
+		ranges.push(LineRange {
+			start: start,
+			end: chars.len(),
+			hyphen: false,
+		});
 
-	lines.push(cur_line);
+		for range in ranges {
 
-	// TODO: don't apply align to char pos
+			// trailing whitespace goes back to the paragraph direction
+			let mut line_levels = levels[range.start..range.end].to_vec();
+			let base = if rtl { 1 } else { 0 };
 
-	let h = lines.len() as f32 * gh;
-	let offset_pt = conf.align.as_pt() * 0.5 + vec2!(0.5, -0.5);
-	let offset = -offset_pt * vec2!(w, h);
+			for (l, ch) in line_levels.iter_mut().zip(&chars[range.start..range.end]).rev() {
+				if !ch.is_whitespace() {
+					break;
+				}
+				*l = base;
+			}
 
-	let mut fchars = vec![];
-	let mut x = 0.0;
+			let mut visual = gfx::bidi_reorder(&line_levels)
+				.into_iter()
+				.map(|i| (range.start + i, glyphs[range.start + i].as_ref(), line_levels[i] % 2 == 1))
+				.collect::<Vec<(usize, Option<&Glyph>, bool)>>();
+
+			if range.hyphen {
+				let h = (range.end, hyphen.as_ref(), rtl);
+				if rtl {
+					visual.insert(0, h);
+				} else {
+					visual.push(h);
+				}
+			}
 
-	for (i, line) in lines
-		.into_iter()
-		.enumerate() {
+			let mut x = 0.0;
+			let mut prev: Option<&Glyph> = None;
+			let mut placed = vec![];
 
-		let ox = (w - line.width) * offset_pt.x;
-		let y = i as f32 * -gh;
+			for (i, g, char_rtl) in visual {
+
+				let g = match g {
+					Some(g) => g,
+					None => continue,
+				};
 
-		for ch in line.chars {
+				if let Some(prev) = prev {
+					x += kerning(prev, g);
+				}
 
-			let mut gw = ch.tex.width() as f32 * ch.quad.w * scale + conf.char_spacing;
+				placed.push((i, FormattedChar {
+					ch: g.ch,
+					pos: vec2!(x, 0),
+					tex: g.fch.tex.clone(),
+					quad: g.fch.quad,
+					offset: vec2!(g.fch.bearing_x, -g.fch.bearing_y) * g.scale,
+					width: g.width,
+					height: gh,
+					scale: g.scale,
+					sdf: fonts[g.font].sdf_spread(),
+					rtl: char_rtl,
+					color: g.color,
+				}));
+
+				x += g.width + conf.char_spacing;
+				prev = Some(g);
 
-			if ch.ch == '\t' {
-				gw *= conf.tab_width as f32;
 			}
 
-			fchars.push(FormattedChar {
-				pos: vec2!(x + ox, y) + offset,
-				..ch
-			});
+			// align by the extent of visible chars, so hanging spaces don't count
+			let visible = placed
+				.iter()
+				.filter(|(_, c)| !c.ch.is_whitespace())
+				.map(|(_, c)| (c.pos.x, c.pos.x + c.width))
+				.collect::<Vec<(f32, f32)>>();
 
-			x += gw;
+			let left = visible.iter().map(|(l, _)| *l).fold(f32::INFINITY, f32::min);
+			let right = visible.iter().map(|(_, r)| *r).fold(f32::NEG_INFINITY, f32::max);
+			let (left, width) = if visible.is_empty() { (0.0, 0.0) } else { (left, right - left) };
+
+			for (_, c) in &mut placed {
+				c.pos.x -= left;
+			}
+
+			// back to logical order for cursors
+			placed.sort_by_key(|(i, _)| *i);
+
+			lines.push((width, placed.into_iter().map(|(_, c)| c).collect::<Vec<FormattedChar>>()));
 
 		}
 
-		x = 0.0;
+	}
+
+	let w = lines.iter().map(|(w, _)| *w).fold(0.0, f32::max);
+	let h = lines.len() as f32 * gh;
+	let offset_pt = conf.align.as_pt() * 0.5 + vec2!(0.5, -0.5);
+	let mut fchars = vec![];
+
+	for (i, (line_w, chars)) in lines.into_iter().enumerate() {
+
+		let ox = (w - line_w) * offset_pt.x;
+		let y = i as f32 * -gh;
+
+		for ch in chars {
+			fchars.push(FormattedChar {
+				pos: ch.pos + vec2!(ox, y),
+				..ch
+			});
+		}
 
 	}
 
 	return FormattedText {
 		chars: fchars,
-		scale: scale,
+		offset: -offset_pt * vec2!(w, h),
 		width: w,
 		height: h,
 		color: conf.color,
 		italic: conf.italic,
 		bold: conf.bold,
 		align: conf.align,
+		outline: conf.outline,
+		shadow: conf.shadow,
+		glow: conf.glow,
 	};
 
 }
@@ -306,6 +576,7 @@ fn format(chunks: &[TextChunk], font: &dyn gfx::Font, conf: &FormatConf) -> Form
 pub struct Text<'a> {
 	content: Vec<TextChunk<'a>>,
 	font: Option<&'a dyn gfx::Font>,
+	fallbacks: Vec<&'a dyn gfx::Font>,
 	conf: FormatConf,
 }
 
@@ -317,6 +588,7 @@ impl<'a> Text<'a> {
 				color: None,
 			}],
 			font: None,
+			fallbacks: vec![],
 			conf: FormatConf::default(),
 		}
 	}
@@ -324,6 +596,7 @@ impl<'a> Text<'a> {
 		return Self {
 			content: c.to_vec(),
 			font: None,
+			fallbacks: vec![],
 			conf: FormatConf::default(),
 		}
 	}
@@ -331,6 +604,11 @@ impl<'a> Text<'a> {
 		self.font = Some(f);
 		return self;
 	}
+	/// font for chars the main font doesn't have, tried in the order added and before the ones from [`Gfx::add_fallback_font`](../struct.Gfx.html#method.add_fallback_font)
+	pub fn fallback(mut self, f: &'a dyn gfx::Font) -> Self {
+		self.fallbacks.push(f);
+		return self;
+	}
 	pub fn color(mut self, color: Color) -> Self {
 		self.conf.color = color;
 		return self;
@@ -355,6 +633,10 @@ impl<'a> Text<'a> {
 		self.conf.line_spacing = h;
 		return self;
 	}
+	pub fn char_spacing(mut self, w: f32) -> Self {
+		self.conf.char_spacing = w;
+		return self;
+	}
 	pub fn italic(mut self, b: bool) -> Self {
 		self.conf.italic = b;
 		return self;
@@ -367,6 +649,26 @@ impl<'a> Text<'a> {
 		self.conf.tab_width = w;
 		return self;
 	}
+	/// char drawn for chars no font has
+	pub fn fallback_char(mut self, ch: char) -> Self {
+		self.conf.fallback_char = ch;
+		return self;
+	}
+	/// outline around glyphs, only for [`SdfFont`](../struct.SdfFont.html)
+	pub fn outline(mut self, w: f32, c: Color) -> Self {
+		self.conf.outline = Some((w, c));
+		return self;
+	}
+	/// draw a copy of the text with an offset behind it
+	pub fn shadow(mut self, offset: Vec2, c: Color) -> Self {
+		self.conf.shadow = Some((offset, c));
+		return self;
+	}
+	/// soft glow fading out from glyph edges, only for [`SdfFont`](../struct.SdfFont.html)
+	pub fn glow(mut self, radius: f32, c: Color) -> Self {
+		self.conf.glow = Some((radius, c));
+		return self;
+	}
 }
 
 pub fn text<'a>(s: &'a str) -> Text<'a> {
@@ -375,7 +677,14 @@ pub fn text<'a>(s: &'a str) -> Text<'a> {
 
 impl<'a> Text<'a> {
 	pub fn format(&self, ctx: &Gfx) -> FormattedText {
-		return format(&self.content, self.font.unwrap_or(ctx.default_font()), &self.conf);
+
+		let mut fonts: Vec<&dyn gfx::Font> = vec![self.font.unwrap_or(ctx.default_font())];
+
+		fonts.extend(self.fallbacks.iter().cloned());
+		fonts.extend(ctx.fallback_fonts());
+
+		return format(&self.content, &fonts, &self.conf);
+
 	}
 }
 
@@ -387,3 +696,70 @@ impl<'a> gfx::Drawable for Text<'a> {
 
 }
 
+#[cfg(all(test, not(web)))]
+#[test]
+fn layout() {
+
+	use gfx::*;
+
+	let mut h = test_headless(64, 64);
+
+	let gfx = h.gfx();
+
+	// solid 8x8 glyphs for chars the default font doesn't have
+	let tex = Texture::from_raw(gfx, 32, 8, &[255; 32 * 8 * 4]).unwrap();
+	let extra = BitmapFont::from_tex(tex, 8, 8, "日本של").unwrap();
+
+	let eq = |a: f32, b: f32| (a - b).abs() < 0.01;
+	let eq2 = |a: Option<Vec2>, b: Vec2| a.map(|a| eq(a.x, b.x) && eq(a.y, b.y)).unwrap_or(false);
+
+	// default font is 8x8
+	let t = text("a日").fallback_char('?').align(Origin::TopLeft).format(gfx);
+	assert!(eq(t.width(), 16.0));
+	assert!(t.chars[1].ch == '?');
+	let t = text("a日").fallback(&extra).align(Origin::TopLeft).size(16.0).format(gfx);
+	assert!(eq(t.width(), 32.0));
+	assert!(t.chars[1].ch == '日');
+
+	let canvas = Canvas::new(gfx, 64, 64).unwrap();
+
+	gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+		return gfx.draw(&t);
+	}).unwrap();
+
+	// centered canvas, the fallback glyph is at x 16..32, y -16..0
+	assert_eq!(canvas.read(vec2!(56, 24)), rgba!(1));
+
+	gfx.add_fallback_font(BitmapFont::from_tex(extra.get('日').unwrap().tex.clone(), 8, 8, "日本של").unwrap());
+	assert!(text("日").format(gfx).chars[0].ch == '日');
+
+	let wrap = |w: f32, break_type: TextWrapBreak| TextWrap {
+		width: w,
+		break_type: break_type,
+	};
+
+	// words, cjk & hyphenated words
+	let t = text("ab cd").wrap(wrap(32.0, TextWrapBreak::Word)).format(gfx);
+	assert!(eq(t.width(), 16.0) && eq(t.height(), 16.0));
+	let t = text("日本日本日").wrap(wrap(24.0, TextWrapBreak::Word)).format(gfx);
+	assert!(eq(t.width(), 24.0) && eq(t.height(), 16.0));
+	let t = text("abcde").wrap(wrap(24.0, TextWrapBreak::Hyphonate)).format(gfx);
+	assert!(eq(t.height(), 24.0));
+	assert!(t.chars[2].ch == '-');
+	let t = text("abcde").wrap(wrap(24.0, TextWrapBreak::None)).format(gfx);
+	assert!(eq(t.height(), 16.0));
+
+	// right to left text is reversed but cursors follow logical order
+	let t = text("ab של").align(Origin::TopLeft).format(gfx);
+	assert!(eq(t.chars[3].pos.x, 32.0));
+	assert!(eq(t.chars[4].pos.x, 24.0));
+	assert!(eq2(t.cursor_pos(4), vec2!(32, 0)));
+	assert!(eq2(t.cursor_pos(5), vec2!(24, 0)));
+	assert_eq!(t.pos_cursor(vec2!(33, -2)), Some(4));
+
+	// align is kept out of char positions
+	let t = text("ab").align(Origin::Center).format(gfx);
+	assert!(eq2(Some(t.chars[0].pos), vec2!(0)));
+	assert!(eq2(t.cursor_pos(0), vec2!(-8, 4)));
+
+}
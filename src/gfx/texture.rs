@@ -2,17 +2,130 @@
 
 use super::*;
 
+/// Texture Pixel Format
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureFormat {
+	/// 8 bit rgba
+	RGBA8,
+	/// 16 bit float rgba, for hdr
+	RGBA16F,
+	/// 32 bit float rgba
+	RGBA32F,
+	/// 8 bit single channel
+	R8,
+	/// 16 bit float 2 channels, for things like velocity
+	RG16F,
+	/// depth only, for shadow maps and depth effects
+	Depth,
+	/// 32 bit unsigned integer single channel, needs gl 3, can't be filtered or multisampled
+	R32UI,
+}
+
+impl TextureFormat {
+
+	// (internal format, format, type of raw data)
+	pub(super) fn as_glow(&self) -> (i32, u32, u32) {
+		return match self {
+			TextureFormat::RGBA8 => (glow::RGBA8 as i32, glow::RGBA, glow::UNSIGNED_BYTE),
+			TextureFormat::RGBA16F => (glow::RGBA16F as i32, glow::RGBA, glow::FLOAT),
+			TextureFormat::RGBA32F => (glow::RGBA32F as i32, glow::RGBA, glow::FLOAT),
+			TextureFormat::R8 => (glow::R8 as i32, glow::RED, glow::UNSIGNED_BYTE),
+			TextureFormat::RG16F => (glow::RG16F as i32, glow::RG, glow::FLOAT),
+			TextureFormat::Depth => (glow::DEPTH_COMPONENT24 as i32, glow::DEPTH_COMPONENT, glow::UNSIGNED_INT),
+			TextureFormat::R32UI => (glow::R32UI as i32, glow::RED_INTEGER, glow::UNSIGNED_INT),
+		};
+	}
+
+	/// bytes of each pixel in raw data, float formats take 32 bit floats
+	pub fn pixel_size(&self) -> usize {
+		return match self {
+			TextureFormat::RGBA8 => 4,
+			TextureFormat::RGBA16F => 16,
+			TextureFormat::RGBA32F => 16,
+			TextureFormat::R8 => 1,
+			TextureFormat::RG16F => 8,
+			TextureFormat::Depth => 4,
+			TextureFormat::R32UI => 4,
+		};
+	}
+
+	/// if data is floats
+	pub fn is_float(&self) -> bool {
+		return match self {
+			TextureFormat::RGBA16F | TextureFormat::RGBA32F | TextureFormat::RG16F => true,
+			_ => false,
+		};
+	}
+
+	pub fn is_depth(&self) -> bool {
+		return *self == TextureFormat::Depth;
+	}
+
+	/// if data is unsigned integers
+	pub fn is_int(&self) -> bool {
+		return *self == TextureFormat::R32UI;
+	}
+
+	// everything except RGBA8 & Depth is not in gl 2 / gles 2
+	pub(super) fn needs_gl3(&self) -> bool {
+		return match self {
+			TextureFormat::RGBA8 | TextureFormat::Depth => false,
+			_ => true,
+		};
+	}
+
+}
+
+/// Texture Sampling & Storage Options
+///
+/// for smooth minified textures like big floors, use trilinear filtering with `filter: FilterMode::Linear, mipmap_filter: Some(FilterMode::Linear)` and an anisotropy like 8.0
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TextureConf {
 	pub filter: FilterMode,
-	pub wrap: WrapMode,
+	/// filter between mipmap levels, mipmaps are only generated & used if this is set
+	pub mipmap_filter: Option<FilterMode>,
+	/// max anisotropic filtering samples, clamped to what the driver supports, ignored if not available
+	pub anisotropy: f32,
+	pub wrap_x: WrapMode,
+	pub wrap_y: WrapMode,
+	pub format: TextureFormat,
+}
+
+impl TextureConf {
+
+	pub(super) fn min_filter(&self) -> i32 {
+		return match (self.filter, self.mipmap_filter) {
+			(f, None) => f.as_glow(),
+			(FilterMode::Nearest, Some(FilterMode::Nearest)) => glow::NEAREST_MIPMAP_NEAREST as i32,
+			(FilterMode::Nearest, Some(FilterMode::Linear)) => glow::NEAREST_MIPMAP_LINEAR as i32,
+			(FilterMode::Linear, Some(FilterMode::Nearest)) => glow::LINEAR_MIPMAP_NEAREST as i32,
+			(FilterMode::Linear, Some(FilterMode::Linear)) => glow::LINEAR_MIPMAP_LINEAR as i32,
+		};
+	}
+
+	// set filter & anisotropy parameters on the bound texture, anisotropy is capped to the driver max
+	pub(super) unsafe fn apply_filter(&self, gl: &glow::Context, target: u32, max_anisotropy: f32) {
+
+		gl.tex_parameter_i32(target, glow::TEXTURE_MIN_FILTER, self.min_filter());
+		gl.tex_parameter_i32(target, glow::TEXTURE_MAG_FILTER, self.filter.as_glow());
+
+		if self.anisotropy > 1.0 && max_anisotropy > 1.0 {
+			gl.tex_parameter_f32(target, glow::TEXTURE_MAX_ANISOTROPY, self.anisotropy.min(max_anisotropy));
+		}
+
+	}
+
 }
 
 impl Default for TextureConf {
 	fn default() -> Self {
 		return Self {
 			filter: FilterMode::Nearest,
-			wrap: WrapMode::ClampToBorder,
+			mipmap_filter: None,
+			anisotropy: 1.0,
+			wrap_x: WrapMode::ClampToBorder,
+			wrap_y: WrapMode::ClampToBorder,
+			format: TextureFormat::RGBA8,
 		};
 	}
 }
@@ -24,6 +137,9 @@ pub struct Texture {
 	gl: Rc<glow::Context>,
 	width: i32,
 	height: i32,
+	format: TextureFormat,
+	// regenerate mipmaps on updates
+	auto_mipmaps: bool,
 }
 
 impl Texture {
@@ -40,45 +156,43 @@ impl Texture {
 
 			let gl = ctx.gl().clone();
 			let gl_tex = TextureHandle::new(&gl)?;
+			let (internal, format, ty) = conf.format.as_glow();
 
 			gl.bind_texture(glow::TEXTURE_2D, Some(gl_tex.id()));
 
 			gl.tex_image_2d(
 				glow::TEXTURE_2D,
 				0,
-				glow::RGBA as i32,
+				internal,
 				w,
 				h,
 				0,
-				glow::RGBA,
-				glow::UNSIGNED_BYTE,
+				format,
+				ty,
 				None,
 			);
 
-			gl.tex_parameter_i32(
-				glow::TEXTURE_2D,
-				glow::TEXTURE_MIN_FILTER,
-				conf.filter.as_glow(),
-			);
-
-			gl.tex_parameter_i32(
-				glow::TEXTURE_2D,
-				glow::TEXTURE_MAG_FILTER,
-				conf.filter.as_glow(),
-			);
+			conf.apply_filter(&gl, glow::TEXTURE_2D, ctx.max_anisotropy());
 
 			gl.tex_parameter_i32(
 				glow::TEXTURE_2D,
 				glow::TEXTURE_WRAP_S,
-				conf.wrap.as_glow(),
+				conf.wrap_x.as_glow(),
 			);
 
 			gl.tex_parameter_i32(
 				glow::TEXTURE_2D,
 				glow::TEXTURE_WRAP_T,
-				conf.wrap.as_glow(),
+				conf.wrap_y.as_glow(),
 			);
 
+			let auto_mipmaps = conf.mipmap_filter.is_some() && !conf.format.is_depth();
+
+			// allocates the levels
+			if auto_mipmaps {
+				gl.generate_mipmap(glow::TEXTURE_2D);
+			}
+
 			gl.bind_texture(glow::TEXTURE_2D, None);
 
 			return Ok(Self {
@@ -86,6 +200,8 @@ impl Texture {
 				gl: gl,
 				width: w,
 				height: h,
+				format: conf.format,
+				auto_mipmaps: auto_mipmaps,
 			});
 
 		}
@@ -113,6 +229,12 @@ impl Texture {
 				None,
 			);
 
+			// sampled as shadow maps
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::NEAREST as i32);
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::NEAREST as i32);
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+
 			gl.bind_texture(glow::TEXTURE_2D, None);
 
 			return Ok(Self {
@@ -120,16 +242,42 @@ impl Texture {
 				gl: gl,
 				width: w,
 				height: h,
+				format: TextureFormat::Depth,
+				auto_mipmaps: false,
 			});
 
 		}
 
 	}
 
+	/// create a texture from raw data in the format of [`TextureConf::format`](struct.TextureConf.html#structfield.format)
 	pub fn from_raw_with_conf(ctx: &impl GLCtx, width: i32, height: i32, data: &[u8], conf: TextureConf) -> Result<Self> {
+
+		if data.len() != width as usize * height as usize * conf.format.pixel_size() {
+			return Err(format!("incorrect texture data size"));
+		}
+
 		let tex = Self::new_with_conf(ctx, width, height, conf)?;
 		tex.data(data);
+
 		return Ok(tex);
+
+	}
+
+	/// create a float texture, data has as many channels as the format
+	pub fn from_f32_with_conf(ctx: &impl GLCtx, width: i32, height: i32, data: &[f32], conf: TextureConf) -> Result<Self> {
+
+		if !conf.format.is_float() {
+			return Err(format!("{:?} is not a float format", conf.format));
+		}
+
+		let bytes = data
+			.iter()
+			.flat_map(|f| f.to_ne_bytes().to_vec())
+			.collect::<Vec<u8>>();
+
+		return Self::from_raw_with_conf(ctx, width, height, &bytes, conf);
+
 	}
 
 	/// create a texture from raw pixels
@@ -146,6 +294,65 @@ impl Texture {
 		return Self::from_img_with_conf(ctx, img, TextureConf::default());
 	}
 
+	/// create a texture with supplied mipmap levels, each level is half the size of the previous one down to 1x1
+	pub fn from_img_levels_with_conf(ctx: &impl GLCtx, levels: Vec<img::Image>, conf: TextureConf) -> Result<Self> {
+
+		let base = levels.first().ok_or_else(|| format!("no mipmap levels"))?;
+		let (w, h) = (base.width(), base.height());
+
+		if conf.format != TextureFormat::RGBA8 {
+			return Err(format!("images can only be loaded to {:?} textures", TextureFormat::RGBA8));
+		}
+
+		for (i, img) in levels.iter().enumerate() {
+			if img.width() != (w >> i).max(1) || img.height() != (h >> i).max(1) {
+				return Err(format!("incorrect size of mipmap level {}", i));
+			}
+		}
+
+		// gles 2 can't limit the max level, so partial chains would be incomplete
+		let count = 32 - (w.max(h) as u32).leading_zeros() as usize;
+
+		if levels.len() != count {
+			return Err(format!("expected {} mipmap levels, got {}", count, levels.len()));
+		}
+
+		let tex = Self::new_with_conf(ctx, w, h, TextureConf {
+			mipmap_filter: None,
+			..conf
+		})?;
+
+		unsafe {
+
+			let gl = &tex.gl;
+
+			tex.bind();
+			gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+
+			for (i, img) in levels.iter().enumerate() {
+				gl.tex_image_2d(
+					glow::TEXTURE_2D,
+					i as i32,
+					glow::RGBA8 as i32,
+					img.width(),
+					img.height(),
+					0,
+					glow::RGBA,
+					glow::UNSIGNED_BYTE,
+					Some(img.as_raw()),
+				);
+			}
+
+			conf.apply_filter(gl, glow::TEXTURE_2D, ctx.max_anisotropy());
+			gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+			tex.unbind();
+
+		}
+
+		return Ok(tex);
+
+	}
+
 	pub fn from_bytes_with_conf(ctx: &impl GLCtx, data: &[u8], conf: TextureConf) -> Result<Self> {
 		return Self::from_img_with_conf(ctx, img::Image::from_bytes(data)?, conf);
 	}
@@ -169,10 +376,15 @@ impl Texture {
 
 	pub(super) fn sub_data(&self, x: i32, y: i32, w: i32, h: i32, data: &[u8]) {
 
+		let (_, format, ty) = self.format.as_glow();
+
 		unsafe {
 
 			self.bind();
 
+			// rows of single channel data are not aligned to 4 bytes
+			self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+
 			self.gl.tex_sub_image_2d(
 				glow::TEXTURE_2D,
 				0,
@@ -180,11 +392,17 @@ impl Texture {
 				y as i32,
 				w as i32,
 				h as i32,
-				glow::RGBA,
-				glow::UNSIGNED_BYTE,
+				format,
+				ty,
 				glow::PixelUnpackData::Slice(data),
 			);
 
+			self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+
+			if self.auto_mipmaps {
+				self.gl.generate_mipmap(glow::TEXTURE_2D);
+			}
+
 			self.unbind();
 
 		}
@@ -195,6 +413,47 @@ impl Texture {
 		self.sub_data(0, 0, self.width, self.height, data);
 	}
 
+	/// update a region with raw data in the texture format, mipmaps are regenerated if they're automatic
+	pub fn update_raw(&self, x: i32, y: i32, w: i32, h: i32, data: &[u8]) -> Result<()> {
+
+		if x < 0 || y < 0 || w < 0 || h < 0 || x + w > self.width || y + h > self.height {
+			return Err(format!("texture region out of bounds"));
+		}
+
+		if data.len() != w as usize * h as usize * self.format.pixel_size() {
+			return Err(format!("incorrect texture data size"));
+		}
+
+		if self.format.is_depth() {
+			return Err(format!("cannot update depth texture"));
+		}
+
+		self.sub_data(x, y, w, h, data);
+
+		return Ok(());
+
+	}
+
+	/// update a region with an [`Image`](../img/struct.Image.html) at a position, for streaming or painting
+	pub fn update(&self, x: i32, y: i32, img: &img::Image) -> Result<()> {
+
+		if self.format != TextureFormat::RGBA8 {
+			return Err(format!("images can only be loaded to {:?} textures", TextureFormat::RGBA8));
+		}
+
+		return self.update_raw(x, y, img.width(), img.height(), img.as_raw());
+
+	}
+
+	/// regenerate mipmaps from the base level, for textures rendered to or with supplied levels
+	pub fn gen_mipmaps(&self) {
+		unsafe {
+			self.bind();
+			self.gl.generate_mipmap(glow::TEXTURE_2D);
+			self.unbind();
+		}
+	}
+
 	/// get texture width
 	pub fn width(&self) -> i32 {
 		return self.width;
@@ -205,9 +464,18 @@ impl Texture {
 		return self.height;
 	}
 
-	/// capture content to an [`Image`](../img/struct.Image.html)
+	/// get pixel format
+	pub fn format(&self) -> TextureFormat {
+		return self.format;
+	}
+
+	/// capture content to an [`Image`](../img/struct.Image.html), float values are clamped to 0.0 - 1.0
 	pub fn capture(&self) -> Result<img::Image> {
 
+		if self.format.is_depth() {
+			return Err(format!("cannot capture depth texture to image"));
+		}
+
 		let size = (self.width * self.height * 4) as usize;
 		let mut pixels = vec![0; size];
 
@@ -229,6 +497,37 @@ impl Texture {
 
 	}
 
+	/// capture content as rgba floats without clamping, for reading hdr or data textures
+	pub fn capture_f32(&self) -> Result<Vec<f32>> {
+
+		if self.format.is_depth() {
+			return Err(format!("cannot capture depth texture as color"));
+		}
+
+		let size = (self.width * self.height * 4) as usize;
+		let mut pixels = vec![0; size * 4];
+
+		self.bind();
+
+		unsafe {
+			self.gl.get_tex_image(
+				glow::TEXTURE_2D,
+				0,
+				glow::RGBA,
+				glow::FLOAT,
+				glow::PixelPackData::Slice(&mut pixels),
+			);
+		}
+
+		self.unbind();
+
+		return Ok(pixels
+			.chunks(4)
+			.map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+			.collect());
+
+	}
+
 	pub(super) fn id(&self) -> glow::Texture {
 		return self.gl_tex.id();
 	}
@@ -241,3 +540,85 @@ impl PartialEq for Texture {
 	}
 }
 
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn mipmaps() {
+
+	let mut h = test_headless(8, 8);
+
+	let gfx = h.gfx();
+	let canvas = Canvas::new(gfx, 8, 8).unwrap();
+
+	let solid = |w: i32, c: Color| {
+		let mut img = img::Image::new(w, w);
+		for x in 0..w {
+			for y in 0..w {
+				img.set(x, y, c).unwrap();
+			}
+		}
+		return img;
+	};
+
+	// draw a texture minified to 2 pixels
+	let draw = |gfx: &mut Gfx, tex: &Texture| {
+		let s = 2.0 / tex.width() as f32;
+		gfx.draw_on(&canvas, CanvasAction::clear(), |gfx| {
+			return gfx.draw_t(mat4!().s2(vec2!(s)), &shapes::sprite(tex));
+		}).unwrap();
+		return canvas.read(vec2!(4, 4));
+	};
+
+	let mut checker = img::Image::new(64, 64);
+
+	for x in 0..64 {
+		for y in 0..64 {
+			checker.set(x, y, if (x + y) % 2 == 0 { rgba!(1) } else { rgba!(0, 0, 0, 1) }).unwrap();
+		}
+	}
+
+	let nearest = Texture::from_img(gfx, checker.clone()).unwrap();
+	let trilinear = Texture::from_img_with_conf(gfx, checker, TextureConf {
+		filter: FilterMode::Linear,
+		mipmap_filter: Some(FilterMode::Linear),
+		anisotropy: 16.0,
+		wrap_x: WrapMode::Repeat,
+		wrap_y: WrapMode::MirroredRepeat,
+		..TextureConf::default()
+	}).unwrap();
+
+	let c = draw(gfx, &nearest);
+	assert!(c.r < 0.1 || c.r > 0.9);
+	let c = draw(gfx, &trilinear);
+	assert!((c.r - 0.5).abs() < 0.1);
+
+	let levels = Texture::from_img_levels_with_conf(gfx, vec![
+		solid(8, rgba!(1, 0, 0, 1)),
+		solid(4, rgba!(0, 1, 0, 1)),
+		solid(2, rgba!(0, 0, 1, 1)),
+		solid(1, rgba!(1, 1, 0, 1)),
+	], TextureConf {
+		mipmap_filter: Some(FilterMode::Nearest),
+		..TextureConf::default()
+	}).unwrap();
+
+	assert_eq!(draw(gfx, &levels), rgba!(0, 0, 1, 1));
+	assert!(Texture::from_img_levels_with_conf(gfx, vec![solid(8, rgba!(1)), solid(8, rgba!(1))], TextureConf::default()).is_err());
+	assert!(Texture::from_img_levels_with_conf(gfx, vec![solid(8, rgba!(1)), solid(4, rgba!(1))], TextureConf::default()).is_err());
+
+	// mipmaps follow updates
+	trilinear.update(0, 0, &solid(64, rgba!(0, 0, 1, 1))).unwrap();
+	assert_eq!(draw(gfx, &trilinear), rgba!(0, 0, 1, 1));
+
+	let tex = Texture::new(gfx, 4, 4).unwrap();
+
+	tex.update(1, 2, &solid(2, rgba!(1, 0, 0, 1))).unwrap();
+
+	let img = tex.capture().unwrap();
+
+	assert_eq!(img.get(1, 2), Some(rgba!(1, 0, 0, 1)));
+	assert_eq!(img.get(2, 3), Some(rgba!(1, 0, 0, 1)));
+	assert_eq!(img.get(0, 0), Some(rgba!(0, 0, 0, 0)));
+	assert!(tex.update(3, 3, &solid(2, rgba!(1))).is_err());
+
+}
@@ -87,6 +87,15 @@ bind_enum!(pub, WrapMode(i32) {
 	MirroredRepeat => glow::MIRRORED_REPEAT as i32,
 });
 
+bind_enum!(pub, CubeFace(u32) {
+	PosX => glow::TEXTURE_CUBE_MAP_POSITIVE_X,
+	NegX => glow::TEXTURE_CUBE_MAP_NEGATIVE_X,
+	PosY => glow::TEXTURE_CUBE_MAP_POSITIVE_Y,
+	NegY => glow::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+	PosZ => glow::TEXTURE_CUBE_MAP_POSITIVE_Z,
+	NegZ => glow::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+});
+
 bind_enum!(pub, Surface(u32) {
 	Color => glow::COLOR_BUFFER_BIT,
 	Stencil => glow::STENCIL_BUFFER_BIT,
@@ -0,0 +1,454 @@
+// wengwengweng
+
+// unicode text processing for text layout, simplified versions of the arabic joining rules, UAX #9 (bidi) and UAX #14 (line breaking)
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BidiClass {
+	// left to right
+	L,
+	// right to left
+	R,
+	// european & arabic numbers
+	EN,
+	// whitespace
+	WS,
+	// other neutrals
+	ON,
+}
+
+fn bidi_class(ch: char) -> BidiClass {
+
+	let c = ch as u32;
+
+	if ch.is_ascii_digit() || (0x0660..=0x0669).contains(&c) || (0x06f0..=0x06f9).contains(&c) {
+		return BidiClass::EN;
+	}
+
+	if ch.is_whitespace() {
+		return BidiClass::WS;
+	}
+
+	if (0x0590..=0x08ff).contains(&c)
+		|| (0xfb1d..=0xfdff).contains(&c)
+		|| (0xfe70..=0xfefe).contains(&c)
+		|| (0x10800..=0x10fff).contains(&c)
+		|| (0x1e800..=0x1efff).contains(&c) {
+		return BidiClass::R;
+	}
+
+	if ch.is_alphanumeric() {
+		return BidiClass::L;
+	}
+
+	return BidiClass::ON;
+
+}
+
+/// if a paragraph is right to left, decided by its first strong character
+pub fn is_rtl(chars: &[char]) -> bool {
+	return chars
+		.iter()
+		.map(|c| bidi_class(*c))
+		.find(|c| *c == BidiClass::L || *c == BidiClass::R)
+		.map(|c| c == BidiClass::R)
+		.unwrap_or(false);
+}
+
+/// resolve embedding levels of a paragraph, odd levels are right to left
+pub fn bidi_levels(chars: &[char]) -> Vec<u8> {
+
+	let rtl = is_rtl(chars);
+	let base = if rtl { BidiClass::R } else { BidiClass::L };
+	let mut classes = chars.iter().map(|c| bidi_class(*c)).collect::<Vec<BidiClass>>();
+	let mut last_strong = base;
+
+	// numbers after left to right text are left to right text
+	for c in &mut classes {
+		match *c {
+			BidiClass::L | BidiClass::R => last_strong = *c,
+			BidiClass::EN if last_strong == BidiClass::L => *c = BidiClass::L,
+			_ => {},
+		}
+	}
+
+	let strong = |c: BidiClass| {
+		return match c {
+			BidiClass::L => Some(BidiClass::L),
+			BidiClass::R | BidiClass::EN => Some(BidiClass::R),
+			_ => None,
+		};
+	};
+
+	// neutrals take the direction around them if both sides agree
+	let mut i = 0;
+
+	while i < classes.len() {
+
+		if strong(classes[i]).is_some() {
+			i += 1;
+			continue;
+		}
+
+		let start = i;
+
+		while i < classes.len() && strong(classes[i]).is_none() {
+			i += 1;
+		}
+
+		let before = if start == 0 { base } else { strong(classes[start - 1]).unwrap_or(base) };
+		let after = classes.get(i).and_then(|c| strong(*c)).unwrap_or(base);
+		let dir = if before == after { before } else { base };
+
+		for c in &mut classes[start..i] {
+			*c = dir;
+		}
+
+	}
+
+	return classes
+		.into_iter()
+		.map(|c| {
+			return match (rtl, c) {
+				(false, BidiClass::L) => 0,
+				(false, BidiClass::R) => 1,
+				(true, BidiClass::R) => 1,
+				_ => 2,
+			};
+		})
+		.collect();
+
+}
+
+/// visual order of a line from its levels, as indices into the line
+pub fn bidi_reorder(levels: &[u8]) -> Vec<usize> {
+
+	let mut order = (0..levels.len()).collect::<Vec<usize>>();
+	let max = levels.iter().cloned().max().unwrap_or(0);
+	let min_odd = levels.iter().cloned().filter(|l| l % 2 == 1).min().unwrap_or(max + 1);
+	let mut level = max;
+
+	// reverse every run at each level from the highest to the lowest odd level
+	while level >= min_odd && level > 0 {
+
+		let mut i = 0;
+
+		while i < order.len() {
+
+			if levels[order[i]] < level {
+				i += 1;
+				continue;
+			}
+
+			let start = i;
+
+			while i < order.len() && levels[order[i]] >= level {
+				i += 1;
+			}
+
+			order[start..i].reverse();
+
+		}
+
+		level -= 1;
+
+	}
+
+	return order;
+
+}
+
+/// mirrored glyph of a character drawn right to left
+pub fn bidi_mirror(ch: char) -> char {
+	return match ch {
+		'(' => ')',
+		')' => '(',
+		'[' => ']',
+		']' => '[',
+		'{' => '}',
+		'}' => '{',
+		'<' => '>',
+		'>' => '<',
+		'«' => '»',
+		'»' => '«',
+		_ => ch,
+	};
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Joining {
+	// joins both sides
+	Dual,
+	// joins to the previous character only
+	Right,
+	// tatweel, joins both sides without changing shape
+	Causing,
+	// marks, ignored when joining
+	Transparent,
+	None,
+}
+
+// isolated presentation form & joining type of arabic letters, the final, initial and medial forms follow the isolated one
+fn arabic_form(ch: char) -> (Option<u32>, Joining) {
+
+	let c = ch as u32;
+
+	if (0x064b..=0x065f).contains(&c) || c == 0x0670 {
+		return (None, Joining::Transparent);
+	}
+
+	if c == 0x0640 {
+		return (None, Joining::Causing);
+	}
+
+	let right = |iso| (Some(iso), Joining::Right);
+	let dual = |iso| (Some(iso), Joining::Dual);
+
+	return match c {
+		0x0621 => (Some(0xfe80), Joining::None),
+		0x0622 => right(0xfe81),
+		0x0623 => right(0xfe83),
+		0x0624 => right(0xfe85),
+		0x0625 => right(0xfe87),
+		0x0626 => dual(0xfe89),
+		0x0627 => right(0xfe8d),
+		0x0628 => dual(0xfe8f),
+		0x0629 => right(0xfe93),
+		0x062a..=0x062e => dual(0xfe95 + (c - 0x062a) * 4),
+		0x062f..=0x0632 => right(0xfea9 + (c - 0x062f) * 2),
+		0x0633..=0x063a => dual(0xfeb1 + (c - 0x0633) * 4),
+		0x0641..=0x0647 => dual(0xfed1 + (c - 0x0641) * 4),
+		0x0648 => right(0xfeed),
+		0x0649 => right(0xfeef),
+		0x064a => dual(0xfef1),
+		_ => (None, Joining::None),
+	};
+
+}
+
+// isolated lam alef ligature, the final form follows
+fn lam_alef(alef: char) -> Option<u32> {
+	return match alef as u32 {
+		0x0622 => Some(0xfef5),
+		0x0623 => Some(0xfef7),
+		0x0625 => Some(0xfef9),
+		0x0627 => Some(0xfefb),
+		_ => None,
+	};
+}
+
+/// replace arabic letters with their contextual presentation forms if the font has them, returns (index of source char, shaped char)
+pub fn shape(chars: &[char], has_glyph: impl Fn(char) -> bool) -> Vec<(usize, char)> {
+
+	let joining = chars.iter().map(|c| arabic_form(*c).1).collect::<Vec<Joining>>();
+
+	let neighbor = |i: usize, forward: bool| {
+		let mut j = i as isize;
+		loop {
+			j += if forward { 1 } else { -1 };
+			if j < 0 || j >= chars.len() as isize {
+				return None;
+			}
+			if joining[j as usize] != Joining::Transparent {
+				return Some(j as usize);
+			}
+		}
+	};
+
+	let joins_prev = |i: usize| {
+		return match joining[i] {
+			Joining::Dual | Joining::Right | Joining::Causing => neighbor(i, false)
+				.map(|p| joining[p] == Joining::Dual || joining[p] == Joining::Causing)
+				.unwrap_or(false),
+			_ => false,
+		};
+	};
+
+	let joins_next = |i: usize| {
+		return match joining[i] {
+			Joining::Dual | Joining::Causing => neighbor(i, true)
+				.map(|n| joining[n] != Joining::None && joining[n] != Joining::Transparent)
+				.unwrap_or(false),
+			_ => false,
+		};
+	};
+
+	let subst = |c: u32, orig: char| {
+		return std::char::from_u32(c)
+			.filter(|c| has_glyph(*c))
+			.unwrap_or(orig);
+	};
+
+	let mut shaped = Vec::with_capacity(chars.len());
+	let mut skip = None;
+
+	for (i, ch) in chars.iter().enumerate() {
+
+		if skip == Some(i) {
+			continue;
+		}
+
+		// lam followed by alef becomes one glyph
+		if *ch == '\u{0644}' {
+			if let Some(next) = neighbor(i, true) {
+				if let Some(lig) = lam_alef(chars[next]) {
+					let lig = lig + if joins_prev(i) { 1 } else { 0 };
+					let c = subst(lig, *ch);
+					if c != *ch {
+						shaped.push((i, c));
+						skip = Some(next);
+						continue;
+					}
+				}
+			}
+		}
+
+		let c = match arabic_form(*ch) {
+			(Some(iso), Joining::Right) => {
+				subst(iso + if joins_prev(i) { 1 } else { 0 }, *ch)
+			},
+			(Some(iso), Joining::Dual) => {
+				let form = match (joins_prev(i), joins_next(i)) {
+					(false, false) => 0,
+					(true, false) => 1,
+					(false, true) => 2,
+					(true, true) => 3,
+				};
+				subst(iso + form, *ch)
+			},
+			_ => *ch,
+		};
+
+		shaped.push((i, c));
+
+	}
+
+	return shaped;
+
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BreakClass {
+	// spaces, break after
+	Space,
+	// zero width space, break after
+	ZeroWidth,
+	// no break space & word joiner, never break around
+	Glue,
+	// combining marks & zero width joiner, never break before
+	Combining,
+	// hyphens & dashes, break after
+	Hyphen,
+	// opening punctuation, never break after
+	Open,
+	// closing punctuation, never break before
+	Close,
+	// ideographs & emoji, break around
+	Ideographic,
+	// everything else
+	Alphabetic,
+}
+
+fn break_class(ch: char) -> BreakClass {
+
+	let c = ch as u32;
+
+	return match ch {
+		' ' | '\t' | '\u{3000}' => BreakClass::Space,
+		'\u{200b}' => BreakClass::ZeroWidth,
+		'\u{a0}' | '\u{2007}' | '\u{202f}' | '\u{2060}' | '\u{feff}' => BreakClass::Glue,
+		'\u{200d}' | '\u{fe0f}' => BreakClass::Combining,
+		'-' | '\u{ad}' | '\u{2010}' | '\u{2012}' | '\u{2013}' | '\u{2014}' => BreakClass::Hyphen,
+		'(' | '[' | '{' | '（' | '「' | '『' | '【' | '《' | '〈' | '〔' | '“' | '‘' => BreakClass::Open,
+		')' | ']' | '}' | ',' | '.' | '!' | '?' | ';' | ':' | '%'
+			| '）' | '」' | '』' | '】' | '》' | '〉' | '〕' | '”' | '’'
+			| '、' | '。' | '，' | '．' | '！' | '？' | '：' | '；' | '・' | 'ー'
+			| 'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'っ' | 'ゃ' | 'ゅ' | 'ょ'
+			| 'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ッ' | 'ャ' | 'ュ' | 'ョ' => BreakClass::Close,
+		_ if (0x0300..=0x036f).contains(&c)
+			|| (0x064b..=0x065f).contains(&c)
+			|| (0x1f3fb..=0x1f3ff).contains(&c) => BreakClass::Combining,
+		_ if (0x2e80..=0x9fff).contains(&c)
+			|| (0xac00..=0xd7af).contains(&c)
+			|| (0xf900..=0xfaff).contains(&c)
+			|| (0xff01..=0xff60).contains(&c)
+			|| (0x1f000..=0x1faff).contains(&c)
+			|| (0x20000..=0x3ffff).contains(&c) => BreakClass::Ideographic,
+		_ => BreakClass::Alphabetic,
+	};
+
+}
+
+/// line break opportunities, if a line can break before each char
+pub fn line_breaks(chars: &[char]) -> Vec<bool> {
+
+	let classes = chars.iter().map(|c| break_class(*c)).collect::<Vec<BreakClass>>();
+
+	return (0..chars.len())
+		.map(|i| {
+
+			if i == 0 {
+				return false;
+			}
+
+			let (prev, cur) = (classes[i - 1], classes[i]);
+
+			return match (prev, cur) {
+				(BreakClass::Glue, _) | (_, BreakClass::Glue) => false,
+				(_, BreakClass::Combining) => false,
+				_ if chars[i - 1] == '\u{200d}' => false,
+				(BreakClass::ZeroWidth, _) => true,
+				(_, BreakClass::Space) | (_, BreakClass::ZeroWidth) => false,
+				(_, BreakClass::Close) => false,
+				(BreakClass::Open, _) => false,
+				(BreakClass::Space, _) => true,
+				(BreakClass::Hyphen, _) => true,
+				(BreakClass::Ideographic, _) | (_, BreakClass::Ideographic) => true,
+				_ => false,
+			};
+
+		})
+		.collect();
+
+}
+
+#[cfg(all(test, not(web)))]
+#[test]
+fn unicode() {
+
+	let chars = |s: &str| s.chars().collect::<Vec<char>>();
+
+	// arabic joining, "سلام" is initial seen, final lam alef ligature, isolated meem
+	let s = chars("سلام");
+	let shaped = shape(&s, |_| true).into_iter().map(|(_, c)| c).collect::<String>();
+	assert_eq!(shaped, "\u{feb3}\u{fefc}\u{fee1}");
+	let shaped = shape(&s, |_| false).into_iter().map(|(_, c)| c).collect::<String>();
+	assert_eq!(shaped, "سلام");
+
+	// dual joining letters alone & right joining letters never join forward
+	assert_eq!(shape(&chars("ب"), |_| true)[0].1, '\u{fe8f}');
+	assert_eq!(shape(&chars("دب"), |_| true).into_iter().map(|(_, c)| c).collect::<String>(), "\u{fea9}\u{fe8f}");
+
+	// hebrew in english keeps its numbers left to right
+	let s = chars("ab שלום 12");
+	let levels = bidi_levels(&s);
+	assert!(!is_rtl(&s));
+	assert_eq!(levels, vec![0, 0, 0, 1, 1, 1, 1, 1, 2, 2]);
+	let visual = bidi_reorder(&levels).into_iter().map(|i| s[i]).collect::<String>();
+	assert_eq!(visual, "ab 12 םולש");
+
+	// english in hebrew
+	let s = chars("שלום abc!");
+	assert!(is_rtl(&s));
+	let visual = bidi_reorder(&bidi_levels(&s)).into_iter().map(|i| s[i]).collect::<String>();
+	assert_eq!(visual, "!abc םולש");
+
+	// words break after spaces, cjk breaks anywhere except before closing punctuation
+	let breaks = line_breaks(&chars("hi there"));
+	assert_eq!(breaks.iter().filter(|b| **b).count(), 1);
+	assert!(breaks[3]);
+	let breaks = line_breaks(&chars("日本語。です"));
+	assert_eq!(breaks, vec![false, true, true, false, true, true]);
+	let breaks = line_breaks(&chars("a\u{a0}b (c)"));
+	assert_eq!(breaks, vec![false, false, false, false, true, false, false]);
+
+}
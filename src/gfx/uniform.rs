@@ -19,6 +19,9 @@ pub enum UniformData {
 	Vec4(Vec4),
 	Int(i32),
 	Mat4(Mat4),
+	Mat4Array(Vec<Mat4>),
 	Texture(Texture),
+	/// a samplerCube
+	CubeTexture(CubeTexture),
 }
 
@@ -100,6 +100,11 @@ impl Image {
 
 	}
 
+	/// raw rgba pixel data
+	pub fn as_raw(&self) -> &[u8] {
+		return &self.data;
+	}
+
 	fn into_image(self) -> Result<image::RgbaImage> {
 		let img: image::RgbaImage = image::ImageBuffer::from_raw(self.width as u32, self.height as u32, self.data)
 			.ok_or_else(|| format!("failed to create image"))?;
@@ -139,6 +144,23 @@ impl Image {
 
 	}
 
+	/// encode as png bytes
+	pub fn to_png(&self) -> Result<Vec<u8>> {
+
+		let mut buf = vec![];
+
+		image::png::PngEncoder::new(&mut buf)
+			.encode(
+				&self.data,
+				self.width as u32,
+				self.height as u32,
+				image::ColorType::Rgba8
+			).map_err(|_| format!("failed to encode png"))?;
+
+		return Ok(buf);
+
+	}
+
 	pub fn into_raw(self) -> Vec<u8> {
 		return self.data;
 	}
@@ -150,7 +172,7 @@ impl Image {
 		for y in 0..self.height {
 			for x in 0..self.width {
 				if let Some(p) = self.get(x, y) {
-					img.set(x, self.height - y, p).ok();
+					img.set(x, self.height - y - 1, p).ok();
 				}
 			}
 		}
@@ -159,5 +181,28 @@ impl Image {
 
 	}
 
-}
+	/// largest difference of any channel of any pixel between 2 images of the same size (0.0 - 1.0)
+	pub fn diff(&self, other: &Self) -> Result<f32> {
+
+		if self.width != other.width || self.height != other.height {
+			return Err(format!(
+				"image size mismatch: {}x{} / {}x{}",
+				self.width,
+				self.height,
+				other.width,
+				other.height,
+			));
+		}
+
+		let max = self.data
+			.iter()
+			.zip(&other.data)
+			.map(|(a, b)| (*a as i32 - *b as i32).abs())
+			.max()
+			.unwrap_or(0);
+
+		return Ok(max as f32 / 255.0);
 
+	}
+
+}
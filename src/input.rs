@@ -95,6 +95,10 @@ pub enum Event {
 	DPIChange(f32),
 	#[cfg(feature = "midi")]
 	MIDI(midi::Msg),
+	#[cfg(feature = "midi")]
+	MIDIConnect(String),
+	#[cfg(feature = "midi")]
+	MIDIDisconnect(String),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -30,9 +30,13 @@ impl Sprite {
 	}
 
 	pub fn from_ase(json: &str) -> Result<Self> {
+		return Ok(Self::from_ase_data(ase::parse(json)?));
+	}
+
+	/// from parsed data, e.g. from [`TextureAtlas::add_ase`](../../gfx/struct.TextureAtlas.html#method.add_ase)
+	pub fn from_ase_data(data: ase::SpriteData) -> Self {
 
 		let mut sprite = Self::new();
-		let data = ase::parse(json)?;
 
 		sprite.frames = data.frames;
 
@@ -44,7 +48,7 @@ impl Sprite {
 			});
 		}
 
-		return Ok(sprite);
+		return sprite;
 
 	}
 
@@ -0,0 +1,267 @@
+// wengwengweng
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use super::*;
+
+/// A Control on a MIDI Device
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Source {
+	/// control change (channel, controller)
+	Control(Channel, i32),
+	/// note velocity, 0 when released (channel, note)
+	Note(Channel, i32),
+	/// pitch bend, mapped from -1.0 - 1.0 to 0.0 - 1.0 (channel)
+	Pitch(Channel),
+	/// channel aftertouch (channel)
+	Pressure(Channel),
+}
+
+impl Source {
+
+	/// get the source and its normalized (0.0 - 1.0) value from a message
+	pub fn from_msg(msg: &Msg) -> Option<(Source, f32)> {
+		return match *msg {
+			Msg::Control(ch, id, val) => Some((Source::Control(ch, id), val)),
+			Msg::NoteOn(ch, n, vel) => Some((Source::Note(ch, n), vel)),
+			Msg::NoteOff(ch, n, _) => Some((Source::Note(ch, n), 0.0)),
+			Msg::Pitch(ch, bend) => Some((Source::Pitch(ch), bend * 0.5 + 0.5)),
+			Msg::ChannelPressure(ch, p) => Some((Source::Pressure(ch), p)),
+			_ => None,
+		};
+	}
+
+}
+
+/// Response Curve
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Curve {
+	Linear,
+	/// raise to a power, > 1.0 for finer control at the low end, < 1.0 for the high end
+	Pow(f32),
+	/// smoothstep
+	Smooth,
+	/// quantize to n evenly spaced steps
+	Step(i32),
+}
+
+impl Curve {
+	pub fn apply(&self, v: f32) -> f32 {
+		let v = v.max(0.0).min(1.0);
+		return match *self {
+			Curve::Linear => v,
+			Curve::Pow(p) => v.powf(p),
+			Curve::Smooth => v * v * (3.0 - 2.0 * v),
+			Curve::Step(n) => {
+				if n <= 1 {
+					0.0
+				} else {
+					let n = (n - 1) as f32;
+					(v * n).round() / n
+				}
+			},
+		};
+	}
+}
+
+/// A Source Bound to a Parameter
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+	pub source: Source,
+	pub min: f32,
+	pub max: f32,
+	pub curve: Curve,
+	pub invert: bool,
+}
+
+impl Binding {
+
+	pub fn new(source: Source) -> Self {
+		return Self {
+			source: source,
+			min: 0.0,
+			max: 1.0,
+			curve: Curve::Linear,
+			invert: false,
+		};
+	}
+
+	pub fn range(mut self, min: f32, max: f32) -> Self {
+		self.min = min;
+		self.max = max;
+		return self;
+	}
+
+	pub fn curve(mut self, c: Curve) -> Self {
+		self.curve = c;
+		return self;
+	}
+
+	pub fn invert(mut self, b: bool) -> Self {
+		self.invert = b;
+		return self;
+	}
+
+	/// map a normalized source value to the parameter range
+	pub fn map(&self, v: f32) -> f32 {
+		let v = if self.invert { 1.0 - v } else { v };
+		return self.min + self.curve.apply(v) * (self.max - self.min);
+	}
+
+}
+
+/// Maps MIDI Controls to Named Parameters
+///
+/// bindings can be saved / loaded with [`data`](../data/index.html) so they can be reconfigured without recompiling
+/// ```no_run
+/// # use dirty::*;
+/// # use midi::*;
+/// let mut mapper = Mapper::new();
+///
+/// mapper.bind("cutoff", Binding::new(Source::Control(0, 74)).range(20.0, 20000.0).curve(Curve::Pow(2.0)));
+/// // bind whatever control is touched next
+/// mapper.learn("resonance");
+///
+/// // in State::event()
+/// # let msg = Msg::Control(0, 71, 0.5);
+/// for (param, val) in mapper.feed(&msg) {
+///     // ...
+/// }
+///
+/// mapper.save("myapp", "midi")?;
+/// # Ok::<(), Error>(())
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Mapper {
+	bindings: HashMap<String, Binding>,
+	#[serde(skip)]
+	values: HashMap<String, f32>,
+	#[serde(skip)]
+	learning: Option<String>,
+}
+
+impl Mapper {
+
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	/// bind a source to a parameter, replacing the previous binding
+	pub fn bind(&mut self, param: &str, b: Binding) {
+		self.bindings.insert(param.to_string(), b);
+	}
+
+	pub fn unbind(&mut self, param: &str) {
+		self.bindings.remove(param);
+	}
+
+	pub fn binding(&self, param: &str) -> Option<&Binding> {
+		return self.bindings.get(param);
+	}
+
+	pub fn binding_mut(&mut self, param: &str) -> Option<&mut Binding> {
+		return self.bindings.get_mut(param);
+	}
+
+	pub fn bindings(&self) -> impl Iterator<Item = (&str, &Binding)> {
+		return self.bindings.iter().map(|(p, b)| (p.as_str(), b));
+	}
+
+	/// bind the next incoming control to a parameter, keeping the range and curve of an existing binding
+	pub fn learn(&mut self, param: &str) {
+		self.learning = Some(param.to_string());
+	}
+
+	pub fn cancel_learn(&mut self) {
+		self.learning = None;
+	}
+
+	/// get the parameter currently waiting for a control
+	pub fn learning(&self) -> Option<&str> {
+		return self.learning.as_deref();
+	}
+
+	/// get the last received value of a parameter
+	pub fn get(&self, param: &str) -> Option<f32> {
+		return self.values.get(param).copied();
+	}
+
+	/// process a message, returns the parameters that changed and their new value
+	pub fn feed(&mut self, msg: &Msg) -> Vec<(String, f32)> {
+
+		let (src, val) = match Source::from_msg(msg) {
+			Some(s) => s,
+			None => return vec![],
+		};
+
+		// releasing a key or centering the bend wheel shouldn't be learnt
+		let active = match msg {
+			Msg::NoteOff(..) => false,
+			// 0x2000 decodes to exactly 0
+			Msg::Pitch(_, bend) if *bend == 0.0 => false,
+			_ => true,
+		};
+
+		if active {
+			if let Some(param) = self.learning.take() {
+				let b = match self.bindings.get(&param) {
+					Some(b) => Binding { source: src, ..*b },
+					None => Binding::new(src),
+				};
+				self.bindings.insert(param, b);
+			}
+		}
+
+		let mut changes = vec![];
+
+		for (param, b) in &self.bindings {
+			if b.source == src {
+				let v = b.map(val);
+				self.values.insert(param.clone(), v);
+				changes.push((param.clone(), v));
+			}
+		}
+
+		return changes;
+
+	}
+
+	/// save bindings with [`data::save`](../data/fn.save.html)
+	pub fn save(&self, proj: &'static str, entry: &'static str) -> Result<()> {
+		return data::save(proj, entry, self);
+	}
+
+	/// load bindings with [`data::load`](../data/fn.load.html)
+	pub fn load(proj: &'static str, entry: &'static str) -> Result<Self> {
+		return data::load(proj, entry);
+	}
+
+}
+
+#[test]
+fn mapper_learn() {
+
+	let mut mapper = Mapper::new();
+
+	mapper.bind("vol", Binding::new(Source::Control(0, 7)).range(0.0, 2.0));
+	mapper.learn("vol");
+
+	assert_eq!(mapper.feed(&Msg::NoteOff(0, 60, 0.0)), vec![]);
+	assert_eq!(mapper.learning(), Some("vol"));
+	assert_eq!(mapper.feed(&Msg::from(&[0xe0, 0x00, 0x40])), vec![]);
+	assert_eq!(mapper.learning(), Some("vol"));
+	assert_eq!(mapper.feed(&Msg::Control(1, 10, 0.5)), vec![(format!("vol"), 1.0)]);
+	assert_eq!(mapper.learning(), None);
+	assert_eq!(mapper.feed(&Msg::Control(0, 7, 1.0)), vec![]);
+	assert_eq!(mapper.get("vol"), Some(1.0));
+
+	let json = data::to_json(&mapper).unwrap();
+	let loaded: Mapper = data::from_json(&json).unwrap();
+
+	assert_eq!(loaded.binding("vol"), Some(&Binding::new(Source::Control(1, 10)).range(0.0, 2.0)));
+	assert_eq!(Curve::Step(3).apply(0.3), 0.5);
+
+}
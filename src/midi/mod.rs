@@ -0,0 +1,632 @@
+// wengwengweng
+
+//! MIDI Input / Output
+//!
+//! incoming messages from connected devices are delivered as [`Event::MIDI`](../input/enum.Event.html), use [`Conf::midi_input`](../conf/struct.Conf.html) to select which input ports to listen to, and [`Output`](struct.Output.html) to send messages
+//!
+//! [`Mapper`](struct.Mapper.html) binds incoming controls to named parameters
+
+// https://ccrma.stanford.edu/~craig/articles/linuxmidi/misc/essenmidi.html
+// https://www.midi.org/specifications-old/item/table-1-summary-of-midi-message
+
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::collections::HashMap;
+
+use crate::*;
+
+export!(map);
+
+const CLIENT_NAME: &str = "dirty_midi";
+const SCAN_INTERVAL: Duration = Duration::from_millis(1000);
+// how often the listener thread checks if it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// MIDI Channel (0 - 15)
+pub type Channel = u8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Msg {
+	/// note off (channel, note, velocity)
+	NoteOff(Channel, i32, f32),
+	/// note on (channel, note, velocity), a note on with 0 velocity is reported as NoteOff
+	NoteOn(Channel, i32, f32),
+	/// polyphonic aftertouch (channel, note, pressure)
+	PolyPressure(Channel, i32, f32),
+	/// control change (channel, controller, value)
+	Control(Channel, i32, f32),
+	/// program change (channel, program)
+	Program(Channel, i32),
+	/// channel aftertouch (channel, pressure)
+	ChannelPressure(Channel, f32),
+	/// pitch bend (channel, bend), bend is in -1.0 - 1.0
+	Pitch(Channel, f32),
+	/// system exclusive, raw bytes excluding the 0xf0 / 0xf7 markers
+	SysEx(Vec<u8>),
+	/// MIDI time code quarter frame
+	TimeCode(u8),
+	/// song position in MIDI beats (16th notes)
+	SongPos(i32),
+	SongSelect(i32),
+	TuneRequest,
+	/// timing clock, sent 24 times per quarter note
+	Clock,
+	Start,
+	Continue,
+	Stop,
+	ActiveSense,
+	Reset,
+	Unknown(Vec<u8>),
+}
+
+fn to_7bit(v: f32) -> u8 {
+	return (v.max(0.0).min(1.0) * 127.0).round() as u8;
+}
+
+fn from_7bit(v: u8) -> f32 {
+	return (v & 0x7f) as f32 / 127.0;
+}
+
+impl Msg {
+
+	pub fn from(msg: &[u8]) -> Msg {
+
+		let first = match msg.get(0) {
+			Some(b) => *b,
+			None => return Msg::Unknown(msg.to_vec()),
+		};
+
+		let ch = first & 0x0f;
+		let second = msg.get(1).map(|b| *b & 0x7f);
+		let third = msg.get(2).map(|b| *b & 0x7f);
+		let data = Option::zip(second, third);
+
+		match first {
+			// note off
+			0x80..=0x8f => {
+				if let Some((note, vel)) = data {
+					return Msg::NoteOff(ch, note as i32, from_7bit(vel));
+				}
+			},
+			// note on
+			0x90..=0x9f => {
+				if let Some((note, vel)) = data {
+					if vel == 0 {
+						return Msg::NoteOff(ch, note as i32, 0.0);
+					}
+					return Msg::NoteOn(ch, note as i32, from_7bit(vel));
+				}
+			},
+			// polyphonic aftertouch
+			0xa0..=0xaf => {
+				if let Some((note, pressure)) = data {
+					return Msg::PolyPressure(ch, note as i32, from_7bit(pressure));
+				}
+			},
+			// continuous
+			0xb0..=0xbf => {
+				if let Some((id, val)) = data {
+					return Msg::Control(ch, id as i32, from_7bit(val));
+				}
+			},
+			// program change
+			0xc0..=0xcf => {
+				if let Some(program) = second {
+					return Msg::Program(ch, program as i32);
+				}
+			},
+			// channel aftertouch
+			0xd0..=0xdf => {
+				if let Some(pressure) = second {
+					return Msg::ChannelPressure(ch, from_7bit(pressure));
+				}
+			},
+			// pitch
+			0xe0..=0xef => {
+				if let Some((lsb, msb)) = data {
+					let val = ((msb as i32) << 7) | lsb as i32;
+					return Msg::Pitch(ch, ((val - 8192) as f32 / 8192.0).max(-1.0));
+				}
+			},
+			// sysex
+			0xf0 => {
+				let end = match msg.last() {
+					Some(&0xf7) if msg.len() >= 2 => msg.len() - 1,
+					_ => msg.len(),
+				};
+				return Msg::SysEx(msg[1..end].to_vec());
+			},
+			0xf1 => {
+				if let Some(val) = second {
+					return Msg::TimeCode(val);
+				}
+			},
+			0xf2 => {
+				if let Some((lsb, msb)) = data {
+					return Msg::SongPos(((msb as i32) << 7) | lsb as i32);
+				}
+			},
+			0xf3 => {
+				if let Some(song) = second {
+					return Msg::SongSelect(song as i32);
+				}
+			},
+			0xf6 => return Msg::TuneRequest,
+			0xf8 => return Msg::Clock,
+			0xfa => return Msg::Start,
+			0xfb => return Msg::Continue,
+			0xfc => return Msg::Stop,
+			0xfe => return Msg::ActiveSense,
+			0xff => return Msg::Reset,
+			_ => {},
+		}
+
+		return Msg::Unknown(msg.to_vec());
+
+	}
+
+	/// encode to raw MIDI bytes
+	pub fn to_bytes(&self) -> Vec<u8> {
+
+		let status = |s: u8, ch: Channel| s | (ch & 0x0f);
+		let note = |n: i32| n.max(0).min(127) as u8;
+
+		return match self {
+			Msg::NoteOff(ch, n, vel) => vec![status(0x80, *ch), note(*n), to_7bit(*vel)],
+			Msg::NoteOn(ch, n, vel) => vec![status(0x90, *ch), note(*n), to_7bit(*vel)],
+			Msg::PolyPressure(ch, n, p) => vec![status(0xa0, *ch), note(*n), to_7bit(*p)],
+			Msg::Control(ch, id, val) => vec![status(0xb0, *ch), note(*id), to_7bit(*val)],
+			Msg::Program(ch, p) => vec![status(0xc0, *ch), note(*p)],
+			Msg::ChannelPressure(ch, p) => vec![status(0xd0, *ch), to_7bit(*p)],
+			Msg::Pitch(ch, bend) => {
+				let val = (bend.max(-1.0).min(1.0) * 8192.0 + 8192.0).round().min(16383.0) as u16;
+				vec![status(0xe0, *ch), (val & 0x7f) as u8, (val >> 7) as u8]
+			},
+			Msg::SysEx(data) => {
+				let mut bytes = Vec::with_capacity(data.len() + 2);
+				bytes.push(0xf0);
+				bytes.extend(data.iter().map(|b| b & 0x7f));
+				bytes.push(0xf7);
+				bytes
+			},
+			Msg::TimeCode(val) => vec![0xf1, val & 0x7f],
+			Msg::SongPos(pos) => {
+				let pos = (*pos).max(0).min(16383) as u16;
+				vec![0xf2, (pos & 0x7f) as u8, (pos >> 7) as u8]
+			},
+			Msg::SongSelect(song) => vec![0xf3, note(*song)],
+			Msg::TuneRequest => vec![0xf6],
+			Msg::Clock => vec![0xf8],
+			Msg::Start => vec![0xfa],
+			Msg::Continue => vec![0xfb],
+			Msg::Stop => vec![0xfc],
+			Msg::ActiveSense => vec![0xfe],
+			Msg::Reset => vec![0xff],
+			Msg::Unknown(bytes) => bytes.clone(),
+		};
+
+	}
+
+	/// get the channel of a channel message
+	pub fn channel(&self) -> Option<Channel> {
+		return match self {
+			Msg::NoteOff(ch, ..)
+				| Msg::NoteOn(ch, ..)
+				| Msg::PolyPressure(ch, ..)
+				| Msg::Control(ch, ..)
+				| Msg::Program(ch, ..)
+				| Msg::ChannelPressure(ch, ..)
+				| Msg::Pitch(ch, ..)
+				=> Some(*ch),
+			_ => None,
+		};
+	}
+
+}
+
+/// get the names of all available MIDI input ports
+pub fn input_ports() -> Result<Vec<String>> {
+
+	let midi_in = midir::MidiInput::new(CLIENT_NAME)
+		.map_err(|_| format!("failed to init midi input"))?;
+
+	return Ok(midi_in
+		.ports()
+		.iter()
+		.filter_map(|p| midi_in.port_name(p).ok())
+		.collect());
+
+}
+
+/// get the names of all available MIDI output ports
+pub fn output_ports() -> Result<Vec<String>> {
+
+	let midi_out = midir::MidiOutput::new(CLIENT_NAME)
+		.map_err(|_| format!("failed to init midi output"))?;
+
+	return Ok(midi_out
+		.ports()
+		.iter()
+		.filter_map(|p| midi_out.port_name(p).ok())
+		.collect());
+
+}
+
+fn connect_input(
+	midi_in: midir::MidiInput,
+	port: &midir::MidiInputPort,
+	port_name: &str,
+	tx: mpsc::Sender<Msg>,
+) -> Result<midir::MidiInputConnection<()>> {
+
+	return midi_in.connect(port, &format!("{} - {}", CLIENT_NAME, port_name), move |_, msg, _| {
+		if tx.send(Msg::from(&msg)).is_err() {
+			elog!("failed to send midi msg");
+		}
+	}, ())
+		.map_err(|_| format!("failed to connect to midi input {}", port_name));
+
+}
+
+/// A Connection to a MIDI Input Port
+pub struct Input {
+	name: String,
+	rx: mpsc::Receiver<Msg>,
+	_conn: midir::MidiInputConnection<()>,
+}
+
+impl Input {
+
+	/// connect to the first input port whose name contains `name`
+	pub fn connect(name: &str) -> Result<Self> {
+
+		let midi_in = midir::MidiInput::new(CLIENT_NAME)
+			.map_err(|_| format!("failed to init midi input"))?;
+
+		let (port, port_name) = midi_in
+			.ports()
+			.into_iter()
+			.filter_map(|p| midi_in.port_name(&p).ok().map(|n| (p, n)))
+			.find(|(_, n)| n.contains(name))
+			.ok_or_else(|| format!("failed to find midi input {}", name))?;
+
+		let (tx, rx) = mpsc::channel();
+		let conn = connect_input(midi_in, &port, &port_name, tx)?;
+
+		return Ok(Self {
+			name: port_name,
+			rx: rx,
+			_conn: conn,
+		});
+
+	}
+
+	/// create a virtual input port that other applications can send to
+	#[cfg(unix)]
+	pub fn new_virtual(name: &str) -> Result<Self> {
+
+		use midir::os::unix::VirtualInput;
+
+		let midi_in = midir::MidiInput::new(name)
+			.map_err(|_| format!("failed to init midi input"))?;
+
+		let (tx, rx) = mpsc::channel();
+
+		let conn = midi_in.create_virtual(name, move |_, msg, _| {
+			if tx.send(Msg::from(&msg)).is_err() {
+				elog!("failed to send midi msg");
+			}
+		}, ())
+			.map_err(|_| format!("failed to create virtual midi input {}", name))?;
+
+		return Ok(Self {
+			name: name.to_string(),
+			rx: rx,
+			_conn: conn,
+		});
+
+	}
+
+	/// name of the connected port
+	pub fn name(&self) -> &str {
+		return &self.name;
+	}
+
+	/// get all received messages since last poll
+	pub fn poll(&self) -> Vec<Msg> {
+		return self.rx.try_iter().collect();
+	}
+
+	/// block until a message arrives or the timeout is reached
+	pub fn recv_timeout(&self, t: Duration) -> Option<Msg> {
+		return self.rx.recv_timeout(t).ok();
+	}
+
+}
+
+/// A Connection to a MIDI Output Port
+pub struct Output {
+	name: String,
+	conn: midir::MidiOutputConnection,
+}
+
+impl Output {
+
+	/// connect to the first output port whose name contains `name`
+	pub fn connect(name: &str) -> Result<Self> {
+
+		let midi_out = midir::MidiOutput::new(CLIENT_NAME)
+			.map_err(|_| format!("failed to init midi output"))?;
+
+		let (port, port_name) = midi_out
+			.ports()
+			.into_iter()
+			.filter_map(|p| midi_out.port_name(&p).ok().map(|n| (p, n)))
+			.find(|(_, n)| n.contains(name))
+			.ok_or_else(|| format!("failed to find midi output {}", name))?;
+
+		let conn = midi_out
+			.connect(&port, &format!("{} - {}", CLIENT_NAME, port_name))
+			.map_err(|_| format!("failed to connect to midi output {}", port_name))?;
+
+		return Ok(Self {
+			name: port_name,
+			conn: conn,
+		});
+
+	}
+
+	/// create a virtual output port that other applications can listen to
+	#[cfg(unix)]
+	pub fn new_virtual(name: &str) -> Result<Self> {
+
+		use midir::os::unix::VirtualOutput;
+
+		let midi_out = midir::MidiOutput::new(name)
+			.map_err(|_| format!("failed to init midi output"))?;
+
+		let conn = midi_out
+			.create_virtual(name)
+			.map_err(|_| format!("failed to create virtual midi output {}", name))?;
+
+		return Ok(Self {
+			name: name.to_string(),
+			conn: conn,
+		});
+
+	}
+
+	/// name of the connected port
+	pub fn name(&self) -> &str {
+		return &self.name;
+	}
+
+	/// send a message
+	pub fn send(&mut self, msg: &Msg) -> Result<()> {
+		return self.conn
+			.send(&msg.to_bytes())
+			.map_err(|_| format!("failed to send midi msg to {}", self.name));
+	}
+
+	pub fn note_on(&mut self, ch: Channel, note: i32, vel: f32) -> Result<()> {
+		return self.send(&Msg::NoteOn(ch, note, vel));
+	}
+
+	pub fn note_off(&mut self, ch: Channel, note: i32) -> Result<()> {
+		return self.send(&Msg::NoteOff(ch, note, 0.0));
+	}
+
+	pub fn control(&mut self, ch: Channel, id: i32, val: f32) -> Result<()> {
+		return self.send(&Msg::Control(ch, id, val));
+	}
+
+	pub fn program(&mut self, ch: Channel, p: i32) -> Result<()> {
+		return self.send(&Msg::Program(ch, p));
+	}
+
+	pub fn pitch(&mut self, ch: Channel, bend: f32) -> Result<()> {
+		return self.send(&Msg::Pitch(ch, bend));
+	}
+
+}
+
+pub(crate) enum Incoming {
+	Msg(Msg),
+	Connect(String),
+	Disconnect(String),
+}
+
+/// Receives Messages & Port Changes from [`listen`](fn.listen.html), the listener thread stops when this is dropped
+pub(crate) struct Listener {
+	rx: mpsc::Receiver<Incoming>,
+	stop: Arc<AtomicBool>,
+}
+
+impl Listener {
+	pub fn try_iter(&self) -> mpsc::TryIter<Incoming> {
+		return self.rx.try_iter();
+	}
+}
+
+impl Drop for Listener {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+	}
+}
+
+// identical devices share a port name, so the nth port with the same name is reported as "name #n"
+fn port_keys(names: &[String]) -> Vec<String> {
+
+	let mut seen: HashMap<&str, usize> = hmap![];
+
+	return names
+		.iter()
+		.map(|n| {
+			let count = seen.entry(n.as_str()).or_insert(0);
+			*count += 1;
+			if *count == 1 {
+				return n.clone();
+			} else {
+				return format!("{} #{}", n, count);
+			}
+		})
+		.collect();
+
+}
+
+/// listen to all input ports that matches the filter (or all ports if none), connecting / disconnecting as devices are plugged / unplugged
+pub(crate) fn listen(filter: Option<String>) -> Result<Listener> {
+
+	let (tx, rx) = mpsc::channel();
+	let stop = Arc::new(AtomicBool::new(false));
+	let thread_stop = stop.clone();
+
+	thread::Builder::new()
+		.name(CLIENT_NAME.to_string())
+		.spawn(move || {
+
+		let scanner = match midir::MidiInput::new(CLIENT_NAME) {
+			Ok(m) => m,
+			Err(_) => {
+				elog!("failed to init midi input");
+				return;
+			},
+		};
+
+		let (msg_tx, msg_rx) = mpsc::channel();
+		let mut conns: HashMap<String, midir::MidiInputConnection<()>> = hmap![];
+		let mut next_scan = Instant::now();
+
+		while !thread_stop.load(Ordering::Relaxed) {
+
+			// rescan on time even if messages keep coming (e.g. clock or active sense)
+			if Instant::now() >= next_scan {
+
+				next_scan = Instant::now() + SCAN_INTERVAL;
+
+				let (ports, names): (Vec<_>, Vec<_>) = scanner
+					.ports()
+					.into_iter()
+					.filter_map(|p| scanner.port_name(&p).ok().map(|n| (p, n)))
+					// skip our own ports
+					.filter(|(_, n)| !n.starts_with(CLIENT_NAME))
+					.filter(|(_, n)| filter.as_ref().map(|f| n.contains(f.as_str())).unwrap_or(true))
+					.unzip();
+
+				let keys = port_keys(&names);
+
+				let lost = conns
+					.keys()
+					.filter(|k| !keys.contains(k))
+					.cloned()
+					.collect::<Vec<String>>();
+
+				for key in lost {
+					conns.remove(&key);
+					if tx.send(Incoming::Disconnect(key)).is_err() {
+						return;
+					}
+				}
+
+				for ((port, name), key) in ports.iter().zip(&names).zip(keys) {
+
+					if conns.contains_key(&key) {
+						continue;
+					}
+
+					let midi_in = match midir::MidiInput::new(CLIENT_NAME) {
+						Ok(m) => m,
+						Err(_) => continue,
+					};
+
+					match connect_input(midi_in, port, name, msg_tx.clone()) {
+						Ok(conn) => {
+							conns.insert(key.clone(), conn);
+							if tx.send(Incoming::Connect(key)).is_err() {
+								return;
+							}
+						},
+						Err(e) => {
+							elog!("{}", e);
+						},
+					}
+
+				}
+
+			}
+
+			let timeout = next_scan
+				.saturating_duration_since(Instant::now())
+				.min(POLL_INTERVAL);
+
+			if let Ok(msg) = msg_rx.recv_timeout(timeout) {
+				if tx.send(Incoming::Msg(msg)).is_err() {
+					return;
+				}
+			}
+
+		}
+
+	}).map_err(|_| format!("failed to spawn midi thread"))?;
+
+	return Ok(Listener {
+		rx: rx,
+		stop: stop,
+	});
+
+}
+
+#[test]
+fn msg_bytes() {
+
+	let msgs = [
+		Msg::NoteOn(3, 60, 1.0),
+		Msg::NoteOff(0, 60, 0.0),
+		Msg::PolyPressure(15, 72, 0.0),
+		Msg::Control(1, 74, 1.0),
+		Msg::Program(9, 12),
+		Msg::ChannelPressure(2, 1.0),
+		Msg::Pitch(4, 0.0),
+		Msg::Pitch(4, -1.0),
+		Msg::SysEx(vec![0x7e, 0x7f, 0x06, 0x01]),
+		Msg::SongPos(1234),
+		Msg::Clock,
+		Msg::Stop,
+	];
+
+	for msg in &msgs {
+		assert_eq!(&Msg::from(&msg.to_bytes()), msg);
+	}
+
+	assert_eq!(Msg::from(&[0x92, 64, 0]), Msg::NoteOff(2, 64, 0.0));
+	assert_eq!(Msg::from(&[0xe0, 0x7f, 0x7f]).channel(), Some(0));
+	assert_eq!(Msg::from(&[]), Msg::Unknown(vec![]));
+
+	let names = ["a", "b", "a"].iter().map(|n| n.to_string()).collect::<Vec<String>>();
+	assert_eq!(port_keys(&names), vec!["a", "b", "a #2"]);
+
+}
+
+// requires an ALSA sequencer, run with "cargo test --features midi -- --ignored"
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore]
+fn virtual_loopback() {
+
+	let mut output = Output::new_virtual("dirty_loopback").unwrap();
+	let input = Input::connect("dirty_loopback").unwrap();
+
+	output.note_on(0, 60, 1.0).unwrap();
+	output.control(5, 7, 0.0).unwrap();
+
+	let t = Duration::from_millis(500);
+
+	assert_eq!(input.recv_timeout(t), Some(Msg::NoteOn(0, 60, 1.0)));
+	assert_eq!(input.recv_timeout(t), Some(Msg::Control(5, 7, 0.0)));
+
+}
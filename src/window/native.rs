@@ -33,6 +33,8 @@ pub struct Window {
 	quit: bool,
 	#[cfg(not(mobile))]
 	gamepad_ctx: gilrs::Gilrs,
+	#[cfg(feature = "midi")]
+	midi_input: Option<String>,
 }
 
 impl Window {
@@ -109,6 +111,8 @@ impl Window {
 			#[cfg(not(mobile))]
 			gamepad_ctx: gilrs::Gilrs::new()
 				.map_err(|_| format!("failed to create gamepad context"))?,
+			#[cfg(feature = "midi")]
+			midi_input: conf.midi_input.clone(),
 		});
 
 	}
@@ -290,7 +294,7 @@ impl Window {
 	) -> Result<()> {
 
 		#[cfg(feature = "midi")]
-		let midi_rx = midi::listen()?;
+		let midi_rx = midi::listen(self.midi_input.clone())?;
 
 		use glutin::event_loop::ControlFlow;
 
@@ -327,7 +331,11 @@ impl Window {
 
 				#[cfg(feature = "midi")]
 				for msg in midi_rx.try_iter() {
-					events.push(Event::MIDI(msg.clone()));
+					events.push(match msg {
+						midi::Incoming::Msg(msg) => Event::MIDI(msg),
+						midi::Incoming::Connect(name) => Event::MIDIConnect(name),
+						midi::Incoming::Disconnect(name) => Event::MIDIDisconnect(name),
+					});
 				}
 
 				match e {